@@ -5,6 +5,11 @@ use std::process::Command;
 fn main() {
   println!("cargo:rustc-env=MACOSX_DEPLOYMENT_TARGET=14.2");
 
+  // the pure-Rust backend doesn't link libsecp256k1
+  if env::var("CARGO_FEATURE_PURE_RUST").is_ok() {
+    return;
+  }
+
   let cargo_target_dir: String =
     env::var("CARGO_TARGET_DIR")
       .unwrap_or_else(|_| "target".to_string());
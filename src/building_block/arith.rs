@@ -0,0 +1,255 @@
+// 256-bit unsigned integers as 4 little-endian 64-bit limbs
+pub type U256 = [u64; 4];
+
+pub const ZERO: U256 = [0; 4];
+pub const ONE: U256 = [1, 0, 0, 0];
+
+pub fn is_zero(a: &U256) -> bool {
+  a.iter().all(|&x| x == 0)
+}
+
+pub fn gte(a: &U256, b: &U256) -> bool {
+  for i in (0..4).rev() {
+    if a[i] != b[i] {
+      return a[i] > b[i];
+    }
+  }
+  true
+}
+
+pub fn add(a: &U256, b: &U256) -> (U256, bool) {
+  let mut r = ZERO;
+  let mut carry = 0u128;
+  for i in 0..4 {
+    let t = a[i] as u128 + b[i] as u128 + carry;
+    r[i] = t as u64;
+    carry = t >> 64;
+  }
+  (r, carry != 0)
+}
+
+pub fn sub(a: &U256, b: &U256) -> (U256, bool) {
+  let mut r = ZERO;
+  let mut borrow = false;
+  for i in 0..4 {
+    let (t, b1) = a[i].overflowing_sub(b[i]);
+    let (t, b2) = t.overflowing_sub(borrow as u64);
+    r[i] = t;
+    borrow = b1 || b2;
+  }
+  (r, borrow)
+}
+
+pub fn mul_wide(a: &U256, b: &U256) -> [u64; 8] {
+  let mut r = [0u64; 8];
+  for i in 0..4 {
+    let mut carry = 0u128;
+    for j in 0..4 {
+      let t = a[i] as u128 * b[j] as u128 + r[i + j] as u128 + carry;
+      r[i + j] = t as u64;
+      carry = t >> 64;
+    }
+    r[i + 4] = carry as u64;
+  }
+  r
+}
+
+// (a + 2^256 * carry) / 2
+fn shr1(a: &U256, carry: bool) -> U256 {
+  let mut r = ZERO;
+  for i in 0..4 {
+    let hi = if i == 3 { carry as u64 } else { a[i + 1] & 1 };
+    r[i] = (a[i] >> 1) | (hi << 63);
+  }
+  r
+}
+
+pub fn bit(a: &U256, i: usize) -> bool {
+  (a[i / 64] >> (i % 64)) & 1 == 1
+}
+
+pub fn from_b32(buf: &[u8; 32]) -> U256 {
+  let mut r = ZERO;
+  for (i, limb) in r.iter_mut().enumerate() {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&buf[(3 - i) * 8..(4 - i) * 8]);
+    *limb = u64::from_be_bytes(bytes);
+  }
+  r
+}
+
+pub fn to_b32(a: &U256) -> [u8; 32] {
+  let mut buf = [0u8; 32];
+  for (i, limb) in a.iter().enumerate() {
+    buf[(3 - i) * 8..(4 - i) * 8].copy_from_slice(&limb.to_be_bytes());
+  }
+  buf
+}
+
+// arithmetic modulo m where 2^255 < m < 2^256, i.e. 2^256 = c (mod m)
+// with c < 2^255. the smaller c is, the fewer folds reduce_wide needs.
+// c is below 2^129 for secp256k1 and below 2^224 for P-256
+pub struct Modulus {
+  pub m: U256,
+  pub c: U256, // 2^256 - m
+}
+
+impl Modulus {
+  // brings any 256-bit value into [0, m). m > 2^255 so one subtraction is enough
+  pub fn reduce(&self, a: &U256) -> U256 {
+    if gte(a, &self.m) { sub(a, &self.m).0 } else { *a }
+  }
+
+  pub fn reduce_wide(&self, w: &[u64; 8]) -> U256 {
+    let mut lo: U256 = [w[0], w[1], w[2], w[3]];
+    let mut hi: U256 = [w[4], w[5], w[6], w[7]];
+
+    // fold the upper half back using 2^256 = c
+    while !is_zero(&hi) {
+      let t = mul_wide(&hi, &self.c);
+      let (s, carry) = add(&lo, &[t[0], t[1], t[2], t[3]]);
+      lo = s;
+      hi = add(&[t[4], t[5], t[6], t[7]], &[carry as u64, 0, 0, 0]).0;
+    }
+    self.reduce(&lo)
+  }
+
+  pub fn add(&self, a: &U256, b: &U256) -> U256 {
+    let (s, carry) = add(&self.reduce(a), &self.reduce(b));
+    if carry || gte(&s, &self.m) { sub(&s, &self.m).0 } else { s }
+  }
+
+  pub fn sub(&self, a: &U256, b: &U256) -> U256 {
+    let (d, borrow) = sub(&self.reduce(a), &self.reduce(b));
+    if borrow { add(&d, &self.m).0 } else { d }
+  }
+
+  pub fn neg(&self, a: &U256) -> U256 {
+    self.sub(&ZERO, a)
+  }
+
+  pub fn mul(&self, a: &U256, b: &U256) -> U256 {
+    self.reduce_wide(&mul_wide(a, b))
+  }
+
+  pub fn sq(&self, a: &U256) -> U256 {
+    self.mul(a, a)
+  }
+
+  pub fn pow(&self, a: &U256, e: &U256) -> U256 {
+    let mut r = ONE;
+    for i in (0..256).rev() {
+      r = self.sq(&r);
+      if bit(e, i) {
+        r = self.mul(&r, a);
+      }
+    }
+    r
+  }
+
+  // x / 2 mod m
+  fn half(&self, x: &U256) -> U256 {
+    if x[0] & 1 == 0 {
+      shr1(x, false)
+    } else {
+      let (s, carry) = add(x, &self.m);
+      shr1(&s, carry)
+    }
+  }
+
+  // variable-time binary extended euclidean algorithm. returns 0 for 0
+  pub fn inv(&self, a: &U256) -> U256 {
+    let mut u = self.reduce(a);
+    if is_zero(&u) {
+      return ZERO;
+    }
+    let mut v = self.m;
+    let mut x1 = ONE;
+    let mut x2 = ZERO;
+
+    while u != ONE && v != ONE {
+      while u[0] & 1 == 0 {
+        u = shr1(&u, false);
+        x1 = self.half(&x1);
+      }
+      while v[0] & 1 == 0 {
+        v = shr1(&v, false);
+        x2 = self.half(&x2);
+      }
+      if gte(&u, &v) {
+        u = sub(&u, &v).0;
+        x1 = self.sub(&x1, &x2);
+      } else {
+        v = sub(&v, &u).0;
+        x2 = self.sub(&x2, &x1);
+      }
+    }
+    if u == ONE { x1 } else { x2 }
+  }
+}
+
+// secp256k1 field prime p = 2^256 - 2^32 - 977
+pub const P: Modulus = Modulus {
+  m: [
+    0xFFFFFFFEFFFFFC2F,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+  ],
+  c: [0x1000003D1, 0, 0, 0],
+};
+
+// secp256k1 group order n
+pub const N: Modulus = Modulus {
+  m: [
+    0xBFD25E8CD0364141,
+    0xBAAEDCE6AF48A03B,
+    0xFFFFFFFFFFFFFFFE,
+    0xFFFFFFFFFFFFFFFF,
+  ],
+  c: [
+    0x402DA1732FC9BEBF,
+    0x4551231950B75FC4,
+    0x1,
+    0x0,
+  ],
+};
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_modulus_constants() {
+    for modulus in [&P, &N] {
+      let (sum, carry) = add(&modulus.m, &modulus.c);
+      assert!(carry);
+      assert!(is_zero(&sum));
+    }
+  }
+
+  #[test]
+  fn test_inv() {
+    for modulus in [&P, &N] {
+      let a = [0x1234, 0x5678, 0x9abc, 0xdef0];
+      let a_inv = modulus.inv(&a);
+      assert_eq!(modulus.mul(&a, &a_inv), ONE);
+
+      // matches fermat's little theorem
+      let e = sub(&modulus.m, &[2, 0, 0, 0]).0;
+      assert_eq!(modulus.pow(&a, &e), a_inv);
+    }
+  }
+
+  #[test]
+  fn test_b32_roundtrip() {
+    let mut buf = [0u8; 32];
+    for (i, b) in buf.iter_mut().enumerate() {
+      *b = i as u8;
+    }
+    let a = from_b32(&buf);
+    assert_eq!(a[3], 0x0001020304050607);
+    assert_eq!(a[0], 0x18191a1b1c1d1e1f);
+    assert_eq!(to_b32(&a), buf);
+  }
+}
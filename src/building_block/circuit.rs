@@ -12,6 +12,11 @@ use crate::building_block::{
   wire_label::WireLabel,
   wires::Wires,
 };
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
 
 #[derive(Debug)]
 pub struct Circuit {
@@ -22,28 +27,34 @@ pub struct Circuit {
   gates: Gates,
 }
 
-impl Circuit {
+// state shared by the gates while garbling a circuit
+struct Garbler<'a, R: RngCore + CryptoRng> {
+  gates: Gates,
+  wires: Wires,
+  input_wires: Vec<usize>,
+  rng: &'a mut R,
+}
+
+impl<'a, R: RngCore + CryptoRng> Garbler<'a, R> {
   fn gen_leaf_gate(
+    &mut self,
     gate_type: &GateType,
     out_wire: usize,
     left_wire: usize,
     right_wire: usize,
-    gates: &mut Gates,
-    wires: &mut Wires,
-    input_wires: &mut Vec<usize>,
   ) -> usize {
     let garbled_table = GarbledTable::new(
-      gates.next_index(),
+      self.gates.next_index(),
       out_wire,
       left_wire,
       right_wire,
       GateType::func(gate_type),
-      wires,
+      &self.wires,
     );
-    input_wires.push(left_wire);
-    input_wires.push(right_wire);
+    self.input_wires.push(left_wire);
+    self.input_wires.push(right_wire);
 
-    gates.create(
+    self.gates.create(
       gate_type,
       out_wire,
       left_wire,
@@ -53,42 +64,25 @@ impl Circuit {
   }
 
   fn gen_internal_gate(
+    &mut self,
     gate_type: &GateType,
-    K: usize,
     left_model: &GateModel,
     right_model: &GateModel,
     out_wire: usize,
     left_wire: usize,
     right_wire: usize,
-    gates: &mut Gates,
-    wires: &mut Wires,
-    input_wires: &mut Vec<usize>,
   ) -> usize {
-    Self::build(
-      K,
-      left_model,
-      left_wire,
-      gates,
-      wires,
-      input_wires,
-    );
-    Self::build(
-      K,
-      right_model,
-      right_wire,
-      gates,
-      wires,
-      input_wires,
-    );
+    self.build(left_model, left_wire);
+    self.build(right_model, right_wire);
     let garbled_table = GarbledTable::new(
-      gates.next_index(),
+      self.gates.next_index(),
       out_wire,
       left_wire,
       right_wire,
-      GateType::func(&gate_type),
-      wires,
+      GateType::func(gate_type),
+      &self.wires,
     );
-    gates.create(
+    self.gates.create(
       gate_type,
       out_wire,
       left_wire,
@@ -98,74 +92,59 @@ impl Circuit {
   }
 
   fn build(
-    K: usize,
+    &mut self,
     gate_model: &GateModel,
     out_wire: usize,
-    gates: &mut Gates,
-    wires: &mut Wires,
-    input_wires: &mut Vec<usize>,
   ) -> usize {
-    let left_wire = wires.create(false);
-    let right_wire = wires.create(false);
+    let left_wire = self.wires.create_with_rng(false, self.rng);
+    let right_wire = self.wires.create_with_rng(false, self.rng);
 
     match gate_model {
       // Internal And
       GateModel::And(GateModelBody::Models(left_model, right_model)) => {
-        Self::gen_internal_gate(
+        self.gen_internal_gate(
           &GateType::And,
-          K,
           left_model,
           right_model,
           out_wire,
           left_wire,
           right_wire,
-          gates,
-          wires,
-          input_wires,
         )
       },
       // Leaf And
       GateModel::And(GateModelBody::Values) => {
-        Self::gen_leaf_gate(
+        self.gen_leaf_gate(
           &GateType::And,
           out_wire,
           left_wire,
           right_wire,
-          gates,
-          wires,
-          input_wires,
         )
       },
       // Internal Or
       GateModel::Or(GateModelBody::Models(left_model, right_model)) => {
-        Self::gen_internal_gate(
+        self.gen_internal_gate(
           &GateType::Or,
-          K,
           left_model,
           right_model,
           out_wire,
           left_wire,
           right_wire,
-          gates,
-          wires,
-          input_wires,
         )
       },
       // Leaf Or
       GateModel::Or(GateModelBody::Values) => {
-        Self::gen_leaf_gate(
+        self.gen_leaf_gate(
           &GateType::Or,
           out_wire,
           left_wire,
           right_wire,
-          gates,
-          wires,
-          input_wires,
         )
       },
     }
   }
+}
 
+impl Circuit {
   pub fn get_input_wire(&self, index: usize) -> &Wire {
     let wire_index = self.input_wires[index];
     self.wires.get(wire_index)
@@ -218,19 +197,28 @@ impl Circuit {
     root_gate_model: &GateModel,
     K: usize,
   ) -> Self {
-    let mut gates = Gates::new();
+    Circuit::new_with_rng(root_gate_model, K, &mut OsRng)
+  }
+
+  pub fn new_with_rng(
+    root_gate_model: &GateModel,
+    K: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Self {
     let mut wires = Wires::new(K);
-    let mut input_wires = Vec::<usize>::new();
-    let root_out_wire = wires.create(false);
+    let root_out_wire = wires.create_with_rng(false, rng);
+    let mut garbler = Garbler {
+      gates: Gates::new(),
+      wires,
+      input_wires: Vec::<usize>::new(),
+      rng,
+    };
 
-    let root_gate_index = Self::build(
-      K,
+    let root_gate_index = garbler.build(
       root_gate_model,
       root_out_wire,
-      &mut gates,
-      &mut wires,
-      &mut input_wires,
     );
+    let Garbler { gates, mut wires, input_wires, .. } = garbler;
 
     let output_decoding_table = OutputDecodingTable::new(
       root_out_wire,
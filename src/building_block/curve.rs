@@ -0,0 +1,237 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+use std::{
+  fmt::Debug,
+  iter::Sum,
+  ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+use rand::{
+  CryptoRng,
+  RngCore,
+};
+use rug::{
+  integer::Order,
+  ops::RemRounding,
+  Integer,
+};
+use serde::{
+  de::DeserializeOwned,
+  Serialize,
+};
+use zeroize::Zeroize;
+
+// element of Z_n where n is the order of the group of a curve
+pub trait CurveScalar:
+  Copy + Debug + Eq + Send + Sync + 'static
+  + Serialize + DeserializeOwned + Zeroize
+  + From<u64>
+  + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+  + AddAssign + SubAssign + MulAssign
+  + Sum + for<'a> Sum<&'a Self>
+{
+  fn order() -> Integer;
+
+  fn zero() -> Self;
+
+  fn is_zero(&self) -> bool;
+
+  // returns 0 for 0
+  fn inv(&self) -> Self;
+
+  fn rand_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self;
+
+  // 32-byte big-endian
+  fn to_bytes(&self) -> [u8; 32];
+
+  // 32-byte big-endian x mod n, and whether x was not less than n
+  fn from_bytes_reduced(buf: &[u8; 32]) -> (Self, bool);
+
+  // fails instead of reducing if x is not less than n
+  fn from_bytes_checked(buf: &[u8; 32]) -> Result<Self, String> {
+    match Self::from_bytes_reduced(buf) {
+      (s, false) => Ok(s),
+      (_, true) => Err("Serialized Scalar is not less than the group order".to_string()),
+    }
+  }
+
+  fn to_integer(&self) -> Integer {
+    Integer::from_digits(&self.to_bytes(), Order::MsfBe)
+  }
+
+  // i mod n. negative values are mapped to n - (|i| mod n)
+  fn from_integer(i: &Integer) -> Self {
+    let i = i.clone().rem_euc(Self::order());
+    let digits = i.to_digits::<u8>(Order::MsfBe);
+    let mut buf = [0u8; 32];
+    buf[32 - digits.len()..].copy_from_slice(&digits);
+    Self::from_bytes_reduced(&buf).0
+  }
+
+  // inverts all scalars with a single inversion using Montgomery's trick.
+  // zeros are mapped to zero as inv() does
+  fn batch_inv(xs: &[Self]) -> Vec<Self> {
+    // prods[i] = product of the non-zero scalars in xs[..i]
+    let mut prods = Vec::with_capacity(xs.len());
+    let mut acc = Self::from(1u64);
+    for x in xs {
+      prods.push(acc);
+      if !x.is_zero() {
+        acc *= *x;
+      }
+    }
+
+    let mut acc_inv = acc.inv();
+    let mut invs = vec![Self::zero(); xs.len()];
+    for (i, x) in xs.iter().enumerate().rev() {
+      if x.is_zero() {
+        continue;
+      }
+      invs[i] = acc_inv * prods[i];
+      acc_inv *= *x;
+    }
+    invs
+  }
+}
+
+// element of the group of a curve in any coordinate system
+pub trait CurvePoint:
+  Copy + Debug + Eq + Send + Sync + 'static
+  + Serialize + DeserializeOwned
+  + Add<Output = Self> + Sub<Output = Self> + Neg<Output = Self>
+  + AddAssign + SubAssign
+  + Sum + for<'a> Sum<&'a Self>
+{
+  fn identity() -> Self;
+
+  fn is_identity(&self) -> bool;
+
+  // 02/03 || x if compressed, 04 || x || y otherwise
+  fn to_sec1(self, compressed: bool) -> Vec<u8>;
+
+  // rejects the point at infinity and points not on the curve
+  fn from_sec1(buf: &[u8]) -> Result<Self, String>;
+}
+
+// prime-order short Weierstrass curve the protocols run on
+pub trait Curve: Copy + Clone + Debug + Eq + Send + Sync + 'static {
+  type Scalar: CurveScalar;
+  type Point: CurvePoint + Mul<Self::Scalar, Output = Self::Point>;
+
+  // RFC 9380 suite ID of hash_to_curve
+  const SUITE_ID: &'static str;
+
+  fn order() -> Integer {
+    Self::Scalar::order()
+  }
+
+  fn generator() -> Self::Point;
+
+  // mul_base, multi_mul and the * operator of Point are variable-time and
+  // only for public scalars
+
+  fn mul_base(k: &Self::Scalar) -> Self::Point {
+    Self::generator() * *k
+  }
+
+  // G * k for secret k with group operations and table accesses that don't
+  // depend on k. the pure-Rust secp256k1 backend and P-256 are still not
+  // constant time as their field arithmetic is not
+  fn mul_base_secret(k: &Self::Scalar) -> Self::Point;
+
+  // p * k for secret k. see mul_base_secret
+  fn mul_secret(p: &Self::Point, k: &Self::Scalar) -> Self::Point;
+
+  // sum of p_i * k_i
+  fn multi_mul(terms: &[(Self::Point, Self::Scalar)]) -> Self::Point {
+    terms.iter().map(|(p, k)| *p * *k).sum()
+  }
+
+  // affine x-coordinate of p mod n, i.e. r of ECDSA. 0 for the point at infinity
+  fn x_to_scalar(p: &Self::Point) -> Self::Scalar;
+
+  fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<Self::Point, String>;
+
+  // recovery id of R of ECDSA. bit 0 is the parity of the affine y-coordinate
+  // and bit 1 is set if the affine x-coordinate is not less than n
+  fn recovery_id(p: &Self::Point) -> u8 {
+    let sec1 = p.to_sec1(true);
+    let x = Integer::from_digits(&sec1[1..], Order::MsfBe);
+    (sec1[0] & 1) | (((x >= Self::order()) as u8) << 1)
+  }
+
+  // R of ECDSA from r and the recovery id of R
+  fn recover_R(r: &Self::Scalar, recovery_id: u8) -> Result<Self::Point, String> {
+    if recovery_id > 3 {
+      return Err(format!("Invalid recovery id {}", recovery_id));
+    }
+    let mut x = r.to_integer();
+    if recovery_id & 2 != 0 {
+      x += Self::order();
+    }
+    let digits = x.to_digits::<u8>(Order::MsfBe);
+    if digits.len() > 32 {
+      return Err("x-coordinate of R is out of range".to_string());
+    }
+    let mut sec1 = vec![0u8; 33];
+    sec1[0] = 0x02 | (recovery_id & 1);
+    sec1[33 - digits.len()..].copy_from_slice(&digits);
+    Self::Point::from_sec1(&sec1)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::{
+    p256::curve::P256,
+    secp256k1::curve::Secp256k1,
+  };
+  use rand::rngs::OsRng;
+
+  fn check_curve<C: Curve>() {
+    let n = C::order();
+    let five = C::Scalar::from(5u64);
+    assert_eq!(C::Scalar::from_integer(&(n.clone() + 5)), five);
+    assert_eq!(C::Scalar::from_integer(&Integer::from(-1)), -C::Scalar::from(1u64));
+    assert_eq!(five.to_integer(), 5);
+
+    let xs = [five, C::Scalar::zero(), C::Scalar::rand_with_rng(&mut OsRng)];
+    let invs = C::Scalar::batch_inv(&xs);
+    assert_eq!(invs[0], five.inv());
+    assert!(invs[1].is_zero());
+    assert_eq!(invs[2] * xs[2], C::Scalar::from(1u64));
+
+    let n_bytes: [u8; 32] = n.to_digits::<u8>(Order::MsfBe).try_into().unwrap();
+    assert!(C::Scalar::from_bytes_checked(&n_bytes).is_err());
+
+    let g = C::generator();
+    let p = C::mul_base(&xs[2]);
+    assert_eq!(p, g * xs[2]);
+    assert_eq!(C::mul_base_secret(&xs[2]), p);
+    assert_eq!(C::mul_secret(&p, &five), p * five);
+    assert!(C::mul_secret(&g, &C::Scalar::zero()).is_identity());
+    assert_eq!(C::multi_mul(&[(g, five), (p, five)]), (g + p) * five);
+    assert_eq!(p - g, p + -g);
+    assert!(C::Point::identity().is_identity());
+    assert_eq!(C::Point::from_sec1(&p.to_sec1(true)).unwrap(), p);
+
+    let recovery_id = C::recovery_id(&p);
+    assert_eq!(C::recover_R(&C::x_to_scalar(&p), recovery_id).unwrap(), p);
+    assert_eq!(C::recover_R(&C::x_to_scalar(&p), recovery_id ^ 1).unwrap(), -p);
+    assert!(C::recover_R(&five, 4).is_err());
+
+    let bin = bincode::serialize(&p).unwrap();
+    assert_eq!(bincode::deserialize::<C::Point>(&bin).unwrap(), p);
+  }
+
+  #[test]
+  fn test_secp256k1() {
+    check_curve::<Secp256k1>();
+  }
+
+  #[test]
+  fn test_p256() {
+    check_curve::<P256>();
+  }
+}
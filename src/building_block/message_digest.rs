@@ -0,0 +1,79 @@
+#![allow(dead_code)]
+
+use rug::{
+  integer::Order,
+  Integer,
+};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use crate::building_block::curve::CurveScalar;
+
+// hash function that maps a message to m of ECDSA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDigest {
+  Sha256,
+  DoubleSha256, // Bitcoin
+  Keccak256, // Ethereum
+  Prehashed, // message is a digest computed by the caller
+}
+
+impl MessageDigest {
+  pub fn digest(&self, msg: &[u8]) -> Vec<u8> {
+    match self {
+      MessageDigest::Sha256 => Sha256::digest(msg).to_vec(),
+      MessageDigest::DoubleSha256 => Sha256::digest(Sha256::digest(msg)).to_vec(),
+      MessageDigest::Keccak256 => Keccak256::digest(msg).to_vec(),
+      MessageDigest::Prehashed => msg.to_vec(),
+    }
+  }
+
+  // m = bits2int(digest(msg)) mod n
+  pub fn to_scalar<S: CurveScalar>(self, msg: &[u8]) -> S {
+    bits2int(&self.digest(msg))
+  }
+}
+
+// bits2int of RFC 6979 section 2.3.2 reduced mod n, i.e. the leftmost
+// bit length of n bits of the digest as an integer mod n
+pub fn bits2int<S: CurveScalar>(digest: &[u8]) -> S {
+  let mut x = Integer::from_digits(digest, Order::MsfBe);
+  let digest_bits = digest.len() as u32 * 8;
+  let n_bits = S::order().significant_bits();
+  if digest_bits > n_bits {
+    x >>= digest_bits - n_bits;
+  }
+  S::from_integer(&x)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::secp256k1::scalar::Scalar;
+  use sha2::Sha512;
+
+  #[test]
+  fn test_digests() {
+    let to_hex = |d: MessageDigest| hex::encode(d.digest(b"abc"));
+    assert_eq!(to_hex(MessageDigest::Sha256), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(to_hex(MessageDigest::DoubleSha256), "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358");
+    assert_eq!(to_hex(MessageDigest::Keccak256), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+    assert_eq!(to_hex(MessageDigest::Prehashed), "616263");
+  }
+
+  #[test]
+  fn test_bits2int() {
+    // digests of 256 bits or less are taken as they are
+    let m: Scalar = MessageDigest::Prehashed.to_scalar(&[1, 2]);
+    assert_eq!(m, Scalar::from(0x0102u32));
+
+    // wider digests are truncated to the leftmost 256 bits
+    let digest = Sha512::digest(b"abc");
+    let m: Scalar = MessageDigest::Prehashed.to_scalar(&digest);
+    let head: Scalar = bits2int(&digest[..32]);
+    assert_eq!(m, head);
+
+    // and the result is reduced mod n
+    let m: Scalar = bits2int(&[0xff; 32]);
+    assert_eq!(m.to_integer(), Integer::from_digits(&[0xffu8; 32], Order::MsfBe) - Scalar::order());
+  }
+}
@@ -1,12 +1,17 @@
+pub mod arith;
 pub mod circuit;
+pub mod curve;
 pub mod gate;
 pub mod gates;
 pub mod gate_model;
 pub mod garbled_table;
 pub mod gate_type;
+pub mod message_digest;
 pub mod ot;
 pub mod output_decoding_table;
+pub mod p256;
 pub mod secp256k1;
+pub mod serde_util;
 pub mod wire;
 pub mod wires;
 pub mod wire_assignment;
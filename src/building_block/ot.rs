@@ -1,3 +1,8 @@
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
 use rsa::{
   Pkcs1v15Encrypt,
   RsaPrivateKey as PrivKey,
@@ -34,11 +39,14 @@ impl EncryptedWireLabels {
 
 impl OT {
   pub fn gen_keys(bits: usize) -> OTKeys {
-    let mut rng = rand::thread_rng();
-    let sk = PrivKey::new(&mut rng, bits).expect("Failed to generate a private key");
+    OT::gen_keys_with_rng(bits, &mut OsRng)
+  }
+
+  pub fn gen_keys_with_rng(bits: usize, rng: &mut (impl RngCore + CryptoRng)) -> OTKeys {
+    let sk = PrivKey::new(rng, bits).expect("Failed to generate a private key");
     let pk = PubKey::from(&sk);
     let pk_prime = {
-      let sk = PrivKey::new(&mut rng, bits).expect("Failed to generate a private key");
+      let sk = PrivKey::new(rng, bits).expect("Failed to generate a private key");
       PubKey::from(&sk)
     };
     OTKeys {
@@ -53,19 +61,26 @@ impl OT {
     false_pub_key: &PubKey,
     wire: &Wire,
   ) -> EncryptedWireLabels {
-    let mut rng = rand::thread_rng();
+    OT::encrypt_wire_labels_with_rng(true_pub_key, false_pub_key, wire, &mut OsRng)
+  }
 
+  pub fn encrypt_wire_labels_with_rng(
+    true_pub_key: &PubKey,
+    false_pub_key: &PubKey,
+    wire: &Wire,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> EncryptedWireLabels {
     let true_wire_label = bincode::serialize(wire.get_label(true)).unwrap();
     let false_wire_label = bincode::serialize(wire.get_label(false)).unwrap();
 
     let enc_true_wire_label =
       true_pub_key
-        .encrypt(&mut rng, Pkcs1v15Encrypt, &true_wire_label)
+        .encrypt(rng, Pkcs1v15Encrypt, &true_wire_label)
         .expect("Failed to encrypt false key");
 
     let enc_false_wire_label = 
       false_pub_key
-        .encrypt(&mut rng, Pkcs1v15Encrypt, &false_wire_label)
+        .encrypt(rng, Pkcs1v15Encrypt, &false_wire_label)
         .expect("Failed to encrypt false key");
 
     EncryptedWireLabels::new(enc_true_wire_label, enc_false_wire_label)
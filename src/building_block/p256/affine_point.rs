@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use crate::building_block::{
+  p256::{
+    field::Field,
+    jacobian_point::JacobianPoint,
+  },
+  serde_util,
+};
+use serde::{
+  de,
+  Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AffinePoint {
+  pub(crate) x: Field,
+  pub(crate) y: Field,
+  pub(crate) infinity: bool,
+}
+
+impl AffinePoint {
+  pub fn new() -> Self { // returns point at infinity
+    AffinePoint {
+      x: Field::from(0u32),
+      y: Field::from(0u32),
+      infinity: true,
+    }
+  }
+
+  pub fn x(&self) -> Field {
+    self.x
+  }
+
+  pub fn y(&self) -> Field {
+    self.y
+  }
+
+  pub fn is_infinity(&self) -> bool {
+    self.infinity
+  }
+
+  // y^2 = x^3 - 3x + b
+  pub fn is_on_curve(&self) -> bool {
+    if self.is_infinity() {
+      return false;
+    }
+    self.y.sq() == Field::curve_rhs(&self.x)
+  }
+
+  // SEC1 encoding: 02/03 || x (33 bytes) if compressed, 04 || x || y (65 bytes) otherwise.
+  // the point at infinity is encoded as a single 0x00
+  pub fn to_sec1(self, compressed: bool) -> Vec<u8> {
+    if self.is_infinity() {
+      return vec![0x00];
+    }
+    let mut buf = Vec::with_capacity(65);
+    if compressed {
+      buf.push(if self.y.is_odd() { 0x03 } else { 0x02 });
+      buf.extend(self.x.serialize());
+    } else {
+      buf.push(0x04);
+      buf.extend(self.x.serialize());
+      buf.extend(self.y.serialize());
+    }
+    buf
+  }
+
+  // rejects the point at infinity, coordinates not less than p and points not on the curve
+  pub fn from_sec1(buf: &[u8]) -> Result<Self, String> {
+    let (prefix, x_y) = match buf.split_first() {
+      Some(x) => x,
+      None => return Err("Empty SEC1 encoding".to_string()),
+    };
+    let pt = match (prefix, x_y.len()) {
+      (0x00, 0) => return Err("SEC1 encoding is the point at infinity".to_string()),
+      (0x02 | 0x03, 32) => {
+        let x = Field::deserialize(x_y)?;
+        let y = match Field::curve_rhs(&x).sqrt() {
+          Some(y) => y,
+          None => return Err("SEC1 x-coordinate is not on the curve".to_string()),
+        };
+        let y = if y.is_odd() == (*prefix == 0x03) { y } else { -y };
+        AffinePoint { x, y, infinity: false }
+      },
+      (0x04, 64) => {
+        let x = Field::deserialize(&x_y[..32])?;
+        let y = Field::deserialize(&x_y[32..])?;
+        AffinePoint { x, y, infinity: false }
+      },
+      _ => return Err(format!("Invalid SEC1 encoding: prefix 0x{:02x} with {}-byte long payload", prefix, x_y.len())),
+    };
+    if !pt.is_on_curve() {
+      return Err("SEC1 point is not on the curve".to_string());
+    }
+    Ok(pt)
+  }
+}
+
+impl From<JacobianPoint> for AffinePoint {
+  fn from(pt: JacobianPoint) -> Self {
+    pt.to_affine()
+  }
+}
+
+// compressed SEC1: hex in human-readable formats and 33 bytes otherwise.
+// the point at infinity is "00" and 33 zero bytes respectively, which
+// Deserialize rejects as from_sec1 does
+impl Serialize for AffinePoint {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let sec1 = self.to_sec1(true);
+    if serializer.is_human_readable() {
+      return serde_util::serialize_bytes(&sec1, serializer);
+    }
+    let mut buf = [0u8; 33];
+    buf[..sec1.len()].copy_from_slice(&sec1);
+    serde_util::serialize_fixed(&buf, serializer)
+  }
+}
+
+// validated by from_sec1, so the point at infinity and points not on the
+// curve are rejected
+impl<'de> Deserialize<'de> for AffinePoint {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let sec1 = if deserializer.is_human_readable() {
+      serde_util::deserialize_bytes(deserializer)?
+    } else {
+      let buf: [u8; 33] = serde_util::deserialize_fixed(deserializer)?;
+      if buf == [0u8; 33] { vec![0x00] } else { buf.to_vec() }
+    };
+    AffinePoint::from_sec1(&sec1).map_err(de::Error::custom)
+  }
+}
+
+// compressed SEC1 in hex
+impl fmt::LowerHex for AffinePoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode(self.to_sec1(true)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const G_X: &str = "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+  const G_Y: &str = "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+
+  #[test]
+  fn test_sec1() {
+    let g = JacobianPoint::get_base_point().to_affine();
+    assert!(g.is_on_curve());
+    assert_eq!(hex::encode(g.to_sec1(false)), format!("04{}{}", G_X, G_Y));
+
+    // G_y is odd
+    let compressed = g.to_sec1(true);
+    assert_eq!(hex::encode(&compressed), format!("03{}", G_X));
+    assert_eq!(AffinePoint::from_sec1(&compressed).unwrap(), g);
+    assert_eq!(AffinePoint::from_sec1(&g.to_sec1(false)).unwrap(), g);
+
+    let mut off_curve = g.to_sec1(false);
+    off_curve[64] ^= 1;
+    assert!(AffinePoint::from_sec1(&off_curve).is_err());
+    assert!(AffinePoint::from_sec1(&[0x00]).is_err());
+  }
+
+  #[test]
+  fn test_serde() {
+    let g = JacobianPoint::get_base_point().to_affine();
+    let json = serde_json::to_string(&g).unwrap();
+    assert_eq!(json, format!(r#""03{}""#, G_X));
+    assert_eq!(serde_json::from_str::<AffinePoint>(&json).unwrap(), g);
+
+    let inf = AffinePoint::new();
+    let bin = bincode::serialize(&inf).unwrap();
+    assert_eq!(bin, [0u8; 33]);
+    assert!(bincode::deserialize::<AffinePoint>(&bin).is_err());
+    assert!(serde_json::from_str::<AffinePoint>(r#""00""#).is_err());
+  }
+}
@@ -0,0 +1,59 @@
+use crate::building_block::{
+  curve::{
+    Curve,
+    CurvePoint,
+  },
+  p256::{
+    jacobian_point::JacobianPoint,
+    scalar::Scalar,
+  },
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct P256;
+
+impl CurvePoint for JacobianPoint {
+  fn identity() -> Self {
+    JacobianPoint::point_at_infinity()
+  }
+
+  fn is_identity(&self) -> bool {
+    self.is_infinity()
+  }
+
+  fn to_sec1(self, compressed: bool) -> Vec<u8> {
+    JacobianPoint::to_sec1(self, compressed)
+  }
+
+  fn from_sec1(buf: &[u8]) -> Result<Self, String> {
+    JacobianPoint::from_sec1(buf)
+  }
+}
+
+impl Curve for P256 {
+  type Scalar = Scalar;
+  type Point = JacobianPoint;
+
+  const SUITE_ID: &'static str = "P256_XMD:SHA-256_SSWU_RO_";
+
+  fn generator() -> JacobianPoint {
+    JacobianPoint::get_base_point()
+  }
+
+  fn mul_base_secret(k: &Scalar) -> JacobianPoint {
+    JacobianPoint::mul_base_secret(k)
+  }
+
+  fn mul_secret(p: &JacobianPoint, k: &Scalar) -> JacobianPoint {
+    p.mul_secret(k)
+  }
+
+  // p > n, so x mod p is reduced once more
+  fn x_to_scalar(p: &JacobianPoint) -> Scalar {
+    Scalar::from_bytes_reduced(&p.to_affine().x().serialize()).0
+  }
+
+  fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<JacobianPoint, String> {
+    JacobianPoint::hash_to_curve(msg, dst)
+  }
+}
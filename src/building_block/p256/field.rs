@@ -0,0 +1,182 @@
+#![allow(dead_code)]
+
+use std::{
+  cmp::PartialEq,
+  fmt,
+  ops::{Add, Mul, Neg, Sub},
+};
+use crate::building_block::arith::{
+  self,
+  Modulus,
+  U256,
+};
+
+// field prime p = 2^256 - 2^224 + 2^192 + 2^96 - 1
+pub const P: Modulus = Modulus {
+  m: [
+    0xFFFFFFFFFFFFFFFF,
+    0x00000000FFFFFFFF,
+    0x0000000000000000,
+    0xFFFFFFFF00000001,
+  ],
+  c: [
+    0x0000000000000001,
+    0xFFFFFFFF00000000,
+    0xFFFFFFFFFFFFFFFF,
+    0x00000000FFFFFFFE,
+  ],
+};
+
+// (p + 1) / 4
+const SQRT_EXP: U256 = [
+  0x0000000000000000,
+  0x0000000040000000,
+  0x4000000000000000,
+  0x3FFFFFFFC0000000,
+];
+
+// b of y^2 = x^3 - 3x + b
+const B: U256 = [
+  0x3BCE3C3E27D2604B,
+  0x651D06B0CC53B0F6,
+  0xB3EBBD55769886BC,
+  0x5AC635D8AA3A93E7,
+];
+
+// element of GF(p), always kept in [0, p)
+#[derive(Debug, Copy, Clone)]
+pub struct Field {
+  pub(crate) n: U256,
+}
+
+impl Field {
+  pub fn from_u256(n: U256) -> Self {
+    Field { n: P.reduce(&n) }
+  }
+
+  pub fn from_hex(hex: &str) -> Result<Self, String> {
+    let buf = hex::decode(hex).map_err(|e| format!("Invalid field element hex {}: {}", hex, e))?;
+    Field::deserialize(&buf)
+  }
+
+  pub fn is_zero(&self) -> bool {
+    arith::is_zero(&self.n)
+  }
+
+  pub fn is_odd(&self) -> bool {
+    self.n[0] & 1 == 1
+  }
+
+  pub fn sq(&self) -> Self {
+    Field { n: P.sq(&self.n) }
+  }
+
+  // returns 0 for 0
+  pub fn inv(&self) -> Self {
+    Field { n: P.inv(&self.n) }
+  }
+
+  // p = 3 (mod 4), so a^((p + 1) / 4) is a square root of a if any
+  pub fn sqrt(&self) -> Option<Self> {
+    let r = Field { n: P.pow(&self.n, &SQRT_EXP) };
+    if r.sq() == *self { Some(r) } else { None }
+  }
+
+  // x^3 - 3x + b
+  pub fn curve_rhs(x: &Field) -> Field {
+    let three_x = *x + *x + *x;
+    x.sq() * *x - three_x + Field { n: B }
+  }
+
+  // 32-byte big-endian
+  pub fn serialize(&self) -> [u8; 32] {
+    arith::to_b32(&self.n)
+  }
+
+  // values not less than p are rejected
+  pub fn deserialize(buf: &[u8]) -> Result<Self, String> {
+    let buf: &[u8; 32] = buf.try_into().map_err(|_| {
+      format!("Serialized Field should be 32-byte long, but got {}-byte long", buf.len())
+    })?;
+    let n = arith::from_b32(buf);
+    if arith::gte(&n, &P.m) {
+      return Err("Serialized Field is not less than the field prime".to_string());
+    }
+    Ok(Field { n })
+  }
+}
+
+impl From<u32> for Field {
+  fn from(n: u32) -> Self {
+    Field { n: [n as u64, 0, 0, 0] }
+  }
+}
+
+impl fmt::LowerHex for Field {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode(self.serialize()))
+  }
+}
+
+macro_rules! impl_op {
+  ($trait:ident, $op_fn:ident, $mod_fn:ident) => {
+    impl $trait<Field> for Field {
+      type Output = Field;
+
+      fn $op_fn(self, rhs: Field) -> Field {
+        Field { n: P.$mod_fn(&self.n, &rhs.n) }
+      }
+    }
+  };
+}
+impl_op!(Add, add, add);
+impl_op!(Sub, sub, sub);
+impl_op!(Mul, mul, mul);
+
+impl Neg for Field {
+  type Output = Field;
+
+  fn neg(self) -> Field {
+    Field { n: P.neg(&self.n) }
+  }
+}
+
+impl PartialEq for Field {
+  fn eq(&self, rhs: &Self) -> bool {
+    self.n == rhs.n
+  }
+}
+impl Eq for Field {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_modulus_constants() {
+    let (sum, carry) = arith::add(&P.m, &P.c);
+    assert!(carry);
+    assert!(arith::is_zero(&sum));
+  }
+
+  #[test]
+  fn test_sqrt() {
+    let a = Field::from(12345u32);
+    let r = a.sq().sqrt().unwrap();
+    assert!(r == a || r == -a);
+
+    // -1 is not a square since p = 3 (mod 4)
+    assert!((-Field::from(1u32)).sqrt().is_none());
+  }
+
+  #[test]
+  fn test_deserialize() {
+    assert!(Field::deserialize(&arith::to_b32(&P.m)).is_err());
+    assert!(Field::deserialize(&[0u8; 31]).is_err());
+    let x = Field::from_hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296").unwrap();
+    assert_eq!(Field::deserialize(&x.serialize()).unwrap(), x);
+  }
+}
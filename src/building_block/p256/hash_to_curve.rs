@@ -0,0 +1,137 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+// P256_XMD:SHA-256_SSWU_RO_ suite of RFC 9380
+
+use crate::building_block::{
+  p256::{
+    affine_point::AffinePoint,
+    field::{Field, P},
+    jacobian_point::JacobianPoint,
+  },
+  secp256k1::hash_to_curve::expand_message_xmd,
+};
+
+// y^2 = x^3 + Ax + B is P-256 itself, so no isogeny is needed
+const B: &str = "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b";
+const Z: u32 = 10; // Z = -10
+
+// count field elements from 48-byte chunks (L = 48) of the expanded message
+pub fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Result<Vec<Field>, String> {
+  let uniform_bytes = expand_message_xmd(msg, dst, count * 48)?;
+
+  // 384-bit big-endian integers mod p
+  Ok(uniform_bytes.chunks(48).map(|chunk| {
+    let mut w = [0u64; 8];
+    for (i, limb) in chunk.rchunks(8).enumerate() {
+      w[i] = u64::from_be_bytes(limb.try_into().unwrap());
+    }
+    Field::from_u256(P.reduce_wide(&w))
+  }).collect())
+}
+
+// simplified SWU. variable-time
+pub fn map_to_curve(u: &Field) -> JacobianPoint {
+  let a = -Field::from(3u32);
+  let b = Field::from_hex(B).unwrap();
+  let z = -Field::from(Z);
+  let one = Field::from(1u32);
+
+  let z_u2 = z * u.sq();
+  let tv1 = z_u2.sq() + z_u2;
+  let x1 = if tv1.is_zero() {
+    b * (z * a).inv()
+  } else {
+    -b * a.inv() * (one + tv1.inv())
+  };
+  let g = |x: &Field| x.sq() * *x + a * *x + b;
+
+  let (x, y) = match g(&x1).sqrt() {
+    Some(y) => (x1, y),
+    None => {
+      let x2 = z_u2 * x1;
+      // g(x2) is square whenever g(x1) is not
+      (x2, g(&x2).sqrt().unwrap())
+    },
+  };
+  let y = if u.is_odd() == y.is_odd() { y } else { -y };
+  AffinePoint { x, y, infinity: false }.into()
+}
+
+// the cofactor of P-256 is 1, so no clearing is needed
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<JacobianPoint, String> {
+  let u = hash_to_field(msg, dst, 2)?;
+  Ok(map_to_curve(&u[0]) + map_to_curve(&u[1]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DST: &[u8] = b"QUUX-V01-CS02-with-P256_XMD:SHA-256_SSWU_RO_";
+
+  struct TestVector {
+    msg: &'static [u8],
+    p_x: &'static str,
+    p_y: &'static str,
+    u_0: &'static str,
+    u_1: &'static str,
+    q0_x: &'static str,
+    q0_y: &'static str,
+  }
+
+  // RFC 9380 J.1.1
+  const TEST_VECTORS: [TestVector; 3] = [
+    TestVector {
+      msg: b"",
+      p_x: "2c15230b26dbc6fc9a37051158c95b79656e17a1a920b11394ca91c44247d3e4",
+      p_y: "8a7a74985cc5c776cdfe4b1f19884970453912e9d31528c060be9ab5c43e8415",
+      u_0: "ad5342c66a6dd0ff080df1da0ea1c04b96e0330dd89406465eeba11582515009",
+      u_1: "8c0f1d43204bd6f6ea70ae8013070a1518b43873bcd850aafa0a9e220e2eea5a",
+      q0_x: "ab640a12220d3ff283510ff3f4b1953d09fad35795140b1c5d64f313967934d5",
+      q0_y: "dccb558863804a881d4fff3455716c836cef230e5209594ddd33d85c565b19b1",
+    },
+    TestVector {
+      msg: b"abc",
+      p_x: "0bb8b87485551aa43ed54f009230450b492fead5f1cc91658775dac4a3388a0f",
+      p_y: "5c41b3d0731a27a7b14bc0bf0ccded2d8751f83493404c84a88e71ffd424212e",
+      u_0: "afe47f2ea2b10465cc26ac403194dfb68b7f5ee865cda61e9f3e07a537220af1",
+      u_1: "379a27833b0bfe6f7bdca08e1e83c760bf9a338ab335542704edcd69ce9e46e0",
+      q0_x: "5219ad0ddef3cc49b714145e91b2f7de6ce0a7a7dc7406c7726c7e373c58cb48",
+      q0_y: "7950144e52d30acbec7b624c203b1996c99617d0b61c2442354301b191d93ecf",
+    },
+    TestVector {
+      msg: b"abcdef0123456789",
+      p_x: "65038ac8f2b1def042a5df0b33b1f4eca6bff7cb0f9c6c1526811864e544ed80",
+      p_y: "cad44d40a656e7aff4002a8de287abc8ae0482b5ae825822bb870d6df9b56ca3",
+      u_0: "0fad9d125a9477d55cf9357105b0eb3a5c4259809bf87180aa01d651f53d312c",
+      u_1: "b68597377392cd3419d8fcc7d7660948c8403b19ea78bbca4b133c9d2196c0fb",
+      q0_x: "a17bdf2965eb88074bc01157e644ed409dac97cfcf0c61c998ed0fa45e79e4a2",
+      q0_y: "4f1bc80c70d411a3cc1d67aeae6e726f0f311639fee560c7f5a664554e3c9c2e",
+    },
+  ];
+
+  #[test]
+  fn test_hash_to_curve() {
+    for tv in TEST_VECTORS {
+      let u = hash_to_field(tv.msg, DST, 2).unwrap();
+      assert_eq!(hex::encode(u[0].serialize()), tv.u_0);
+      assert_eq!(hex::encode(u[1].serialize()), tv.u_1);
+
+      let q0 = map_to_curve(&u[0]).to_affine();
+      assert_eq!(hex::encode(q0.x().serialize()), tv.q0_x);
+      assert_eq!(hex::encode(q0.y().serialize()), tv.q0_y);
+
+      let p = hash_to_curve(tv.msg, DST).unwrap().to_affine();
+      assert_eq!(hex::encode(p.x().serialize()), tv.p_x);
+      assert_eq!(hex::encode(p.y().serialize()), tv.p_y);
+    }
+  }
+
+  #[test]
+  fn test_map_to_curve_is_on_curve() {
+    for i in 0..16u32 {
+      assert!(map_to_curve(&Field::from(i)).to_affine().is_on_curve());
+    }
+  }
+}
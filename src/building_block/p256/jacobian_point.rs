@@ -0,0 +1,403 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+use std::{
+  cmp::PartialEq,
+  fmt,
+  iter::Sum,
+  ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+use crate::building_block::p256::{
+  affine_point::AffinePoint,
+  field::Field,
+  hash_to_curve,
+  scalar::{Scalar, N},
+};
+use serde::{
+  Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
+};
+
+// (x, y) = (X/Z^2, Y/Z^3)
+#[derive(Debug, Copy, Clone)]
+pub struct JacobianPoint {
+  pub(crate) x: Field,
+  pub(crate) y: Field,
+  pub(crate) z: Field,
+  pub(crate) infinity: bool,
+}
+
+const G_X: &str = "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+const G_Y: &str = "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+
+impl JacobianPoint {
+  pub fn point_at_infinity() -> Self {
+    let zero = Field::from(0u32);
+    JacobianPoint {
+      x: zero,
+      y: zero,
+      z: zero,
+      infinity: true,
+    }
+  }
+
+  pub fn get_base_point() -> Self {
+    AffinePoint {
+      x: Field::from_hex(G_X).unwrap(),
+      y: Field::from_hex(G_Y).unwrap(),
+      infinity: false,
+    }.into()
+  }
+
+  pub fn is_infinity(&self) -> bool {
+    self.infinity
+  }
+
+  pub fn to_affine(self) -> AffinePoint {
+    if self.infinity {
+      return AffinePoint::new();
+    }
+    let z_inv = self.z.inv();
+    let z_inv2 = z_inv.sq();
+    AffinePoint {
+      x: self.x * z_inv2,
+      y: self.y * z_inv2 * z_inv,
+      infinity: false,
+    }
+  }
+
+  pub fn to_sec1(self, compressed: bool) -> Vec<u8> {
+    self.to_affine().to_sec1(compressed)
+  }
+
+  pub fn from_sec1(buf: &[u8]) -> Result<Self, String> {
+    AffinePoint::from_sec1(buf).map(|pt| pt.into())
+  }
+
+  // P256_XMD:SHA-256_SSWU_RO_ of RFC 9380
+  pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<Self, String> {
+    hash_to_curve::hash_to_curve(msg, dst)
+  }
+
+  // dbl-2001-b (a = -3)
+  pub fn double(&self) -> Self {
+    if self.infinity || self.y.is_zero() {
+      return JacobianPoint::point_at_infinity();
+    }
+    let delta = self.z.sq();
+    let gamma = self.y.sq();
+    let beta = self.x * gamma;
+    let alpha = {
+      let t = (self.x - delta) * (self.x + delta);
+      t + t + t
+    };
+    let beta4 = {
+      let t = beta + beta;
+      t + t
+    };
+    let x = alpha.sq() - (beta4 + beta4);
+    let z = (self.y + self.z).sq() - gamma - delta;
+    let gamma_sq8 = {
+      let t = gamma.sq();
+      let t = t + t;
+      let t = t + t;
+      t + t
+    };
+    let y = alpha * (beta4 - x) - gamma_sq8;
+    JacobianPoint { x, y, z, infinity: false }
+  }
+
+  // add-2007-bl, falling back to doubling when both points are equal
+  fn add_point(&self, rhs: &JacobianPoint) -> Self {
+    if self.infinity {
+      return *rhs;
+    }
+    if rhs.infinity {
+      return *self;
+    }
+    let z1z1 = self.z.sq();
+    let z2z2 = rhs.z.sq();
+    let u1 = self.x * z2z2;
+    let u2 = rhs.x * z1z1;
+    let s1 = self.y * rhs.z * z2z2;
+    let s2 = rhs.y * self.z * z1z1;
+    let h = u2 - u1;
+    let r = s2 - s1;
+
+    if h.is_zero() {
+      return if r.is_zero() {
+        self.double()
+      } else {
+        JacobianPoint::point_at_infinity()
+      };
+    }
+    let hh = h.sq();
+    let hhh = h * hh;
+    let v = u1 * hh;
+
+    let x = r.sq() - hhh - (v + v);
+    let y = r * (v - x) - s1 * hhh;
+    let z = self.z * rhs.z * h;
+    JacobianPoint { x, y, z, infinity: false }
+  }
+
+  // variable-time fixed-window (w = 4) multiplication
+  fn mul_scalar(&self, k: &Scalar) -> Self {
+    let mut table = [JacobianPoint::point_at_infinity(); 16];
+    for i in 1..16 {
+      table[i] = table[i - 1].add_point(self);
+    }
+
+    let mut r = JacobianPoint::point_at_infinity();
+    for i in (0..64).rev() {
+      for _ in 0..4 {
+        r = r.double();
+      }
+      let nibble = (k.d[i / 16] >> ((i % 16) * 4)) & 0xf;
+      if nibble != 0 {
+        r = r.add_point(&table[nibble as usize]);
+      }
+    }
+    r
+  }
+
+  // self * k with a sequence of group operations and table accesses that
+  // doesn't depend on k. see JacobianPoint::mul_secret of secp256k1 for the
+  // offset digits. not constant time as the field arithmetic is not
+  pub fn mul_secret(&self, k: &Scalar) -> Self {
+    if self.infinity {
+      return *self;
+    }
+    // table[j] = (j + 16) * self
+    let mut table = [*self; 16];
+    for _ in 0..4 {
+      table[0] = table[0].double();
+    }
+    for j in 1..16 {
+      table[j] = table[j - 1].add_point(self);
+    }
+    let ones = 0x1111111111111111;
+    let offset = N.reduce_wide(&[ones - 1, ones, ones, ones, 1, 0, 0, 0]);
+    let k = N.sub(&k.d, &offset);
+
+    let mut r = JacobianPoint::point_at_infinity();
+    for i in (0..64).rev() {
+      for _ in 0..4 {
+        r = r.double();
+      }
+      let nibble = (k[i / 16] >> ((i % 16) * 4)) & 0xf;
+      r = r.add_point(&select(&table, nibble));
+    }
+    r
+  }
+
+  // G * k for secret k. see mul_secret
+  pub fn mul_base_secret(k: &Scalar) -> Self {
+    JacobianPoint::get_base_point().mul_secret(k)
+  }
+}
+
+// table[idx] read by touching every entry
+fn select(table: &[JacobianPoint; 16], idx: u64) -> JacobianPoint {
+  let mut r = [0u64; 12];
+  for (j, p) in table.iter().enumerate() {
+    let mask = 0u64.wrapping_sub((j as u64 == idx) as u64);
+    for (l, x) in p.x.n.iter().chain(&p.y.n).chain(&p.z.n).enumerate() {
+      r[l] |= x & mask;
+    }
+  }
+  JacobianPoint {
+    x: Field::from_u256([r[0], r[1], r[2], r[3]]),
+    y: Field::from_u256([r[4], r[5], r[6], r[7]]),
+    z: Field::from_u256([r[8], r[9], r[10], r[11]]),
+    infinity: false,
+  }
+}
+
+impl From<AffinePoint> for JacobianPoint {
+  fn from(pt: AffinePoint) -> Self {
+    if pt.is_infinity() {
+      return JacobianPoint::point_at_infinity();
+    }
+    JacobianPoint {
+      x: pt.x,
+      y: pt.y,
+      z: Field::from(1u32),
+      infinity: false,
+    }
+  }
+}
+
+impl Add<JacobianPoint> for JacobianPoint {
+  type Output = JacobianPoint;
+
+  fn add(self, rhs: JacobianPoint) -> JacobianPoint {
+    self.add_point(&rhs)
+  }
+}
+
+impl Sub<JacobianPoint> for JacobianPoint {
+  type Output = JacobianPoint;
+
+  fn sub(self, rhs: JacobianPoint) -> JacobianPoint {
+    self.add_point(&-rhs)
+  }
+}
+
+// variable-time, so only for public scalars
+impl Mul<Scalar> for JacobianPoint {
+  type Output = JacobianPoint;
+
+  fn mul(self, rhs: Scalar) -> JacobianPoint {
+    self.mul_scalar(&rhs)
+  }
+}
+
+impl Neg for JacobianPoint {
+  type Output = JacobianPoint;
+
+  fn neg(self) -> JacobianPoint {
+    JacobianPoint { y: -self.y, ..self }
+  }
+}
+
+impl AddAssign<JacobianPoint> for JacobianPoint {
+  fn add_assign(&mut self, rhs: JacobianPoint) {
+    *self = *self + rhs;
+  }
+}
+
+impl SubAssign<JacobianPoint> for JacobianPoint {
+  fn sub_assign(&mut self, rhs: JacobianPoint) {
+    *self = *self - rhs;
+  }
+}
+
+impl Sum for JacobianPoint {
+  fn sum<I: Iterator<Item = JacobianPoint>>(iter: I) -> Self {
+    iter.fold(JacobianPoint::point_at_infinity(), |acc, x| acc + x)
+  }
+}
+
+impl<'a> Sum<&'a JacobianPoint> for JacobianPoint {
+  fn sum<I: Iterator<Item = &'a JacobianPoint>>(iter: I) -> Self {
+    iter.fold(JacobianPoint::point_at_infinity(), |acc, x| acc + *x)
+  }
+}
+
+impl PartialEq for JacobianPoint {
+  fn eq(&self, rhs: &Self) -> bool {
+    if self.infinity || rhs.infinity {
+      return self.infinity == rhs.infinity;
+    }
+    let z1z1 = self.z.sq();
+    let z2z2 = rhs.z.sq();
+    self.x * z2z2 == rhs.x * z1z1 &&
+      self.y * rhs.z * z2z2 == rhs.y * self.z * z1z1
+  }
+}
+impl Eq for JacobianPoint {} // JacobianPoint has total equality
+
+// same encoding as AffinePoint
+impl Serialize for JacobianPoint {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.to_affine().serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for JacobianPoint {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    AffinePoint::deserialize(deserializer).map(|pt| pt.into())
+  }
+}
+
+// compressed SEC1 in hex
+impl fmt::LowerHex for JacobianPoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::LowerHex::fmt(&self.to_affine(), f)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::{
+    arith,
+    p256::scalar::N,
+  };
+
+  // k * G for k = 1, 2, 3, 20 and 112233445566778899 from
+  // http://point-at-infinity.org/ecc/nisttv
+  const MUL_TEST_VECTORS: [(u64, &str, &str); 5] = [
+    (
+      1,
+      "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+      "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+    ),
+    (
+      2,
+      "7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc47669978",
+      "07775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1",
+    ),
+    (
+      3,
+      "5ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c",
+      "8734640c4998ff7e374b06ce1a64a2ecd82ab036384fb83d9a79b127a27d5032",
+    ),
+    (
+      20,
+      "83a01a9378395bab9bcd6a0ad03cc56d56e6b19250465a94a234dc4c6b28da9a",
+      "76e49b6de2f73234ae6a5eb9d612b75c9f2202bb6923f54ff8240aaa86f640b8",
+    ),
+    (
+      112233445566778899,
+      "339150844ec15234807fe862a86be77977dbfb3ae3d96f4c22795513aeaab82f",
+      "b1c14ddfdc8ec1b2583f51e85a5eb3a155840f2034730e9b5ada38b674336a21",
+    ),
+  ];
+
+  #[test]
+  fn test_mul() {
+    let g = JacobianPoint::get_base_point();
+    for (k, x, y) in MUL_TEST_VECTORS {
+      let p = (g * Scalar::from(k)).to_affine();
+      assert_eq!(hex::encode(p.x().serialize()), x);
+      assert_eq!(hex::encode(p.y().serialize()), y);
+    }
+  }
+
+  #[test]
+  fn test_add_double() {
+    let g = JacobianPoint::get_base_point();
+    let g2 = g + g;
+    let g3 = g2 + g;
+    assert_eq!(g2, g.double());
+    assert_eq!(g3, g * Scalar::from(3u64));
+    assert_eq!(g3 - g2, g);
+    assert!((g - g).is_infinity());
+    assert!(g2.to_affine().is_on_curve());
+    assert_eq!([g, g2].iter().sum::<JacobianPoint>(), g3);
+  }
+
+  #[test]
+  fn test_mul_by_order() {
+    let g = JacobianPoint::get_base_point();
+    let n_minus_1 = Scalar { d: arith::sub(&N.m, &arith::ONE).0 };
+    assert_eq!(g * n_minus_1, -g);
+    assert!((g * n_minus_1 + g).is_infinity());
+    assert_eq!(g.mul_secret(&n_minus_1), -g);
+  }
+
+  #[test]
+  fn test_mul_secret() {
+    let g = JacobianPoint::get_base_point();
+    for (k, x, _) in MUL_TEST_VECTORS {
+      let p = JacobianPoint::mul_base_secret(&Scalar::from(k)).to_affine();
+      assert_eq!(hex::encode(p.x().serialize()), x);
+    }
+    assert!(g.mul_secret(&Scalar::zero()).is_infinity());
+    assert!(JacobianPoint::point_at_infinity().mul_secret(&Scalar::from(5u64)).is_infinity());
+  }
+}
@@ -0,0 +1,8 @@
+// NIST P-256 (secp256r1) over the pure-Rust 256-bit modular arithmetic
+
+pub mod affine_point;
+pub mod curve;
+pub mod field;
+pub mod hash_to_curve;
+pub mod jacobian_point;
+pub mod scalar;
@@ -0,0 +1,252 @@
+#![allow(dead_code)]
+
+use std::{
+  cmp::PartialEq,
+  fmt,
+  iter::Sum,
+  ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+use crate::building_block::{
+  arith::{
+    self,
+    Modulus,
+    U256,
+  },
+  curve::CurveScalar,
+  serde_util,
+};
+use rand::{
+  CryptoRng,
+  RngCore,
+};
+use rug::{
+  integer::Order,
+  Integer,
+};
+use serde::{
+  de,
+  Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
+};
+use zeroize::Zeroize;
+
+// group order n
+pub const N: Modulus = Modulus {
+  m: [
+    0xF3B9CAC2FC632551,
+    0xBCE6FAADA7179E84,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFF00000000,
+  ],
+  c: [
+    0x0C46353D039CDAAF,
+    0x4319055258E8617B,
+    0x0000000000000000,
+    0x00000000FFFFFFFF,
+  ],
+};
+
+// element of Z_n, always kept in [0, n)
+#[derive(Debug, Copy, Clone)]
+pub struct Scalar {
+  pub(crate) d: U256,
+}
+
+impl Scalar {
+  pub fn zero() -> Self {
+    Scalar { d: arith::ZERO }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    arith::is_zero(&self.d)
+  }
+
+  pub fn inv(&self) -> Self {
+    Scalar { d: N.inv(&self.d) }
+  }
+
+  // n is about 2^256 - 2^224, so 32 random bytes mod n would be biased by 2^-32.
+  // reducing 64 bytes makes the bias negligible
+  pub fn rand_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    let mut w = [0u64; 8];
+    for limb in w.iter_mut() {
+      *limb = rng.next_u64();
+    }
+    let d = N.reduce_wide(&w);
+    w.zeroize();
+    Scalar { d }
+  }
+
+  // 32-byte big-endian
+  pub fn to_bytes(self) -> [u8; 32] {
+    arith::to_b32(&self.d)
+  }
+
+  // 32-byte big-endian x mod n, and whether x was not less than n
+  pub fn from_bytes_reduced(buf: &[u8; 32]) -> (Self, bool) {
+    let d = arith::from_b32(buf);
+    (Scalar { d: N.reduce(&d) }, arith::gte(&d, &N.m))
+  }
+
+  // 0x-prefixed 32-byte big-endian
+  pub fn to_hex(self) -> String {
+    format!("{:#x}", self)
+  }
+}
+
+impl From<u64> for Scalar {
+  fn from(n: u64) -> Self {
+    Scalar { d: [n, 0, 0, 0] }
+  }
+}
+
+impl fmt::LowerHex for Scalar {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode(self.to_bytes()))
+  }
+}
+
+// 32-byte big-endian, as hex in human-readable formats
+impl Serialize for Scalar {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serde_util::serialize_fixed(&self.to_bytes(), serializer)
+  }
+}
+
+// values not less than n are rejected instead of being reduced
+impl<'de> Deserialize<'de> for Scalar {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let buf = serde_util::deserialize_fixed(deserializer)?;
+    Scalar::from_bytes_checked(&buf).map_err(de::Error::custom)
+  }
+}
+
+macro_rules! impl_op {
+  ($trait:ident, $op_fn:ident, $mod_fn:ident) => {
+    impl $trait<Scalar> for Scalar {
+      type Output = Scalar;
+
+      fn $op_fn(self, rhs: Scalar) -> Scalar {
+        Scalar { d: N.$mod_fn(&self.d, &rhs.d) }
+      }
+    }
+  };
+}
+impl_op!(Add, add, add);
+impl_op!(Sub, sub, sub);
+impl_op!(Mul, mul, mul);
+
+macro_rules! impl_assign_op {
+  ($trait:ident, $op_fn:ident, $op:tt) => {
+    impl $trait<Scalar> for Scalar {
+      fn $op_fn(&mut self, rhs: Scalar) {
+        *self = *self $op rhs;
+      }
+    }
+  };
+}
+impl_assign_op!(AddAssign, add_assign, +);
+impl_assign_op!(SubAssign, sub_assign, -);
+impl_assign_op!(MulAssign, mul_assign, *);
+
+impl Neg for Scalar {
+  type Output = Scalar;
+
+  fn neg(self) -> Scalar {
+    Scalar { d: N.neg(&self.d) }
+  }
+}
+
+impl Sum for Scalar {
+  fn sum<I: Iterator<Item = Scalar>>(iter: I) -> Self {
+    iter.fold(Scalar::zero(), |acc, x| acc + x)
+  }
+}
+
+impl<'a> Sum<&'a Scalar> for Scalar {
+  fn sum<I: Iterator<Item = &'a Scalar>>(iter: I) -> Self {
+    iter.fold(Scalar::zero(), |acc, x| acc + *x)
+  }
+}
+
+impl PartialEq for Scalar {
+  fn eq(&self, rhs: &Self) -> bool {
+    self.d == rhs.d
+  }
+}
+impl Eq for Scalar {}
+
+impl Zeroize for Scalar {
+  fn zeroize(&mut self) {
+    self.d.zeroize();
+  }
+}
+
+impl CurveScalar for Scalar {
+  fn order() -> Integer {
+    Integer::from_digits(&arith::to_b32(&N.m), Order::MsfBe)
+  }
+
+  fn zero() -> Self {
+    Scalar::zero()
+  }
+
+  fn is_zero(&self) -> bool {
+    Scalar::is_zero(self)
+  }
+
+  fn inv(&self) -> Self {
+    Scalar::inv(self)
+  }
+
+  fn rand_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    Scalar::rand_with_rng(rng)
+  }
+
+  fn to_bytes(&self) -> [u8; 32] {
+    Scalar::to_bytes(*self)
+  }
+
+  fn from_bytes_reduced(buf: &[u8; 32]) -> (Self, bool) {
+    Scalar::from_bytes_reduced(buf)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::rngs::OsRng;
+
+  #[test]
+  fn test_modulus_constants() {
+    let (sum, carry) = arith::add(&N.m, &N.c);
+    assert!(carry);
+    assert!(arith::is_zero(&sum));
+  }
+
+  #[test]
+  fn test_from_bytes() {
+    let n = arith::to_b32(&N.m);
+    assert_eq!(Scalar::from_bytes_reduced(&n), (Scalar::zero(), true));
+    assert!(Scalar::from_bytes_checked(&n).is_err());
+
+    let s = Scalar::rand_with_rng(&mut OsRng);
+    assert_eq!(Scalar::from_bytes_checked(&s.to_bytes()).unwrap(), s);
+  }
+
+  #[test]
+  fn test_serde() {
+    let s = Scalar::from(255u64);
+    let json = serde_json::to_string(&s).unwrap();
+    assert_eq!(json, format!(r#""{:x}""#, s));
+    assert_eq!(serde_json::from_str::<Scalar>(&json).unwrap(), s);
+
+    let n = hex::encode(arith::to_b32(&N.m));
+    assert!(serde_json::from_str::<Scalar>(&format!(r#""{}""#, n)).is_err());
+  }
+}
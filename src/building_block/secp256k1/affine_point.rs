@@ -1,29 +1,33 @@
 #![allow(dead_code)]
 
-use std::ffi::c_int;
+use std::{
+  ffi::c_int,
+  fmt,
+  str::FromStr,
+};
 use crate::building_block::secp256k1::{
+  backend::group_ge_set_gej,
   field::{
     Field,
     Fe5x52,
   },
   jacobian_point::JacobianPoint,
 };
+use crate::building_block::serde_util;
 use serde::{
-  Serialize,
+  de,
   Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
 };
 
-extern "C" {
-  #[link_name = "secp256k1_export_group_ge_set_gej"]
-  fn group_ge_set_gej(r: *mut AffinePoint, a: *const JacobianPoint);
-}
-
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
 pub struct AffinePoint { // using 5x52 assuming 64-bit arch
-  x: Fe5x52,
-  y: Fe5x52,
-  infinity: c_int,
+  pub(crate) x: Fe5x52,
+  pub(crate) y: Fe5x52,
+  pub(crate) infinity: c_int,
 }
 
 impl AffinePoint {
@@ -38,6 +42,73 @@ impl AffinePoint {
   pub fn x(&self) -> Field {
     self.x.into()
   }
+
+  pub fn y(&self) -> Field {
+    self.y.into()
+  }
+
+  pub fn is_infinity(&self) -> bool {
+    self.infinity != 0
+  }
+
+  // y^2 = x^3 + 7
+  pub fn is_on_curve(&self) -> bool {
+    if self.is_infinity() {
+      return false;
+    }
+    self.y().sq() == Field::curve_rhs(&self.x())
+  }
+
+  // SEC1 encoding: 02/03 || x (33 bytes) if compressed, 04 || x || y (65 bytes) otherwise.
+  // the point at infinity is encoded as a single 0x00
+  pub fn to_sec1(self, compressed: bool) -> Vec<u8> {
+    if self.is_infinity() {
+      return vec![0x00];
+    }
+    let x = self.x().serialize();
+    let y = self.y();
+
+    let mut buf = Vec::with_capacity(65);
+    if compressed {
+      buf.push(if y.is_odd() { 0x03 } else { 0x02 });
+      buf.extend(x);
+    } else {
+      buf.push(0x04);
+      buf.extend(x);
+      buf.extend(y.serialize());
+    }
+    buf
+  }
+
+  // rejects the point at infinity, coordinates not less than p and points not on the curve
+  pub fn from_sec1(buf: &[u8]) -> Result<Self, String> {
+    let (prefix, x_y) = match buf.split_first() {
+      Some(x) => x,
+      None => return Err("Empty SEC1 encoding".to_string()),
+    };
+    let pt = match (prefix, x_y.len()) {
+      (0x00, 0) => return Err("SEC1 encoding is the point at infinity".to_string()),
+      (0x02 | 0x03, 32) => {
+        let x = Field::deserialize(x_y)?;
+        let y = match Field::curve_rhs(&x).sqrt() {
+          Some(y) => y,
+          None => return Err("SEC1 x-coordinate is not on the curve".to_string()),
+        };
+        let y = if y.is_odd() == (*prefix == 0x03) { y } else { -y };
+        AffinePoint { x: x.n, y: y.n, infinity: 0 }
+      },
+      (0x04, 64) => {
+        let x = Field::deserialize(&x_y[..32])?;
+        let y = Field::deserialize(&x_y[32..])?;
+        AffinePoint { x: x.n, y: y.n, infinity: 0 }
+      },
+      _ => return Err(format!("Invalid SEC1 encoding: prefix 0x{:02x} with {}-byte long payload", prefix, x_y.len())),
+    };
+    if !pt.is_on_curve() {
+      return Err("SEC1 point is not on the curve".to_string());
+    }
+    Ok(pt)
+  }
 }
 
 impl From<JacobianPoint> for AffinePoint {
@@ -52,3 +123,176 @@ impl From<JacobianPoint> for AffinePoint {
     affine_pt
   }
 }
+
+// compressed SEC1: hex in human-readable formats and 33 bytes otherwise.
+// the point at infinity is "00" and 33 zero bytes respectively, which
+// Deserialize rejects as from_sec1 does
+impl Serialize for AffinePoint {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let sec1 = self.to_sec1(true);
+    if serializer.is_human_readable() {
+      return serde_util::serialize_bytes(&sec1, serializer);
+    }
+    let mut buf = [0u8; 33];
+    buf[..sec1.len()].copy_from_slice(&sec1);
+    serde_util::serialize_fixed(&buf, serializer)
+  }
+}
+
+// validated by from_sec1, so the point at infinity and points not on the
+// curve are rejected
+impl<'de> Deserialize<'de> for AffinePoint {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let sec1 = if deserializer.is_human_readable() {
+      serde_util::deserialize_bytes(deserializer)?
+    } else {
+      let buf: [u8; 33] = serde_util::deserialize_fixed(deserializer)?;
+      if buf == [0u8; 33] { vec![0x00] } else { buf.to_vec() }
+    };
+    AffinePoint::from_sec1(&sec1).map_err(de::Error::custom)
+  }
+}
+
+// compressed SEC1 in hex
+impl fmt::Display for AffinePoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:x}", self)
+  }
+}
+
+impl fmt::LowerHex for AffinePoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode(self.to_sec1(true)))
+  }
+}
+
+impl fmt::UpperHex for AffinePoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode_upper(self.to_sec1(true)))
+  }
+}
+
+// compressed or uncompressed SEC1 in hex, optionally 0x-prefixed
+impl FromStr for AffinePoint {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let buf = hex::decode(s).map_err(|e| format!("Invalid point hex: {}", e))?;
+    AffinePoint::from_sec1(&buf)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const G_X: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+  const G_Y: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+  #[test]
+  fn test_to_sec1() {
+    let g = JacobianPoint::get_base_point().to_affine();
+    assert_eq!(hex::encode(g.to_sec1(true)), format!("02{}", G_X));
+    assert_eq!(hex::encode(g.to_sec1(false)), format!("04{}{}", G_X, G_Y));
+
+    // 2G has an even y
+    let g2 = (JacobianPoint::get_base_point() + JacobianPoint::get_base_point()).to_affine();
+    assert_eq!(
+      hex::encode(g2.to_sec1(true)),
+      "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+    );
+  }
+
+  #[test]
+  fn test_from_sec1_decompression() {
+    let g = JacobianPoint::get_base_point();
+
+    let even = hex::decode(format!("02{}", G_X)).unwrap();
+    let pt = AffinePoint::from_sec1(&even).unwrap();
+    assert_eq!(hex::encode(pt.y().serialize()), G_Y);
+    assert_eq!(JacobianPoint::from(pt), g);
+
+    // -G has the same x and an odd y
+    let odd = hex::decode(format!("03{}", G_X)).unwrap();
+    let pt = AffinePoint::from_sec1(&odd).unwrap();
+    assert!(pt.y().is_odd());
+    assert_eq!(JacobianPoint::from(pt) + g, JacobianPoint::point_at_infinity());
+  }
+
+  #[test]
+  fn test_from_sec1_rejects_invalid_encodings() {
+    // empty and point at infinity
+    assert!(AffinePoint::from_sec1(&[]).is_err());
+    assert!(AffinePoint::from_sec1(&[0x00]).is_err());
+
+    // y is off by one
+    let mut off_curve = hex::decode(format!("04{}{}", G_X, G_Y)).unwrap();
+    off_curve[64] ^= 1;
+    assert!(AffinePoint::from_sec1(&off_curve).is_err());
+
+    // x^3 + 7 has no square root for x = 0
+    let mut no_sqrt = vec![0x02];
+    no_sqrt.extend([0u8; 32]);
+    assert!(AffinePoint::from_sec1(&no_sqrt).is_err());
+
+    // x is not less than p
+    let mut x_ge_p = vec![0x02];
+    x_ge_p.extend([0xffu8; 32]);
+    assert!(AffinePoint::from_sec1(&x_ge_p).is_err());
+
+    // wrong length and unknown prefix
+    let compressed = hex::decode(format!("02{}", G_X)).unwrap();
+    assert!(AffinePoint::from_sec1(&compressed[..32]).is_err());
+    let mut hybrid = hex::decode(format!("04{}{}", G_X, G_Y)).unwrap();
+    hybrid[0] = 0x06;
+    assert!(AffinePoint::from_sec1(&hybrid).is_err());
+  }
+
+  #[test]
+  fn test_text_formats() {
+    let g = JacobianPoint::get_base_point().to_affine();
+    assert_eq!(g.to_string(), format!("02{}", G_X));
+    assert_eq!(format!("{:#x}", g), format!("0x02{}", G_X));
+    assert_eq!(format!("{:X}", g), format!("02{}", G_X.to_uppercase()));
+
+    assert_eq!(AffinePoint::from_str(&g.to_string()).unwrap().y().serialize(), g.y().serialize());
+    let uncompressed = format!("04{}{}", G_X, G_Y);
+    let pt: AffinePoint = uncompressed.parse().unwrap();
+    assert_eq!(pt.to_string(), g.to_string());
+    assert!(AffinePoint::from_str("02zz").is_err());
+    assert!(AffinePoint::from_str("00").is_err());
+  }
+
+  #[test]
+  fn test_serde() {
+    let g = JacobianPoint::get_base_point().to_affine();
+    let json = serde_json::to_string(&g).unwrap();
+    assert_eq!(json, format!("\"02{}\"", G_X));
+    assert_eq!(serde_json::from_str::<AffinePoint>(&json).unwrap().to_string(), g.to_string());
+
+    let bin = bincode::serialize(&g).unwrap();
+    assert_eq!(bin, g.to_sec1(true));
+    assert_eq!(bincode::deserialize::<AffinePoint>(&bin).unwrap().to_string(), g.to_string());
+
+    // point at infinity
+    let inf = AffinePoint::new();
+    assert_eq!(serde_json::to_string(&inf).unwrap(), "\"00\"");
+    assert_eq!(bincode::serialize(&inf).unwrap(), vec![0u8; 33]);
+    assert!(serde_json::from_str::<AffinePoint>("\"00\"").is_err());
+    assert!(bincode::deserialize::<AffinePoint>(&[0u8; 33]).is_err());
+
+    // x = 5 is not on the curve
+    let mut bad = [0u8; 33];
+    bad[0] = 0x02;
+    bad[32] = 5;
+    assert!(bincode::deserialize::<AffinePoint>(&bad).is_err());
+    assert!(serde_json::from_str::<AffinePoint>(&format!("\"{}\"", hex::encode(bad))).is_err());
+  }
+}
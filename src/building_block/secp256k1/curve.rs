@@ -0,0 +1,103 @@
+use crate::building_block::{
+  curve::{
+    Curve,
+    CurvePoint,
+    CurveScalar,
+  },
+  secp256k1::{
+    jacobian_point::JacobianPoint,
+    scalar::Scalar,
+    util::secp256k1_group_order,
+  },
+};
+use rand::{
+  CryptoRng,
+  RngCore,
+};
+use rug::Integer;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl CurveScalar for Scalar {
+  fn order() -> Integer {
+    secp256k1_group_order()
+  }
+
+  fn zero() -> Self {
+    Scalar::zero()
+  }
+
+  fn is_zero(&self) -> bool {
+    Scalar::is_zero(self)
+  }
+
+  fn inv(&self) -> Self {
+    Scalar::inv(self)
+  }
+
+  fn rand_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    Scalar::rand_with_rng(rng)
+  }
+
+  fn to_bytes(&self) -> [u8; 32] {
+    self.secp256k1_serialize().try_into().unwrap()
+  }
+
+  fn from_bytes_reduced(buf: &[u8; 32]) -> (Self, bool) {
+    Scalar::from_bytes_reduced(buf)
+  }
+}
+
+impl CurvePoint for JacobianPoint {
+  fn identity() -> Self {
+    JacobianPoint::point_at_infinity()
+  }
+
+  fn is_identity(&self) -> bool {
+    self.infinity != 0
+  }
+
+  fn to_sec1(self, compressed: bool) -> Vec<u8> {
+    JacobianPoint::to_sec1(self, compressed)
+  }
+
+  fn from_sec1(buf: &[u8]) -> Result<Self, String> {
+    JacobianPoint::from_sec1(buf)
+  }
+}
+
+impl Curve for Secp256k1 {
+  type Scalar = Scalar;
+  type Point = JacobianPoint;
+
+  const SUITE_ID: &'static str = "secp256k1_XMD:SHA-256_SSWU_RO_";
+
+  fn generator() -> JacobianPoint {
+    JacobianPoint::get_base_point()
+  }
+
+  fn mul_base(k: &Scalar) -> JacobianPoint {
+    JacobianPoint::mul_base(k)
+  }
+
+  fn mul_base_secret(k: &Scalar) -> JacobianPoint {
+    JacobianPoint::mul_base_secret(k)
+  }
+
+  fn mul_secret(p: &JacobianPoint, k: &Scalar) -> JacobianPoint {
+    p.mul_secret(k)
+  }
+
+  fn multi_mul(terms: &[(JacobianPoint, Scalar)]) -> JacobianPoint {
+    JacobianPoint::multi_mul(terms)
+  }
+
+  fn x_to_scalar(p: &JacobianPoint) -> Scalar {
+    p.to_affine().x().into()
+  }
+
+  fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<JacobianPoint, String> {
+    JacobianPoint::hash_to_curve(msg, dst)
+  }
+}
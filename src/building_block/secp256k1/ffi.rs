@@ -5,6 +5,7 @@ use std::ffi::{
   c_uchar,
 };
 use crate::building_block::secp256k1::{
+  affine_point::AffinePoint,
   field::Field,
   scalar::Scalar,
   jacobian_point::JacobianPoint,
@@ -86,5 +87,8 @@ extern "C" {
 
   #[link_name = "secp256k1_export_group_get_base_point"]
   pub fn group_get_base_point(r: *mut JacobianPoint);
+
+  #[link_name = "secp256k1_export_group_ge_set_gej"]
+  pub fn group_ge_set_gej(r: *mut AffinePoint, a: *const JacobianPoint);
 }
 
@@ -1,27 +1,18 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
-//use std::{
-//  // cmp::PartialEq,
-//  // fmt,
-//  // ops::{Add, AddAssign, Sub, Mul, MulAssign},
-//  os::raw::c_uchar,
-//};
-// use rand::{
-//   rngs::OsRng,
-//   RngCore,
-// };
-// use rug::{
-//   integer::Order,
-//   Integer,
-// };
+use std::ops::{Add, Sub, Mul, Neg};
 use crate::building_block::secp256k1::{
-  ffi::{
-//    fe_add,
-//    fe_eq,
-//    fe_get_b32,
-//    fe_is_equal,
+  backend::{
+    fe_add,
+    fe_get_b32,
+    fe_is_equal,
     fe_inv,
+    fe_is_zero,
+    fe_mul,
+    fe_normalize,
+    fe_set_int,
+    fe_sq,
   },
 };
 use serde::{
@@ -31,10 +22,26 @@ use serde::{
 
 pub type Fe5x52 = [u64; 5];
 
+// field prime p in big-endian
+const P: [u8; 32] = [
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFC, 0x2F,
+];
+
+// (p + 1) / 4 in big-endian
+const SQRT_EXP: [u8; 32] = [
+  0x3F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFF, 0xBF, 0xFF, 0xFF, 0x0C,
+];
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Field { // using 5x52 assuming 64-bit arch
-  n: Fe5x52,
+  pub(crate) n: Fe5x52,
 }
 
 impl From<Fe5x52> for Field {
@@ -50,6 +57,15 @@ impl Field {
     }
   }
 
+  // fe_set_int only accepts small values
+  fn from_int(n: u32) -> Self {
+    let mut r = Field::new();
+    unsafe {
+      fe_set_int(&mut r, n);
+    }
+    r
+  }
+
   pub fn inv(&self) -> Self {
     let mut r = Field::new();
     unsafe {
@@ -58,319 +74,305 @@ impl Field {
     r
   }
 
-/*
-  pub fn neg(&self) -> Self {
-    let mut r = Scalar::new();
+  fn normalize(&self) -> Self {
+    let mut r = *self;
     unsafe {
-      scalar_negate(&mut r, self);
+      fe_normalize(&mut r as *mut Field);
     }
     r
   }
 
-  // 32-byte random scalar
-  pub fn rand() -> Self {
-    let mut buf = [0u8; 32];
-    OsRng.fill_bytes(&mut buf);
-    Scalar::from(buf)
+  pub fn sq(&self) -> Self {
+    let mut r = Field::new();
+    unsafe {
+      fe_sq(&mut r, self);
+    }
+    r
+  }
+
+  fn pow(&self, exp: &[u8; 32]) -> Self {
+    let mut r = Field::from_int(1);
+    for byte in exp {
+      for i in (0..8).rev() {
+        r = r.sq();
+        if (byte >> i) & 1 == 1 {
+          r = r * self;
+        }
+      }
+    }
+    r
+  }
+
+  pub fn is_zero(&self) -> bool {
+    unsafe {
+      fe_is_zero(&self.normalize()) != 0
+    }
   }
 
-  pub fn to_hex(&self) -> String {
-    // TODO implement this
-    "ab".to_string()
+  pub fn is_odd(&self) -> bool {
+    self.serialize()[31] & 1 == 1
   }
 
+  // p = 3 mod 4, so a^((p+1)/4) is a square root of a if a has one
+  pub fn sqrt(&self) -> Option<Self> {
+    let r = self.pow(&SQRT_EXP);
+    if r.sq() == *self {
+      Some(r.normalize())
+    } else {
+      None
+    }
+  }
+
+  // x^3 + 7
+  pub(crate) fn curve_rhs(x: &Field) -> Self {
+    x.sq() * x + Field::from_int(7)
+  }
+
+  // 32-byte big-endian
   pub fn serialize(&self) -> Vec<u8> {
     let mut buf = [0u8; 32];
     unsafe {
-      scalar_get_b32(buf.as_mut_ptr(), self);
+      fe_get_b32(buf.as_mut_ptr(), &self.normalize());
     }
     buf.to_vec()
   }
 
   pub fn deserialize(buf: &[u8]) -> Result<Self, String> {
     if buf.len() != 32 {
-      return Err("Serialized Scalar should be 32-byte long".to_string());
+      return Err(format!("Serialized Field should be 32-byte long, but got {}-byte long", buf.len()));
+    }
+    if buf >= &P[..] {
+      return Err("Serialized Field is not less than the field prime".to_string());
+    }
+    let mut d = [0u64; 4];
+    for (i, limb) in d.iter_mut().enumerate() {
+      let mut bytes = [0u8; 8];
+      bytes.copy_from_slice(&buf[(3 - i) * 8..(4 - i) * 8]);
+      *limb = u64::from_be_bytes(bytes);
+    }
+    const M52: u64 = 0xFFFFFFFFFFFFF;
+    Ok(Field {
+      n: [
+        d[0] & M52,
+        ((d[0] >> 52) | (d[1] << 12)) & M52,
+        ((d[1] >> 40) | (d[2] << 24)) & M52,
+        ((d[2] >> 28) | (d[3] << 36)) & M52,
+        d[3] >> 16,
+      ],
+    })
+  }
+
+  pub fn to_hex(self) -> String {
+    format!("0x{}", hex::encode(self.serialize()))
+  }
+
+  pub fn from_hex(s: &str) -> Result<Self, String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let buf = hex::decode(s).map_err(|e| format!("Invalid Field hex: {}", e))?;
+    Field::deserialize(&buf)
+  }
+}
+
+impl From<u32> for Field {
+  fn from(n: u32) -> Self {
+    let mut buf = [0u8; 32];
+    buf[28..].copy_from_slice(&n.to_be_bytes());
+    Field::deserialize(&buf).unwrap()
+  }
+}
+
+macro_rules! impl_op {
+  ("nn", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
+    impl $trait<$rhs> for $lhs {
+      type Output = Field;
+
+      fn $op_fn(self, rhs: $rhs) -> Field {
+        let mut r = Field::new();
+        unsafe {
+          $ffi_fn(&mut r, &self, &rhs);
+        }
+        r.normalize()
+      }
     }
-    let mut s = Scalar::new();
+  };
+  ("rn", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
+    impl $trait<$rhs> for $lhs {
+      type Output = Field;
+
+      fn $op_fn(self, rhs: $rhs) -> Field {
+        let mut r = Field::new();
+        unsafe {
+          $ffi_fn(&mut r, self, &rhs);
+        }
+        r.normalize()
+      }
+    }
+  };
+  ("nr", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
+    impl $trait<$rhs> for $lhs {
+      type Output = Field;
+
+      fn $op_fn(self, rhs: $rhs) -> Field {
+        let mut r = Field::new();
+        unsafe {
+          $ffi_fn(&mut r, &self, rhs);
+        }
+        r.normalize()
+      }
+    }
+  };
+  ("rr", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
+    impl $trait<$rhs> for $lhs {
+      type Output = Field;
+
+      fn $op_fn(self, rhs: $rhs) -> Field {
+        let mut r = Field::new();
+        unsafe {
+          $ffi_fn(&mut r, self, rhs);
+        }
+        r.normalize()
+      }
+    }
+  };
+}
+
+// Add
+impl_op!("nn", Add, add, fe_add, Field, Field);
+impl_op!("rn", Add, add, fe_add, &Field, Field);
+impl_op!("nr", Add, add, fe_add, Field, &Field);
+impl_op!("rr", Add, add, fe_add, &Field, &Field);
+
+// Mul
+impl_op!("nn", Mul, mul, fe_mul, Field, Field);
+impl_op!("rn", Mul, mul, fe_mul, &Field, Field);
+impl_op!("nr", Mul, mul, fe_mul, Field, &Field);
+impl_op!("rr", Mul, mul, fe_mul, &Field, &Field);
+
+// p - a
+impl Neg for &Field {
+  type Output = Field;
+
+  fn neg(self) -> Field {
+    if self.is_zero() {
+      return Field::new();
+    }
+    let a = self.serialize();
+    let mut buf = [0u8; 32];
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+      let d = P[i] as i16 - a[i] as i16 - borrow;
+      borrow = (d < 0) as i16;
+      buf[i] = d.rem_euclid(256) as u8;
+    }
+    Field::deserialize(&buf).unwrap()
+  }
+}
+
+impl Neg for Field {
+  type Output = Field;
+
+  fn neg(self) -> Field {
+    -&self
+  }
+}
+
+// there is no fe_sub, so subtract by adding the negation
+macro_rules! impl_sub {
+  ($lhs:ty, $rhs:ty) => {
+    impl Sub<$rhs> for $lhs {
+      type Output = Field;
+
+      fn sub(self, rhs: $rhs) -> Field {
+        self + -rhs
+      }
+    }
+  };
+}
+impl_sub!(Field, Field);
+impl_sub!(&Field, Field);
+impl_sub!(Field, &Field);
+impl_sub!(&Field, &Field);
+
+impl PartialEq for Field {
+  fn eq(&self, rhs: &Self) -> bool {
     unsafe {
-      scalar_set_b32(&mut s, buf.as_ptr());
+      fe_is_equal(&self.normalize(), &rhs.normalize()) != 0
     }
-    Ok(s)
   }
-*/
 }
+impl Eq for Field {} // Field has total equality
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_add_sub() {
+    let a = Field::from(5u32);
+    let b = Field::from(7u32);
+    assert_eq!(a + b, Field::from(12u32));
+    assert_eq!(b - a, Field::from(2u32));
+    assert_eq!(a - b, -Field::from(2u32));
+    assert_eq!(a - a, Field::from(0u32));
+  }
+
+  #[test]
+  fn test_mul_inv() {
+    let a = Field::from(5u32);
+    let b = Field::from(7u32);
+    assert_eq!(a * b, Field::from(35u32));
+    assert_eq!(a * a.inv(), Field::from(1u32));
+    assert_eq!(a.sq(), Field::from(25u32));
+  }
+
+  #[test]
+  fn test_neg() {
+    let a = Field::from(5u32);
+    assert_eq!(a + -a, Field::from(0u32));
+    assert_eq!(-(-a), a);
+    assert_eq!(-Field::from(0u32), Field::from(0u32));
+    assert_eq!(
+      (-Field::from(1u32)).to_hex(),
+      "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+    );
+  }
+
+  #[test]
+  fn test_wrap_around() {
+    let p_minus_1 = -Field::from(1u32);
+    assert_eq!(p_minus_1 + Field::from(1u32), Field::from(0u32));
+    assert!(!p_minus_1.is_odd());
+    assert!(Field::from(1u32).is_odd());
+  }
+
+  #[test]
+  fn test_sqrt() {
+    let a = Field::from(5u32);
+    let r = a.sq().sqrt().unwrap();
+    assert!(r == a || r == -a);
+
+    // 7 is a quadratic non-residue mod p, so x = 0 is not on the curve
+    assert!(Field::from(7u32).sqrt().is_none());
+  }
+
+  #[test]
+  fn test_serialize() {
+    let a = Field::from(0x01020304u32);
+    let buf = a.serialize();
+    assert_eq!(buf.len(), 32);
+    assert_eq!(&buf[28..], &[1, 2, 3, 4]);
+    assert_eq!(Field::deserialize(&buf).unwrap(), a);
 
-// impl fmt::Display for Scalar {
-//   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-//     write!(f, "{}", u64::from(*self))
-//   }
-// }
-// 
-// macro_rules! impl_from_rhs {
-//   ($lhs:ty, $num_bytes:expr) => {
-//     impl From<Scalar> for $lhs {
-//       fn from(s: Scalar) -> Self {
-//         let mut buf = [0u8; 32];
-// 
-//         unsafe {
-//           scalar_get_b32(buf.as_mut_ptr(), &s);
-//         }
-//         let mut ret: $lhs = 0;
-// 
-//         for i in 0..$num_bytes {
-//           ret |= (buf[32 - 1 - i] as $lhs) << (i * 8);
-//         }
-//         ret
-//       }
-//     }
-//   }
-// }
-// impl_from_rhs!(u128, 16);
-// impl_from_rhs!(usize, 8);
-// impl_from_rhs!(u64, 8);
-// impl_from_rhs!(u32, 4);
-// impl_from_rhs!(u16, 2);
-// impl_from_rhs!(u8, 1);
-// 
-// macro_rules! impl_from_lhs {
-//   ($lhs:ty) => {
-//     impl From<$lhs> for Scalar {
-//       fn from(n: $lhs) -> Self {
-//         let mut s = Scalar::new();
-//         unsafe {
-//           scalar_set_int(&mut s, n as u32);
-//         }
-//         s
-//       }
-//     }
-//   }
-// }
-// impl_from_lhs!(usize);
-// impl_from_lhs!(u64);
-// impl_from_lhs!(u32);
-// impl_from_lhs!(u16);
-// impl_from_lhs!(u8);
-// 
-// impl From<[u8; 32]> for Scalar {
-//   fn from(buf: [u8; 32]) -> Self {
-//     let mut s = Scalar::new();
-//     unsafe {
-//       scalar_set_b32(&mut s, buf.as_ptr());
-//     }
-//     s
-//   }
-// }
-// 
-// impl From<&Integer> for Scalar {
-//   fn from(i: &Integer) -> Self {
-//     let mut s = Scalar::new();
-//     let mut buf = i.to_digits::<u8>(Order::MsfBe);
-//     
-//     // make buf 32 bytes
-//     if buf.len() > 32 {
-//       buf.drain(0..(buf.len() - 32));
-//     } else if buf.len() < 32 {
-//       let mut padded_buf = vec![0u8; 32 - buf.len()];
-//       padded_buf.extend_from_slice(&buf);
-//       buf = padded_buf;
-//     }
-//     
-//     let mut buf_array = [0u8; 32];
-//     buf_array.copy_from_slice(&buf);
-//     unsafe {
-//       scalar_set_b32(&mut s, buf_array.as_ptr());
-//     }
-//     s
-//   }
-// }
-// 
-// impl From<Scalar> for Integer {
-//   fn from(s: Scalar) -> Self {
-//     let buf = s.serialize();
-//     Integer::from_digits(&buf, Order::MsfBe)
-//   }
-// }
-// 
-// macro_rules! impl_op {
-//   ("nn", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
-//     impl $trait<$rhs> for $lhs {
-//       type Output = Scalar;
-// 
-//       fn $op_fn(self, rhs: $rhs) -> Scalar {
-//         let mut r = Scalar::new();
-//         unsafe {
-//           $ffi_fn(&mut r, &self, &rhs);
-//         }
-//         r
-//       }
-//     }
-//   };
-//   ("rn", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
-//     impl $trait<$rhs> for $lhs {
-//       type Output = Scalar;
-// 
-//       fn $op_fn(self, rhs: $rhs) -> Scalar {
-//         let mut r = Scalar::new();
-//         unsafe {
-//           $ffi_fn(&mut r, self, &rhs);
-//         }
-//         r
-//       }
-//     }
-//   };
-//   ("nr", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
-//     impl $trait<$rhs> for $lhs {
-//       type Output = Scalar;
-// 
-//       fn $op_fn(self, rhs: $rhs) -> Scalar {
-//         let mut r = Scalar::new();
-//         unsafe {
-//           $ffi_fn(&mut r, &self, rhs);
-//         }
-//         r
-//       }
-//     }
-//   };
-//   ("rr", $trait:ident, $op_fn:ident, $ffi_fn:ident, $lhs:ty, $rhs:ty) => {
-//     impl $trait<$rhs> for $lhs {
-//       type Output = Scalar;
-// 
-//       fn $op_fn(self, rhs: $rhs) -> Scalar {
-//         let mut r = Scalar::new();
-//         unsafe {
-//           $ffi_fn(&mut r, self, rhs);
-//         }
-//         r
-//       }
-//     }
-//   };
-// }
-// 
-// // Add
-// impl_op!("nn", Add, add, scalar_add, Scalar, Scalar);
-// impl_op!("rn", Add, add, scalar_add, &Scalar, Scalar);
-// impl_op!("nr", Add, add, scalar_add, Scalar, &Scalar);
-// impl_op!("rr", Add, add, scalar_add, &Scalar, &Scalar);
-// 
-// // Sub
-// impl_op!("nn", Sub, sub, scalar_sub, Scalar, Scalar);
-// impl_op!("rn", Sub, sub, scalar_sub, &Scalar, Scalar);
-// impl_op!("nr", Sub, sub, scalar_sub, Scalar, &Scalar);
-// impl_op!("rr", Sub, sub, scalar_sub, &Scalar, &Scalar);
-// 
-// // Mul
-// impl_op!("nn", Mul, mul, scalar_mul, Scalar, Scalar);
-// impl_op!("rn", Mul, mul, scalar_mul, &Scalar, Scalar);
-// impl_op!("nr", Mul, mul, scalar_mul, Scalar, &Scalar);
-// impl_op!("rr", Mul, mul, scalar_mul, &Scalar, &Scalar);
-// 
-// impl AddAssign<Scalar> for Scalar {
-//   fn add_assign(&mut self, rhs: Self) {
-//     let res = self.clone() + rhs;
-//     self.d = res.d;
-//   }
-// }
-// 
-// impl MulAssign<Scalar> for Scalar {
-//   fn mul_assign(&mut self, rhs: Self) {
-//     let res = self.clone() * rhs;
-//     self.d = res.d;
-//   }
-// }
-// 
-// impl PartialEq for Scalar {
-//   fn eq(&self, rhs: &Self) -> bool {
-//     let r;
-//     unsafe {
-//       r = scalar_eq(self, rhs);
-//     }
-//     r != 0
-//   }
-// }
-// impl Eq for Scalar {} // Scalar has total equality
-//  
-// #[cfg(test)]
-// mod tests {
-//   use super::*;
-// 
-//   #[test]
-//   fn test_int_conv() {
-//     let a = Scalar::from(5u32);
-//     let a_u64: u64 = a.into();
-//     assert_eq!(a_u64, 5u64);
-// 
-//     let a = Scalar::from(1000u32);
-//     let a_u64: u64 = a.into();
-//     assert_eq!(a_u64, 1000u64);
-//   }
-// 
-//   #[test]
-//   fn test_eq() {
-//     let a = Scalar::from(5u32);
-//     let b = Scalar::from(7u32);
-//     assert_eq!(a, a);
-//     assert_eq!(b, b);
-//     assert_ne!(a, b);
-//   }
-// 
-//   #[test]
-//   fn test_add() {
-//     let a = Scalar::from(5u32);
-//     let b = Scalar::from(7u32);
-//     let act = a + b;
-//     let exp = Scalar::from(12u32);
-//     assert_eq!(act, exp);
-//   }
-// 
-//   #[test]
-//   fn test_sub() {
-//     let a = Scalar::from(5u32);
-//     let b = Scalar::from(7u32);
-//     let act = b - a;
-//     let exp = Scalar::from(2u32);
-//     assert_eq!(act, exp);
-//   }
-// 
-//   #[test]
-//   fn test_mul() {
-//     let a = Scalar::from(5u32);
-//     let b = Scalar::from(7u32);
-//     let act = b * a;
-//     let exp = Scalar::from(35u32);
-//     assert_eq!(act, exp);
-//   }
-// 
-//   #[test]
-//   fn test_neg() {
-//     let a = Scalar::from(5u32);
-//     let a_neg = a.neg();
-//     let act = a + a_neg;
-//     let exp = Scalar::zero();
-//     assert_eq!(act, exp);
-// 
-//     assert_eq!(a_neg, a_neg);
-//     assert_eq!(a, a_neg.neg());
-//   }
-// 
-//   #[test]
-//   fn test_inv() {
-//     let a = Scalar::from(5u32);
-//     let a_inv = a.inv();
-//     let act = a * a_inv;
-//     let exp = Scalar::from(1u32);
-//     assert_eq!(act, exp);
-// 
-//     assert_eq!(a_inv, a_inv);
-//     assert_eq!(a, a_inv.inv());
-//   }
-// 
-//   #[test]
-//   fn test_rand() {
-//     let a = Scalar::rand();
-//     let b = Scalar::rand();
-//     assert_ne!(a, b);
-//   }
-// }
- 
- 
+    assert!(Field::deserialize(&buf[1..]).is_err());
+    assert!(Field::deserialize(&P).is_err());
+  }
 
+  #[test]
+  fn test_hex() {
+    let a = -Field::from(2u32);
+    let s = a.to_hex();
+    assert_eq!(Field::from_hex(&s).unwrap(), a);
+    assert_eq!(Field::from_hex(&s[2..]).unwrap(), a);
+    assert!(Field::from_hex("0xzz").is_err());
+  }
+}
@@ -0,0 +1,252 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+// secp256k1_XMD:SHA-256_SSWU_RO_ suite of RFC 9380
+
+use crate::building_block::secp256k1::{
+  affine_point::AffinePoint,
+  field::Field,
+  jacobian_point::JacobianPoint,
+};
+use sha2::{Digest, Sha256};
+
+// isogenous curve E': y^2 = x^3 + A'x + B'
+const ISO_A: &str = "3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533";
+const ISO_B: &str = "00000000000000000000000000000000000000000000000000000000000006eb";
+const Z: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc24"; // -11
+
+// 3-isogeny map constants k_(i,j) from lowest to highest degree
+const K1: [&str; 4] = [
+  "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7",
+  "07d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581",
+  "534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262",
+  "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c",
+];
+const K2: [&str; 2] = [
+  "d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b",
+  "edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14",
+];
+const K3: [&str; 4] = [
+  "4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c",
+  "c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3",
+  "29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931",
+  "2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84",
+];
+const K4: [&str; 3] = [
+  "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b",
+  "7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573",
+  "6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f",
+];
+
+fn fe(hex: &str) -> Field {
+  Field::from_hex(hex).unwrap()
+}
+
+// a[0] + a[1] * x + a[2] * x^2 + ...
+fn eval_poly(a: &[&str], x: &Field) -> Field {
+  a.iter().rev().fold(Field::from(0u32), |acc, k| acc * x + fe(k))
+}
+
+pub fn expand_message_xmd(
+  msg: &[u8],
+  dst: &[u8],
+  len_in_bytes: usize,
+) -> Result<Vec<u8>, String> {
+  let ell = len_in_bytes.div_ceil(32);
+  if ell > 255 || len_in_bytes > 0xffff {
+    return Err(format!("Cannot expand message to {} bytes", len_in_bytes));
+  }
+  // DST longer than 255 bytes is replaced with its hash
+  let dst = if dst.len() > 255 {
+    Sha256::new()
+      .chain_update(b"H2C-OVERSIZE-DST-")
+      .chain_update(dst)
+      .finalize()
+      .to_vec()
+  } else {
+    dst.to_vec()
+  };
+  let dst_prime = [&dst[..], &[dst.len() as u8]].concat();
+
+  let b_0 = Sha256::new()
+    .chain_update([0u8; 64])
+    .chain_update(msg)
+    .chain_update((len_in_bytes as u16).to_be_bytes())
+    .chain_update([0u8])
+    .chain_update(&dst_prime)
+    .finalize();
+
+  let mut uniform_bytes = Vec::with_capacity(ell * 32);
+  let mut b_i = Sha256::new()
+    .chain_update(b_0)
+    .chain_update([1u8])
+    .chain_update(&dst_prime)
+    .finalize();
+  uniform_bytes.extend_from_slice(&b_i);
+
+  for i in 2..=ell {
+    let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+    b_i = Sha256::new()
+      .chain_update(xored)
+      .chain_update([i as u8])
+      .chain_update(&dst_prime)
+      .finalize();
+    uniform_bytes.extend_from_slice(&b_i);
+  }
+  uniform_bytes.truncate(len_in_bytes);
+  Ok(uniform_bytes)
+}
+
+// count field elements from 48-byte chunks (L = 48) of the expanded message
+pub fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Result<Vec<Field>, String> {
+  let uniform_bytes = expand_message_xmd(msg, dst, count * 48)?;
+
+  // 2^192
+  let f_2_192 = fe("0000000000000001000000000000000000000000000000000000000000000000");
+
+  // split into two 24-byte halves so that each fits in a field element
+  let to_field = |buf: &[u8]| {
+    let mut padded = [0u8; 32];
+    padded[8..].copy_from_slice(buf);
+    Field::deserialize(&padded).unwrap()
+  };
+  Ok(uniform_bytes.chunks(48).map(|chunk| {
+    to_field(&chunk[..24]) * f_2_192 + to_field(&chunk[24..])
+  }).collect())
+}
+
+// simplified SWU onto E'. variable-time
+fn map_to_iso_curve(u: &Field) -> (Field, Field) {
+  let (a, b, z) = (fe(ISO_A), fe(ISO_B), fe(Z));
+  let one = Field::from(1u32);
+
+  let z_u2 = z * u.sq();
+  let tv1 = z_u2.sq() + z_u2;
+  let x1 = if tv1.is_zero() {
+    b * (z * a).inv()
+  } else {
+    -b * a.inv() * (one + tv1.inv())
+  };
+  let g = |x: &Field| x.sq() * x + a * x + b;
+
+  let (x, y) = match g(&x1).sqrt() {
+    Some(y) => (x1, y),
+    None => {
+      let x2 = z_u2 * x1;
+      // g(x2) is square whenever g(x1) is not
+      (x2, g(&x2).sqrt().unwrap())
+    },
+  };
+  let y = if u.is_odd() == y.is_odd() { y } else { -y };
+  (x, y)
+}
+
+// 3-isogeny from E' to secp256k1
+fn iso_map(x: &Field, y: &Field) -> JacobianPoint {
+  let x_num = eval_poly(&K1, x);
+  let x_den = eval_poly(&K2, x) + x.sq();
+  let y_num = eval_poly(&K3, x);
+  let y_den = eval_poly(&K4, x) + x.sq() * x;
+
+  if x_den.is_zero() || y_den.is_zero() {
+    return JacobianPoint::point_at_infinity();
+  }
+  let pt = AffinePoint {
+    x: (x_num * x_den.inv()).n,
+    y: (y * y_num * y_den.inv()).n,
+    infinity: 0,
+  };
+  pt.into()
+}
+
+pub fn map_to_curve(u: &Field) -> JacobianPoint {
+  let (x, y) = map_to_iso_curve(u);
+  iso_map(&x, &y)
+}
+
+// the cofactor of secp256k1 is 1, so no clearing is needed
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<JacobianPoint, String> {
+  let u = hash_to_field(msg, dst, 2)?;
+  Ok(map_to_curve(&u[0]) + map_to_curve(&u[1]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const DST: &[u8] = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+
+  struct TestVector {
+    msg: &'static [u8],
+    p_x: &'static str,
+    p_y: &'static str,
+    u_0: &'static str,
+    u_1: &'static str,
+    q0_x: &'static str,
+    q0_y: &'static str,
+  }
+
+  // RFC 9380 J.8.1
+  const TEST_VECTORS: [TestVector; 3] = [
+    TestVector {
+      msg: b"",
+      p_x: "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+      p_y: "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+      u_0: "6b0f9910dd2ba71c78f2ee9f04d73b5f4c5f7fc773a701abea1e573cab002fb3",
+      u_1: "1ae6c212e08fe1a5937f6202f929a2cc8ef4ee5b9782db68b0d5799fd8f09e16",
+      q0_x: "74519ef88b32b425a095e4ebcc84d81b64e9e2c2675340a720bb1a1857b99f1e",
+      q0_y: "c174fa322ab7c192e11748beed45b508e9fdb1ce046dee9c2cd3a2a86b410936",
+    },
+    TestVector {
+      msg: b"abc",
+      p_x: "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+      p_y: "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+      u_0: "128aab5d3679a1f7601e3bdf94ced1f43e491f544767e18a4873f397b08a2b61",
+      u_1: "5897b65da3b595a813d0fdcc75c895dc531be76a03518b044daaa0f2e4689e00",
+      q0_x: "07dd9432d426845fb19857d1b3a91722436604ccbbbadad8523b8fc38a5322d7",
+      q0_y: "604588ef5138cffe3277bbd590b8550bcbe0e523bbaf1bed4014a467122eb33f",
+    },
+    TestVector {
+      msg: b"abcdef0123456789",
+      p_x: "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+      p_y: "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+      u_0: "ea67a7c02f2cd5d8b87715c169d055a22520f74daeb080e6180958380e2f98b9",
+      u_1: "7434d0d1a500d38380d1f9615c021857ac8d546925f5f2355319d823a478da18",
+      q0_x: "576d43ab0260275adf11af990d130a5752704f79478628761720808862544b5d",
+      q0_y: "643c4a7fb68ae6cff55edd66b809087434bbaff0c07f3f9ec4d49bb3c16623c3",
+    },
+  ];
+
+  #[test]
+  fn test_expand_message_xmd() {
+    // RFC 9380 K.1
+    let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+    assert_eq!(
+      hex::encode(expand_message_xmd(b"", dst, 0x20).unwrap()),
+      "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+    );
+    assert_eq!(
+      hex::encode(expand_message_xmd(b"abc", dst, 0x20).unwrap()),
+      "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+    );
+    assert_eq!(expand_message_xmd(b"", dst, 0x80).unwrap().len(), 0x80);
+    assert!(expand_message_xmd(b"", dst, 256 * 32).is_err());
+  }
+
+  #[test]
+  fn test_hash_to_curve() {
+    for tv in TEST_VECTORS {
+      let u = hash_to_field(tv.msg, DST, 2).unwrap();
+      assert_eq!(hex::encode(u[0].serialize()), tv.u_0);
+      assert_eq!(hex::encode(u[1].serialize()), tv.u_1);
+
+      let q0 = map_to_curve(&u[0]).to_affine();
+      assert_eq!(hex::encode(q0.x().serialize()), tv.q0_x);
+      assert_eq!(hex::encode(q0.y().serialize()), tv.q0_y);
+
+      let p = hash_to_curve(tv.msg, DST).unwrap().to_affine();
+      assert_eq!(hex::encode(p.x().serialize()), tv.p_x);
+      assert_eq!(hex::encode(p.y().serialize()), tv.p_y);
+    }
+  }
+}
@@ -4,26 +4,52 @@
 use std::{
   cmp::PartialEq,
   ffi::c_int,
-  ops::{Add, AddAssign, Mul},
+  fmt,
+  iter::Sum,
+  ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+  str::FromStr,
+  sync::OnceLock,
 };
 use crate::building_block::secp256k1::{
   affine_point::AffinePoint,
-  ffi::{
+  backend::{
     group_add,
     group_mul,
     group_eq,
     group_get_base_point,
   },
   field::{Field, Fe5x52},
+  hash_to_curve,
   scalar::Scalar,
 };
 use serde::{
-  Serialize,
   Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
 };
 
+// G * j * 16^i for the i-th 4-bit window and j in [0, 16)
+static BASE_TABLES: OnceLock<Vec<[JacobianPoint; 16]>> = OnceLock::new();
+
+fn base_tables() -> &'static [[JacobianPoint; 16]] {
+  BASE_TABLES.get_or_init(|| {
+    let mut tables = Vec::with_capacity(64);
+    let mut base = JacobianPoint::get_base_point();
+    for _ in 0..64 {
+      let mut table = [JacobianPoint::new(); 16];
+      for j in 1..16 {
+        table[j] = table[j - 1] + base;
+      }
+      base = table[15] + base;
+      tables.push(table);
+    }
+    tables
+  })
+}
+
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
 pub struct JacobianPoint { // using 5x52 assuming 64-bit arch
   pub x: Fe5x52,
   pub y: Fe5x52,
@@ -72,19 +98,179 @@ impl JacobianPoint {
   pub fn deserialize(bytes: &[u8]) -> Self {
     bincode::deserialize(bytes).unwrap()
   }
+
+  pub fn to_sec1(self, compressed: bool) -> Vec<u8> {
+    self.to_affine().to_sec1(compressed)
+  }
+
+  pub fn from_sec1(buf: &[u8]) -> Result<Self, String> {
+    AffinePoint::from_sec1(buf).map(|pt| pt.into())
+  }
+
+  // secp256k1_XMD:SHA-256_SSWU_RO_ of RFC 9380
+  pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<Self, String> {
+    hash_to_curve::hash_to_curve(msg, dst)
+  }
+
+  // converts all points with a single field inversion using Montgomery's trick
+  pub fn batch_to_affine(points: &[JacobianPoint]) -> Vec<AffinePoint> {
+    let one = Field::from(1u32);
+
+    // prods[i] = product of z of the finite points in points[..i]
+    let mut prods = Vec::with_capacity(points.len());
+    let mut acc = one;
+    for p in points {
+      prods.push(acc);
+      if p.infinity == 0 {
+        acc = acc * p.z();
+      }
+    }
+
+    let mut acc_inv = acc.inv();
+    let mut affine_pts = vec![AffinePoint::new(); points.len()];
+    for (i, p) in points.iter().enumerate().rev() {
+      if p.infinity != 0 {
+        continue;
+      }
+      let z_inv = acc_inv * prods[i];
+      acc_inv = acc_inv * p.z();
+
+      let z_inv2 = z_inv.sq();
+      let z_inv3 = z_inv2 * z_inv;
+      affine_pts[i] = AffinePoint {
+        x: (Field::from(p.x) * z_inv2).n,
+        y: (Field::from(p.y) * z_inv3).n,
+        infinity: 0,
+      };
+    }
+    affine_pts
+  }
+
+  // G * k using the precomputed tables. needs no doublings.
+  // variable-time, so k must be public. use mul_base_secret otherwise
+  pub fn mul_base(k: &Scalar) -> JacobianPoint {
+    let mut r = JacobianPoint::new();
+    for (i, table) in base_tables().iter().enumerate() {
+      let nibble = (k.d[i / 16] >> ((i % 16) * 4)) & 0xf;
+      if nibble != 0 {
+        r += table[nibble as usize];
+      }
+    }
+    r
+  }
+
+  // G * k for secret k. see mul_secret
+  pub fn mul_base_secret(k: &Scalar) -> JacobianPoint {
+    JacobianPoint::get_base_point().mul_secret(k)
+  }
+
+  // self * k for secret k with a sequence of group operations and table
+  // accesses that doesn't depend on k. the signed windows of libsecp256k1
+  // are replaced by digits offset by 16 so that no table entry is the point
+  // at infinity: with k' = k - c where c = sum of 16^(i+1) for i in [0, 64),
+  // the sum of (d_i + 16) * 16^i over the nibbles d_i of k' is k. group_add
+  // branches only on infinity and equal points, which happen at the same
+  // steps for every k. libsecp256k1's field arithmetic is constant time apart
+  // from the zero checks of its addition, while the pure-Rust backend's is
+  // not constant time at all. the * operator is variable-time in both
+  pub fn mul_secret(&self, k: &Scalar) -> JacobianPoint {
+    if self.infinity != 0 {
+      return *self;
+    }
+    // table[j] = (j + 16) * self
+    let mut table = [*self; 16];
+    for _ in 0..4 {
+      table[0] = table[0] + table[0];
+    }
+    for j in 1..16 {
+      table[j] = table[j - 1] + self;
+    }
+    let k = k - window_offset();
+
+    let mut r = JacobianPoint::new();
+    for i in (0..64).rev() {
+      for _ in 0..4 {
+        r = r + r;
+      }
+      let nibble = (k.d[i / 16] >> ((i % 16) * 4)) & 0xf;
+      r += select(&table, nibble);
+    }
+    r
+  }
+
+  // sum of p_i * k_i using Strauss' method. the doublings are shared by all
+  // terms and each point gets a 4-bit window table. variable-time
+  pub fn multi_mul(terms: &[(JacobianPoint, Scalar)]) -> JacobianPoint {
+    let tables: Vec<[JacobianPoint; 16]> = terms.iter().map(|(p, _)| {
+      let mut table = [JacobianPoint::new(); 16];
+      for i in 1..16 {
+        table[i] = table[i - 1] + p;
+      }
+      table
+    }).collect();
+
+    let mut r = JacobianPoint::new();
+    for i in (0..64).rev() {
+      if r.infinity == 0 {
+        for _ in 0..4 {
+          r = r + r;
+        }
+      }
+      for ((_, k), table) in terms.iter().zip(&tables) {
+        let nibble = (k.d[i / 16] >> ((i % 16) * 4)) & 0xf;
+        if nibble != 0 {
+          r += table[nibble as usize];
+        }
+      }
+    }
+    r
+  }
+}
+
+// sum of 16^(i+1) for i in [0, 64) mod n
+fn window_offset() -> Scalar {
+  let (one, sixteen) = (Scalar::from(1u32), Scalar::from(16u32));
+  (0..64).fold(Scalar::zero(), |acc, _| (acc + one) * sixteen)
+}
+
+// table[idx] read by touching every entry
+fn select(table: &[JacobianPoint; 16], idx: u64) -> JacobianPoint {
+  let mut r = JacobianPoint { infinity: 0, ..JacobianPoint::new() };
+  for (j, p) in table.iter().enumerate() {
+    let mask = 0u64.wrapping_sub((j as u64 == idx) as u64);
+    for l in 0..5 {
+      r.x[l] |= p.x[l] & mask;
+      r.y[l] |= p.y[l] & mask;
+      r.z[l] |= p.z[l] & mask;
+    }
+  }
+  r
+}
+
+impl From<AffinePoint> for JacobianPoint {
+  fn from(pt: AffinePoint) -> Self {
+    if pt.is_infinity() {
+      return JacobianPoint::new();
+    }
+    JacobianPoint {
+      x: pt.x,
+      y: pt.y,
+      z: [1, 0, 0, 0, 0],
+      infinity: 0,
+    }
+  }
 }
 
+// n is typically a private key, so this takes the mul_base_secret path
 impl From<Scalar> for JacobianPoint {
   fn from(n: Scalar) -> Self {
-    let g = JacobianPoint::get_base_point();
-    g * n
+    JacobianPoint::mul_base_secret(&n)
   }
 }
 
 impl From<&Scalar> for JacobianPoint {
   fn from(n: &Scalar) -> Self {
-    let g = JacobianPoint::get_base_point();
-    g * n
+    JacobianPoint::mul_base_secret(n)
   }
 }
 
@@ -149,7 +335,7 @@ impl_op!("nr", Add, add, group_add, JacobianPoint, &JacobianPoint);
 impl_op!("rn", Add, add, group_add, &JacobianPoint, JacobianPoint);
 impl_op!("rr", Add, add, group_add, &JacobianPoint, &JacobianPoint);
 
-// Mul
+// Mul. variable-time, so only for public scalars
 impl_op!("nr", Mul, mul, group_mul, JacobianPoint, Scalar);
 impl_op!("rr", Mul, mul, group_mul, &JacobianPoint, Scalar);
 
@@ -177,14 +363,86 @@ impl Mul<&Scalar> for &JacobianPoint {
   }
 }
 
-impl AddAssign<JacobianPoint> for JacobianPoint {
-  fn add_assign(&mut self, rhs: Self) {
-    let res = self.clone() + rhs;
+// k * P reads as in the papers
+macro_rules! impl_scalar_mul {
+  ($lhs:ty, $rhs:ty) => {
+    impl Mul<$rhs> for $lhs {
+      type Output = JacobianPoint;
+
+      fn mul(self, rhs: $rhs) -> JacobianPoint {
+        rhs * self
+      }
+    }
+  };
+}
+impl_scalar_mul!(Scalar, JacobianPoint);
+impl_scalar_mul!(&Scalar, JacobianPoint);
+impl_scalar_mul!(Scalar, &JacobianPoint);
+impl_scalar_mul!(&Scalar, &JacobianPoint);
+
+// (x, y) -> (x, -y)
+impl Neg for &JacobianPoint {
+  type Output = JacobianPoint;
+
+  fn neg(self) -> JacobianPoint {
+    if self.infinity != 0 {
+      return *self;
+    }
+    JacobianPoint {
+      y: (-Field::from(self.y)).n,
+      ..*self
+    }
+  }
+}
+
+impl Neg for JacobianPoint {
+  type Output = JacobianPoint;
 
-    self.x = res.x;
-    self.y = res.y;
-    self.z = res.z;
-    self.infinity = res.infinity;
+  fn neg(self) -> JacobianPoint {
+    -&self
+  }
+}
+
+// Sub
+macro_rules! impl_sub {
+  ($lhs:ty, $rhs:ty) => {
+    impl Sub<$rhs> for $lhs {
+      type Output = JacobianPoint;
+
+      fn sub(self, rhs: $rhs) -> JacobianPoint {
+        self + -rhs
+      }
+    }
+  };
+}
+impl_sub!(JacobianPoint, JacobianPoint);
+impl_sub!(&JacobianPoint, JacobianPoint);
+impl_sub!(JacobianPoint, &JacobianPoint);
+impl_sub!(&JacobianPoint, &JacobianPoint);
+
+macro_rules! impl_assign_op {
+  ($trait:ident, $op_fn:ident, $op:tt, $rhs:ty) => {
+    impl $trait<$rhs> for JacobianPoint {
+      fn $op_fn(&mut self, rhs: $rhs) {
+        *self = *self $op rhs;
+      }
+    }
+  };
+}
+impl_assign_op!(AddAssign, add_assign, +, JacobianPoint);
+impl_assign_op!(AddAssign, add_assign, +, &JacobianPoint);
+impl_assign_op!(SubAssign, sub_assign, -, JacobianPoint);
+impl_assign_op!(SubAssign, sub_assign, -, &JacobianPoint);
+
+impl Sum for JacobianPoint {
+  fn sum<I: Iterator<Item = JacobianPoint>>(iter: I) -> Self {
+    iter.fold(JacobianPoint::new(), |acc, x| acc + x)
+  }
+}
+
+impl<'a> Sum<&'a JacobianPoint> for JacobianPoint {
+  fn sum<I: Iterator<Item = &'a JacobianPoint>>(iter: I) -> Self {
+    iter.fold(JacobianPoint::new(), |acc, x| acc + x)
   }
 }
 
@@ -199,6 +457,46 @@ impl PartialEq for JacobianPoint {
 }
 impl Eq for JacobianPoint {} // JacobianPoint has total equality
 
+// same encoding as AffinePoint
+impl Serialize for JacobianPoint {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.to_affine().serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for JacobianPoint {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    AffinePoint::deserialize(deserializer).map(|pt| pt.into())
+  }
+}
+
+// compressed SEC1 in hex
+impl fmt::Display for JacobianPoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(&self.to_affine(), f)
+  }
+}
+
+impl fmt::LowerHex for JacobianPoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::LowerHex::fmt(&self.to_affine(), f)
+  }
+}
+
+impl fmt::UpperHex for JacobianPoint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::UpperHex::fmt(&self.to_affine(), f)
+  }
+}
+
+impl FromStr for JacobianPoint {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    AffinePoint::from_str(s).map(|pt| pt.into())
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -214,6 +512,109 @@ mod tests {
     assert_ne!(a, c);
   }
 
+  #[test]
+  fn test_batch_to_affine() {
+    let mut points: Vec<JacobianPoint> = (0..10)
+      .map(|_| JacobianPoint::from(Scalar::rand()) + JacobianPoint::get_base_point())
+      .collect();
+    points[4] = JacobianPoint::point_at_infinity();
+
+    let affine_pts = JacobianPoint::batch_to_affine(&points);
+    assert_eq!(affine_pts.len(), points.len());
+    for (p, affine_pt) in points.iter().zip(&affine_pts) {
+      assert_eq!(p.to_affine().to_sec1(false), affine_pt.to_sec1(false));
+    }
+    assert!(affine_pts[4].is_infinity());
+    assert!(JacobianPoint::batch_to_affine(&[]).is_empty());
+  }
+
+  #[test]
+  fn test_mul_base() {
+    let g = JacobianPoint::get_base_point();
+    for _ in 0..10 {
+      let k = Scalar::rand();
+      assert_eq!(JacobianPoint::mul_base(&k), g * k);
+    }
+    let n_minus_1 = Scalar::from(1u8).neg();
+    assert_eq!(JacobianPoint::mul_base(&n_minus_1) + g, JacobianPoint::point_at_infinity());
+    assert_eq!(JacobianPoint::mul_base(&Scalar::zero()), JacobianPoint::point_at_infinity());
+    assert_eq!(JacobianPoint::mul_base(&Scalar::from(1u8)), g);
+  }
+
+  #[test]
+  fn test_mul_secret() {
+    let g = JacobianPoint::get_base_point();
+    for _ in 0..10 {
+      let k = Scalar::rand();
+      let p = g * k;
+      assert_eq!(JacobianPoint::mul_base_secret(&k), p);
+      assert_eq!(JacobianPoint::from(k), p);
+      assert_eq!(p.mul_secret(&k), p * k);
+    }
+    for k in [1u32, 15, 16, 17, 256] {
+      assert_eq!(g.mul_secret(&Scalar::from(k)), g * Scalar::from(k));
+    }
+    let inf = JacobianPoint::point_at_infinity();
+    assert_eq!(JacobianPoint::mul_base_secret(&Scalar::zero()), inf);
+    assert_eq!(g.mul_secret(&Scalar::from(1u8).neg()) + g, inf);
+    assert_eq!(inf.mul_secret(&Scalar::rand()), inf);
+  }
+
+  #[test]
+  fn test_multi_mul() {
+    let terms: Vec<(JacobianPoint, Scalar)> = (0..5)
+      .map(|_| (JacobianPoint::from(Scalar::rand()), Scalar::rand()))
+      .collect();
+    let exp = terms.iter().fold(
+      JacobianPoint::point_at_infinity(),
+      |acc, (p, k)| acc + p * k,
+    );
+    assert_eq!(JacobianPoint::multi_mul(&terms), exp);
+
+    // zero scalars, the point at infinity and opposite terms
+    let g = JacobianPoint::get_base_point();
+    let k = Scalar::rand();
+    let terms = vec![
+      (g, Scalar::zero()),
+      (JacobianPoint::point_at_infinity(), k),
+      (g, k),
+      (g, k.neg()),
+    ];
+    assert_eq!(JacobianPoint::multi_mul(&terms), JacobianPoint::point_at_infinity());
+    assert_eq!(JacobianPoint::multi_mul(&[]), JacobianPoint::point_at_infinity());
+  }
+
+  #[test]
+  #[allow(clippy::op_ref)]
+  fn test_neg_sub() {
+    let g = JacobianPoint::get_base_point();
+    let k = Scalar::rand();
+    let p = g * k;
+    assert_eq!(-p, g * -k);
+    assert_eq!(p - p, JacobianPoint::point_at_infinity());
+    assert_eq!(&p - g, g * (k - Scalar::from(1u8)));
+    assert_eq!(-JacobianPoint::point_at_infinity(), JacobianPoint::point_at_infinity());
+
+    let mut q = p;
+    q -= &g;
+    q += g;
+    assert_eq!(q, p);
+    q -= p;
+    assert_eq!(q, JacobianPoint::point_at_infinity());
+  }
+
+  #[test]
+  fn test_sum_and_scalar_lhs() {
+    let g = JacobianPoint::get_base_point();
+    let ks: Vec<Scalar> = (0..5).map(|_| Scalar::rand()).collect();
+    let ps: Vec<JacobianPoint> = ks.iter().map(|k| k * g).collect();
+
+    let exp = g * ks.iter().sum::<Scalar>();
+    assert_eq!(ps.iter().sum::<JacobianPoint>(), exp);
+    assert_eq!(ps.into_iter().sum::<JacobianPoint>(), exp);
+    assert_eq!(ks[0] * g, g * ks[0]);
+  }
+
   #[test]
   fn test_eq() {
     let a = JacobianPoint::get_base_point();
@@ -225,4 +626,49 @@ mod tests {
     assert_ne!(a, c);
     assert_ne!(b, c);
   }
+
+  #[test]
+  fn test_sec1_roundtrip() {
+    for _ in 0..10 {
+      let p = JacobianPoint::from(Scalar::rand());
+      for compressed in [true, false] {
+        let buf = p.to_sec1(compressed);
+        assert_eq!(buf.len(), if compressed { 33 } else { 65 });
+        assert_eq!(JacobianPoint::from_sec1(&buf).unwrap(), p);
+      }
+    }
+  }
+
+  #[test]
+  fn test_sec1_point_at_infinity() {
+    let inf = JacobianPoint::point_at_infinity();
+    assert_eq!(inf.to_sec1(true), vec![0x00]);
+    assert!(JacobianPoint::from_sec1(&[0x00]).is_err());
+  }
+
+  #[test]
+  fn test_text_formats() {
+    let p = JacobianPoint::from(Scalar::rand());
+    assert_eq!(JacobianPoint::from_str(&p.to_string()).unwrap(), p);
+    assert_eq!(format!("{:#x}", p).parse::<JacobianPoint>().unwrap(), p);
+    assert_eq!(format!("{:X}", p).parse::<JacobianPoint>().unwrap(), p);
+    assert_eq!(JacobianPoint::point_at_infinity().to_string(), "00");
+  }
+
+  #[test]
+  fn test_serde() {
+    let p = JacobianPoint::from(Scalar::rand());
+    let json = serde_json::to_string(&p).unwrap();
+    assert_eq!(json, format!("\"{}\"", p));
+    assert_eq!(serde_json::from_str::<JacobianPoint>(&json).unwrap(), p);
+
+    let bin = p.serialize();
+    assert_eq!(bin.len(), 33);
+    assert_eq!(JacobianPoint::deserialize(&bin), p);
+
+    // rejected as in from_sec1
+    let inf = JacobianPoint::point_at_infinity();
+    assert!(bincode::deserialize::<JacobianPoint>(&inf.serialize()).is_err());
+    assert!(serde_json::from_str::<JacobianPoint>(&serde_json::to_string(&inf).unwrap()).is_err());
+  }
 }
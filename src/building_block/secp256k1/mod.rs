@@ -1,6 +1,18 @@
 pub mod affine_point;
+pub mod curve;
+#[cfg(not(feature = "pure-rust"))]
 pub mod ffi;
 pub mod field;
+pub mod hash_to_curve;
 pub mod jacobian_point;
+pub mod native;
 pub mod scalar;
+pub mod schnorr;
+pub mod secret_scalar;
 pub mod util;
+
+// libsecp256k1 through ffi by default, the pure-Rust port with `pure-rust`
+#[cfg(not(feature = "pure-rust"))]
+use ffi as backend;
+#[cfg(feature = "pure-rust")]
+use native as backend;
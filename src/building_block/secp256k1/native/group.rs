@@ -0,0 +1,176 @@
+use crate::building_block::arith::{
+  self,
+  U256,
+  P,
+};
+
+// jacobian point over normalized field elements: (x, y) = (X/Z^2, Y/Z^3)
+#[derive(Debug, Copy, Clone)]
+pub struct Gej {
+  pub x: U256,
+  pub y: U256,
+  pub z: U256,
+  pub infinity: bool,
+}
+
+pub const G: Gej = Gej {
+  x: [
+    0x59F2815B16F81798,
+    0x029BFCDB2DCE28D9,
+    0x55A06295CE870B07,
+    0x79BE667EF9DCBBAC,
+  ],
+  y: [
+    0x9C47D08FFB10D4B8,
+    0xFD17B448A6855419,
+    0x5DA4FBFC0E1108A8,
+    0x483ADA7726A3C465,
+  ],
+  z: arith::ONE,
+  infinity: false,
+};
+
+impl Gej {
+  pub fn infinity() -> Self {
+    Gej {
+      x: arith::ZERO,
+      y: arith::ZERO,
+      z: arith::ZERO,
+      infinity: true,
+    }
+  }
+
+  // dbl-2009-l (a = 0)
+  pub fn double(&self) -> Self {
+    if self.infinity || arith::is_zero(&self.y) {
+      return Gej::infinity();
+    }
+    let a = P.sq(&self.x);
+    let b = P.sq(&self.y);
+    let c = P.sq(&b);
+    let d = {
+      let t = P.sq(&P.add(&self.x, &b));
+      let t = P.sub(&P.sub(&t, &a), &c);
+      P.add(&t, &t)
+    };
+    let e = P.add(&P.add(&a, &a), &a);
+    let f = P.sq(&e);
+
+    let x = P.sub(&f, &P.add(&d, &d));
+    let c8 = {
+      let c2 = P.add(&c, &c);
+      let c4 = P.add(&c2, &c2);
+      P.add(&c4, &c4)
+    };
+    let y = P.sub(&P.mul(&e, &P.sub(&d, &x)), &c8);
+    let z = {
+      let t = P.mul(&self.y, &self.z);
+      P.add(&t, &t)
+    };
+    Gej { x, y, z, infinity: false }
+  }
+
+  // add-2007-bl, falling back to doubling when both points are equal
+  pub fn add(&self, rhs: &Gej) -> Self {
+    if self.infinity {
+      return *rhs;
+    }
+    if rhs.infinity {
+      return *self;
+    }
+    let z1z1 = P.sq(&self.z);
+    let z2z2 = P.sq(&rhs.z);
+    let u1 = P.mul(&self.x, &z2z2);
+    let u2 = P.mul(&rhs.x, &z1z1);
+    let s1 = P.mul(&P.mul(&self.y, &rhs.z), &z2z2);
+    let s2 = P.mul(&P.mul(&rhs.y, &self.z), &z1z1);
+    let h = P.sub(&u2, &u1);
+    let r = P.sub(&s2, &s1);
+
+    if arith::is_zero(&h) {
+      return if arith::is_zero(&r) {
+        self.double()
+      } else {
+        Gej::infinity()
+      };
+    }
+    let hh = P.sq(&h);
+    let hhh = P.mul(&h, &hh);
+    let v = P.mul(&u1, &hh);
+
+    let x = P.sub(&P.sub(&P.sq(&r), &hhh), &P.add(&v, &v));
+    let y = P.sub(&P.mul(&r, &P.sub(&v, &x)), &P.mul(&s1, &hhh));
+    let z = P.mul(&P.mul(&self.z, &rhs.z), &h);
+    Gej { x, y, z, infinity: false }
+  }
+
+  // variable-time fixed-window (w = 4) multiplication
+  pub fn mul(&self, k: &U256) -> Self {
+    let mut table = [Gej::infinity(); 16];
+    for i in 1..16 {
+      table[i] = table[i - 1].add(self);
+    }
+
+    let mut r = Gej::infinity();
+    for i in (0..64).rev() {
+      for _ in 0..4 {
+        r = r.double();
+      }
+      let nibble = (k[i / 16] >> ((i % 16) * 4)) & 0xf;
+      if nibble != 0 {
+        r = r.add(&table[nibble as usize]);
+      }
+    }
+    r
+  }
+
+  pub fn eq(&self, rhs: &Gej) -> bool {
+    if self.infinity || rhs.infinity {
+      return self.infinity == rhs.infinity;
+    }
+    let z1z1 = P.sq(&self.z);
+    let z2z2 = P.sq(&rhs.z);
+    let x_eq = P.mul(&self.x, &z2z2) == P.mul(&rhs.x, &z1z1);
+    let y_eq =
+      P.mul(&P.mul(&self.y, &rhs.z), &z2z2) ==
+      P.mul(&P.mul(&rhs.y, &self.z), &z1z1);
+    x_eq && y_eq
+  }
+
+  // returns affine (x, y)
+  pub fn to_affine(self) -> (U256, U256) {
+    let z_inv = P.inv(&self.z);
+    let z_inv2 = P.sq(&z_inv);
+    let z_inv3 = P.mul(&z_inv2, &z_inv);
+    (P.mul(&self.x, &z_inv2), P.mul(&self.y, &z_inv3))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn is_on_curve(p: &Gej) -> bool {
+    let (x, y) = p.to_affine();
+    let rhs = P.add(&P.mul(&P.sq(&x), &x), &[7, 0, 0, 0]);
+    P.sq(&y) == rhs
+  }
+
+  #[test]
+  fn test_double_add() {
+    let g2 = G.double();
+    let g3 = g2.add(&G);
+    assert!(is_on_curve(&g2));
+    assert!(is_on_curve(&g3));
+    assert!(G.add(&G).eq(&g2));
+    assert!(g3.eq(&G.add(&g2)));
+    assert!(!g3.eq(&g2));
+  }
+
+  #[test]
+  fn test_mul_by_order() {
+    assert!(G.mul(&arith::N.m).infinity);
+    let n_minus_1 = arith::sub(&arith::N.m, &arith::ONE).0;
+    assert!(G.mul(&n_minus_1).add(&G).infinity);
+  }
+}
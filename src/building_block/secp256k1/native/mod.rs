@@ -0,0 +1,318 @@
+#![allow(dead_code)]
+#![allow(clippy::missing_safety_doc)]
+
+// Pure-Rust drop-in for the secp256k1-export functions declared in ffi.rs.
+// Every function keeps the signature of its C counterpart so that Scalar,
+// Field and the point types work with either backend unchanged.
+
+mod group;
+
+use std::ffi::{
+  c_int,
+  c_uchar,
+};
+use crate::building_block::secp256k1::{
+  affine_point::AffinePoint,
+  field::{Field, Fe5x52},
+  jacobian_point::JacobianPoint,
+  scalar::Scalar,
+};
+use crate::building_block::arith::{
+  self,
+  U256,
+  N,
+  P,
+};
+use group::Gej;
+
+const M52: u64 = 0xFFFFFFFFFFFFF;
+
+// limbs of an unnormalized field element can exceed 52 bits,
+// so accumulate them into a 512-bit value and reduce
+fn fe_from_5x52(n: &Fe5x52) -> U256 {
+  let mut w = [0u64; 8];
+  for (i, &limb) in n.iter().enumerate() {
+    let shift = 52 * i;
+    let mut carry = (limb as u128) << (shift % 64);
+    let mut j = shift / 64;
+    while carry != 0 {
+      let t = w[j] as u128 + (carry as u64) as u128;
+      w[j] = t as u64;
+      carry = (carry >> 64) + (t >> 64);
+      j += 1;
+    }
+  }
+  P.reduce_wide(&w)
+}
+
+fn fe_to_5x52(a: &U256) -> Fe5x52 {
+  [
+    a[0] & M52,
+    ((a[0] >> 52) | (a[1] << 12)) & M52,
+    ((a[1] >> 40) | (a[2] << 24)) & M52,
+    ((a[2] >> 28) | (a[3] << 36)) & M52,
+    a[3] >> 16,
+  ]
+}
+
+fn to_gej(p: &JacobianPoint) -> Gej {
+  if p.infinity != 0 {
+    return Gej::infinity();
+  }
+  Gej {
+    x: fe_from_5x52(&p.x),
+    y: fe_from_5x52(&p.y),
+    z: fe_from_5x52(&p.z),
+    infinity: false,
+  }
+}
+
+fn from_gej(p: &Gej) -> JacobianPoint {
+  if p.infinity {
+    return JacobianPoint::point_at_infinity();
+  }
+  JacobianPoint {
+    x: fe_to_5x52(&p.x),
+    y: fe_to_5x52(&p.y),
+    z: fe_to_5x52(&p.z),
+    infinity: 0,
+  }
+}
+
+// Scalar
+pub unsafe fn scalar_set_int(r: *mut Scalar, n: u32) {
+  (*r).d = [n as u64, 0, 0, 0];
+}
+
+pub unsafe fn scalar_inverse(r: *mut Scalar, a: *const Scalar) {
+  (*r).d = N.inv(&(*a).d);
+}
+
+pub unsafe fn scalar_is_zero(a: *const Scalar) -> c_int {
+  arith::is_zero(&N.reduce(&(*a).d)) as c_int
+}
+
+pub unsafe fn scalar_negate(r: *mut Scalar, a: *const Scalar) {
+  (*r).d = N.neg(&(*a).d);
+}
+
+pub unsafe fn scalar_eq(a: *const Scalar, b: *const Scalar) -> c_int {
+  (N.reduce(&(*a).d) == N.reduce(&(*b).d)) as c_int
+}
+
+pub unsafe fn scalar_set_b32(r: *mut Scalar, buf: *const u8) {
+  let buf = &*(buf as *const [u8; 32]);
+  (*r).d = N.reduce(&arith::from_b32(buf));
+}
+
+pub unsafe fn scalar_get_b32(buf: *mut u8, a: *const Scalar) {
+  let b32 = arith::to_b32(&N.reduce(&(*a).d));
+  std::ptr::copy_nonoverlapping(b32.as_ptr(), buf, 32);
+}
+
+pub unsafe fn scalar_add(r: *mut Scalar, a: *const Scalar, b: *const Scalar) {
+  (*r).d = N.add(&(*a).d, &(*b).d);
+}
+
+pub unsafe fn scalar_sub(r: *mut Scalar, a: *const Scalar, b: *const Scalar) {
+  (*r).d = N.sub(&(*a).d, &(*b).d);
+}
+
+pub unsafe fn scalar_mul(r: *mut Scalar, a: *const Scalar, b: *const Scalar) {
+  (*r).d = N.mul(&(*a).d, &(*b).d);
+}
+
+// Field
+pub unsafe fn fe_add(r: *mut Field, a: *const Field, b: *const Field) {
+  let x = P.add(&fe_from_5x52(&(*a).n), &fe_from_5x52(&(*b).n));
+  (*r).n = fe_to_5x52(&x);
+}
+
+pub unsafe fn fe_is_equal(a: *const Field, b: *const Field) -> c_int {
+  (fe_from_5x52(&(*a).n) == fe_from_5x52(&(*b).n)) as c_int
+}
+
+pub unsafe fn fe_get_b32(r: *mut c_uchar, a: *const Field) {
+  let b32 = arith::to_b32(&fe_from_5x52(&(*a).n));
+  std::ptr::copy_nonoverlapping(b32.as_ptr(), r, 32);
+}
+
+pub unsafe fn fe_inv(r: *mut Field, a: *const Field) {
+  let x = P.inv(&fe_from_5x52(&(*a).n));
+  (*r).n = fe_to_5x52(&x);
+}
+
+pub unsafe fn fe_is_zero(a: *const Field) -> c_int {
+  arith::is_zero(&fe_from_5x52(&(*a).n)) as c_int
+}
+
+pub unsafe fn fe_mul(r: *mut Field, a: *const Field, b: *const Field) {
+  let x = P.mul(&fe_from_5x52(&(*a).n), &fe_from_5x52(&(*b).n));
+  (*r).n = fe_to_5x52(&x);
+}
+
+// mirrors the C signature which normalizes in place through a const pointer
+pub unsafe fn fe_normalize(a: *const Field) {
+  let a = a as *mut Field;
+  (*a).n = fe_to_5x52(&fe_from_5x52(&(*a).n));
+}
+
+pub unsafe fn fe_set_int(r: *mut Field, n: u32) {
+  (*r).n = [n as u64, 0, 0, 0, 0];
+}
+
+pub unsafe fn fe_sq(r: *mut Field, a: *const Field) {
+  let x = P.sq(&fe_from_5x52(&(*a).n));
+  (*r).n = fe_to_5x52(&x);
+}
+
+// Point
+pub unsafe fn group_add(r: *mut JacobianPoint, a: *const JacobianPoint, b: *const JacobianPoint) {
+  *r = from_gej(&to_gej(&*a).add(&to_gej(&*b)));
+}
+
+pub unsafe fn group_mul(r: *mut JacobianPoint, a: *const JacobianPoint, q: Scalar) {
+  *r = from_gej(&to_gej(&*a).mul(&N.reduce(&q.d)));
+}
+
+pub unsafe fn group_eq(a: *const JacobianPoint, b: *const JacobianPoint) -> c_int {
+  to_gej(&*a).eq(&to_gej(&*b)) as c_int
+}
+
+pub unsafe fn group_get_base_point(r: *mut JacobianPoint) {
+  *r = from_gej(&group::G);
+}
+
+pub unsafe fn group_ge_set_gej(r: *mut AffinePoint, a: *const JacobianPoint) {
+  let p = to_gej(&*a);
+  if p.infinity {
+    *r = AffinePoint::new();
+    return;
+  }
+  let (x, y) = p.to_affine();
+  (*r).x = fe_to_5x52(&x);
+  (*r).y = fe_to_5x52(&y);
+  (*r).infinity = 0;
+}
+
+// check the pure-Rust backend against libsecp256k1 when both are available
+#[cfg(all(test, not(feature = "pure-rust")))]
+mod tests {
+  use super::*;
+  use crate::building_block::secp256k1::ffi;
+
+  fn rand_scalar() -> Scalar {
+    Scalar::rand()
+  }
+
+  fn rand_field() -> Field {
+    Field::from(fe_to_5x52(&Scalar::rand().d))
+  }
+
+  fn ffi_normalize(mut fe: Field) -> Field {
+    unsafe {
+      ffi::fe_normalize(&mut fe as *mut Field);
+    }
+    fe
+  }
+
+  #[test]
+  fn test_scalar_ops_match_ffi() {
+    for _ in 0..100 {
+      let (a, b) = (rand_scalar(), rand_scalar());
+      let (mut r1, mut r2) = (Scalar::new(), Scalar::new());
+      unsafe {
+        scalar_add(&mut r1, &a, &b);
+        ffi::scalar_add(&mut r2, &a, &b);
+        assert_eq!(r1.d, r2.d);
+
+        scalar_sub(&mut r1, &a, &b);
+        ffi::scalar_sub(&mut r2, &a, &b);
+        assert_eq!(r1.d, r2.d);
+
+        scalar_mul(&mut r1, &a, &b);
+        ffi::scalar_mul(&mut r2, &a, &b);
+        assert_eq!(r1.d, r2.d);
+
+        scalar_inverse(&mut r1, &a);
+        ffi::scalar_inverse(&mut r2, &a);
+        assert_eq!(r1.d, r2.d);
+
+        scalar_negate(&mut r1, &a);
+        ffi::scalar_negate(&mut r2, &a);
+        assert_eq!(r1.d, r2.d);
+      }
+    }
+  }
+
+  #[test]
+  fn test_scalar_set_b32_match_ffi() {
+    let buf = [0xffu8; 32]; // overflows n
+    let (mut r1, mut r2) = (Scalar::new(), Scalar::new());
+    unsafe {
+      scalar_set_b32(&mut r1, buf.as_ptr());
+      ffi::scalar_set_b32(&mut r2, buf.as_ptr());
+    }
+    assert_eq!(r1.d, r2.d);
+  }
+
+  #[test]
+  fn test_field_ops_match_ffi() {
+    for _ in 0..100 {
+      let (a, b) = (rand_field(), rand_field());
+      let (mut r1, mut r2) = (Field::from([0; 5]), Field::from([0; 5]));
+      let (mut b1, mut b2) = ([0u8; 32], [0u8; 32]);
+      unsafe {
+        fe_mul(&mut r1, &a, &b);
+        ffi::fe_mul(&mut r2, &a, &b);
+        assert_eq!(r1.n, ffi_normalize(r2).n);
+
+        fe_add(&mut r1, &a, &b);
+        ffi::fe_add(&mut r2, &a, &b);
+        assert_eq!(r1.n, ffi_normalize(r2).n);
+
+        fe_inv(&mut r1, &a);
+        ffi::fe_inv(&mut r2, &a);
+        assert_eq!(r1.n, ffi_normalize(r2).n);
+
+        fe_sq(&mut r1, &a);
+        ffi::fe_sq(&mut r2, &a);
+        fe_get_b32(b1.as_mut_ptr(), &r1);
+        ffi::fe_get_b32(b2.as_mut_ptr(), &ffi_normalize(r2));
+        assert_eq!(b1, b2);
+      }
+    }
+  }
+
+  #[test]
+  fn test_group_ops_match_ffi() {
+    let mut g1 = JacobianPoint::point_at_infinity();
+    let mut g2 = JacobianPoint::point_at_infinity();
+    unsafe {
+      group_get_base_point(&mut g1);
+      ffi::group_get_base_point(&mut g2);
+      assert!(ffi::group_eq(&g1, &g2) != 0);
+    }
+
+    for _ in 0..10 {
+      let k = rand_scalar();
+      let (mut p1, mut p2) = (g1, g2);
+      let (mut q1, mut q2) = (g1, g2);
+      let (mut a1, mut a2) = (AffinePoint::new(), AffinePoint::new());
+      unsafe {
+        group_mul(&mut p1, &g1, k);
+        ffi::group_mul(&mut p2, &g2, k);
+        assert!(ffi::group_eq(&p1, &p2) != 0);
+        assert!(group_eq(&p1, &p2) != 0);
+
+        group_add(&mut q1, &p1, &g1);
+        ffi::group_add(&mut q2, &p2, &g2);
+        assert!(ffi::group_eq(&q1, &q2) != 0);
+
+        group_ge_set_gej(&mut a1, &q1);
+        ffi::group_ge_set_gej(&mut a2, &q2);
+        assert_eq!(a1.x, ffi_normalize(Field::from(a2.x)).n);
+      }
+    }
+  }
+}
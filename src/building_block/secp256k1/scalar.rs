@@ -4,12 +4,13 @@
 use std::{
   cmp::PartialEq,
   fmt,
-  ops::{Add, AddAssign, Sub, Mul, MulAssign},
+  iter::{Product, Sum},
+  ops::{Add, AddAssign, Div, DivAssign, Neg, Sub, SubAssign, Mul, MulAssign},
+  str::FromStr,
 };
+use crate::building_block::curve::CurveScalar;
 use crate::building_block::secp256k1::{
-  ffi::{
-    fe_get_b32,
-    fe_normalize,
+  backend::{
     scalar_add,
     scalar_eq,
     scalar_get_b32,
@@ -19,32 +20,38 @@ use crate::building_block::secp256k1::{
     scalar_negate,
     scalar_sub,
     scalar_set_b32,
-    scalar_set_int,
   },
   field::Field,
 };
 use rand::{
   rngs::OsRng,
+  CryptoRng,
   RngCore,
 };
 use rug::{
   integer::Order,
   Integer,
 };
+use crate::building_block::serde_util;
 use serde::{
-  //de::{
-  //  self,
-  //  SeqAccess,
-  //  Visitor,
-  //},
+  de,
   Deserialize,
-  //Deserializer,
+  Deserializer,
   Serialize,
-  //Serializer,
+  Serializer,
 };
+use zeroize::Zeroize;
+
+// group order n in big-endian
+const N: [u8; 32] = [
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+  0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B,
+  0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36, 0x41, 0x41,
+];
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone)]
 pub struct Scalar { // using 4x64 assuming 64-bit arch
   pub d: [u64; 4],
 }
@@ -82,10 +89,20 @@ impl Scalar {
     r
   }
 
+  // inverts all scalars with a single inversion using Montgomery's trick.
+  // zeros are mapped to zero as inv() does
+  pub fn batch_inv(xs: &[Scalar]) -> Vec<Scalar> {
+    <Scalar as CurveScalar>::batch_inv(xs)
+  }
+
   // 32-byte random scalar
   pub fn rand() -> Self {
+    Scalar::rand_with_rng(&mut OsRng)
+  }
+
+  pub fn rand_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
     let mut buf = [0u8; 32];
-    OsRng.fill_bytes(&mut buf);
+    rng.fill_bytes(&mut buf);
     Scalar::from(buf)
   }
 
@@ -100,6 +117,10 @@ impl Scalar {
   }
 
   pub fn rand_bits(n: usize) -> Self {
+    Scalar::rand_bits_with_rng(n, &mut OsRng)
+  }
+
+  pub fn rand_bits_with_rng(n: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self {
       assert!(n <= 256, "n should be less than or equal to 256");
       if n == 0 {
         return Scalar::zero();
@@ -108,7 +129,6 @@ impl Scalar {
       let rem_bits  = (n - 1) % 64 + 1;
 
       let mut d = [0u64;4];
-      let mut rng = OsRng;
       for i in 0..=top_limb {
         d[i] = rng.next_u64();
       }
@@ -123,12 +143,9 @@ impl Scalar {
       Scalar { d }
   }
 
-  pub fn to_hex(&self) -> String {
-    let mut buf = [0u8; 32];
-    for (i, limb) in self.d.iter().enumerate() {
-      buf[i * 8..(i + 1) * 8].copy_from_slice(&limb.to_le_bytes());
-    }
-    format!("0x{}", hex::encode(buf))
+  // 0x-prefixed 32-byte big-endian
+  pub fn to_hex(self) -> String {
+    format!("{:#x}", self)
   }
 
   pub fn secp256k1_serialize(&self) -> Vec<u8> {
@@ -149,71 +166,166 @@ impl Scalar {
     }
     Ok(s)
   }
+
+  // 32-byte big-endian x mod n, and whether x was not less than n
+  pub fn from_bytes_reduced(buf: &[u8; 32]) -> (Self, bool) {
+    (Scalar::from(*buf), *buf >= N)
+  }
+
+  // fails instead of reducing if x is not less than n
+  pub fn from_bytes_checked(buf: &[u8; 32]) -> Result<Self, String> {
+    match Scalar::from_bytes_reduced(buf) {
+      (s, false) => Ok(s),
+      (_, true) => Err("Serialized Scalar is not less than the group order".to_string()),
+    }
+  }
 }
 
+// x mod n. use from_bytes_reduced to tell whether x was reduced
 impl From<Field> for Scalar {
   fn from(field: Field) -> Self {
-    let mut scalar = Scalar::new();
-    unsafe {
-      fe_normalize(&field);
-      let mut buf = [0u8; 32];
-      fe_get_b32(buf.as_mut_ptr(), &field);
-      scalar_set_b32(&mut scalar, buf.as_ptr()); // TODO handle overflow?
-      scalar
-    }
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(&field.serialize());
+    Scalar::from_bytes_reduced(&buf).0
   }
 }
 
 impl fmt::Display for Scalar {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{}", u64::from(*self))
+    write!(f, "{}", Integer::from(self))
   }
 }
 
-macro_rules! impl_from_rhs {
-  ($lhs:ty, $num_bytes:expr) => {
-    impl From<Scalar> for $lhs {
-      fn from(s: Scalar) -> Self {
-        let mut buf = [0u8; 32];
+// 64 hex digits in big-endian. {:#x} adds the 0x prefix
+impl fmt::LowerHex for Scalar {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode(self.secp256k1_serialize()))
+  }
+}
+
+impl fmt::UpperHex for Scalar {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode_upper(self.secp256k1_serialize()))
+  }
+}
+
+// 32-byte big-endian, as hex in human-readable formats
+impl Serialize for Scalar {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut buf = [0u8; 32];
+    unsafe {
+      scalar_get_b32(buf.as_mut_ptr(), self);
+    }
+    serde_util::serialize_fixed(&buf, serializer)
+  }
+}
+
+// values not less than n are rejected instead of being reduced
+impl<'de> Deserialize<'de> for Scalar {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let buf = serde_util::deserialize_fixed(deserializer)?;
+    Scalar::from_bytes_checked(&buf).map_err(de::Error::custom)
+  }
+}
+
+// accepts decimal and 0x-prefixed hex. values not less than n are rejected
+impl FromStr for Scalar {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+      Some(hex) => (hex, 16),
+      None => (s, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+      return Err(format!("Invalid Scalar string: {}", s));
+    }
+    let i = Integer::from_str_radix(digits, radix as i32)
+      .map_err(|e| format!("Invalid Scalar string {}: {}", s, e))?;
+    if i >= Integer::from_digits(&N, Order::MsfBe) {
+      return Err(format!("Scalar {} is not less than the group order", s));
+    }
+    Ok(Scalar::from(&i))
+  }
+}
+
+macro_rules! impl_try_from_scalar {
+  ($lhs:ty) => {
+    impl TryFrom<Scalar> for $lhs {
+      type Error = String;
 
+      fn try_from(s: Scalar) -> Result<Self, Self::Error> {
+        let mut buf = [0u8; 32];
         unsafe {
           scalar_get_b32(buf.as_mut_ptr(), &s);
         }
+        let num_bytes = std::mem::size_of::<$lhs>();
+        if buf[..32 - num_bytes].iter().any(|&b| b != 0) {
+          return Err(format!("Scalar {} does not fit in {}", s, stringify!($lhs)));
+        }
         let mut ret: $lhs = 0;
-
-        for i in 0..$num_bytes {
-          ret |= (buf[32 - 1 - i] as $lhs) << (i * 8);
+        for (i, b) in buf[32 - num_bytes..].iter().rev().enumerate() {
+          ret |= (*b as $lhs) << (i * 8);
         }
-        ret
+        Ok(ret)
       }
     }
   }
 }
-impl_from_rhs!(u128, 16);
-impl_from_rhs!(usize, 8);
-impl_from_rhs!(u64, 8);
-impl_from_rhs!(u32, 4);
-impl_from_rhs!(u16, 2);
-impl_from_rhs!(u8, 1);
-
-macro_rules! impl_from_lhs {
+impl_try_from_scalar!(u128);
+impl_try_from_scalar!(usize);
+impl_try_from_scalar!(u64);
+impl_try_from_scalar!(u32);
+impl_try_from_scalar!(u16);
+impl_try_from_scalar!(u8);
+
+// any u128 is less than n, so no reduction happens
+impl From<u128> for Scalar {
+  fn from(n: u128) -> Self {
+    let mut buf = [0u8; 32];
+    buf[16..].copy_from_slice(&n.to_be_bytes());
+    Scalar::from(buf)
+  }
+}
+
+macro_rules! impl_from_unsigned {
   ($lhs:ty) => {
     impl From<$lhs> for Scalar {
       fn from(n: $lhs) -> Self {
-        let mut s = Scalar::new();
-        unsafe {
-          scalar_set_int(&mut s, n as u32);
-        }
-        s
+        Scalar::from(n as u128)
+      }
+    }
+  }
+}
+impl_from_unsigned!(usize);
+impl_from_unsigned!(u64);
+impl_from_unsigned!(u32);
+impl_from_unsigned!(u16);
+impl_from_unsigned!(u8);
+
+// negative values are mapped to n - |x|
+macro_rules! impl_from_signed {
+  ($lhs:ty) => {
+    impl From<$lhs> for Scalar {
+      fn from(n: $lhs) -> Self {
+        let abs = Scalar::from(n.unsigned_abs() as u128);
+        if n < 0 { abs.neg() } else { abs }
       }
     }
   }
 }
-impl_from_lhs!(usize);
-impl_from_lhs!(u64);
-impl_from_lhs!(u32);
-impl_from_lhs!(u16);
-impl_from_lhs!(u8);
+impl_from_signed!(i128);
+impl_from_signed!(isize);
+impl_from_signed!(i64);
+impl_from_signed!(i32);
+impl_from_signed!(i16);
+impl_from_signed!(i8);
 
 impl From<[u8; 32]> for Scalar {
   fn from(buf: [u8; 32]) -> Self {
@@ -341,17 +453,83 @@ impl_op!("rn", Mul, mul, scalar_mul, &Scalar, Scalar);
 impl_op!("nr", Mul, mul, scalar_mul, Scalar, &Scalar);
 impl_op!("rr", Mul, mul, scalar_mul, &Scalar, &Scalar);
 
-impl AddAssign<Scalar> for Scalar {
-  fn add_assign(&mut self, rhs: Self) {
-    let res = self.clone() + rhs;
-    self.d = res.d;
+// Div
+macro_rules! impl_div {
+  ($lhs:ty, $rhs:ty) => {
+    impl Div<$rhs> for $lhs {
+      type Output = Scalar;
+
+      #[allow(clippy::suspicious_arithmetic_impl)]
+      fn div(self, rhs: $rhs) -> Scalar {
+        self * rhs.inv()
+      }
+    }
+  };
+}
+impl_div!(Scalar, Scalar);
+impl_div!(&Scalar, Scalar);
+impl_div!(Scalar, &Scalar);
+impl_div!(&Scalar, &Scalar);
+
+macro_rules! impl_assign_op {
+  ($trait:ident, $op_fn:ident, $op:tt, $rhs:ty) => {
+    impl $trait<$rhs> for Scalar {
+      fn $op_fn(&mut self, rhs: $rhs) {
+        *self = *self $op rhs;
+      }
+    }
+  };
+}
+impl_assign_op!(AddAssign, add_assign, +, Scalar);
+impl_assign_op!(AddAssign, add_assign, +, &Scalar);
+impl_assign_op!(SubAssign, sub_assign, -, Scalar);
+impl_assign_op!(SubAssign, sub_assign, -, &Scalar);
+impl_assign_op!(MulAssign, mul_assign, *, Scalar);
+impl_assign_op!(MulAssign, mul_assign, *, &Scalar);
+impl_assign_op!(DivAssign, div_assign, /, Scalar);
+impl_assign_op!(DivAssign, div_assign, /, &Scalar);
+
+impl Neg for &Scalar {
+  type Output = Scalar;
+
+  fn neg(self) -> Scalar {
+    let mut r = Scalar::new();
+    unsafe {
+      scalar_negate(&mut r, self);
+    }
+    r
+  }
+}
+
+impl Neg for Scalar {
+  type Output = Scalar;
+
+  fn neg(self) -> Scalar {
+    -&self
+  }
+}
+
+impl Sum for Scalar {
+  fn sum<I: Iterator<Item = Scalar>>(iter: I) -> Self {
+    iter.fold(Scalar::zero(), |acc, x| acc + x)
   }
 }
 
-impl MulAssign<Scalar> for Scalar {
-  fn mul_assign(&mut self, rhs: Self) {
-    let res = self.clone() * rhs;
-    self.d = res.d;
+impl<'a> Sum<&'a Scalar> for Scalar {
+  fn sum<I: Iterator<Item = &'a Scalar>>(iter: I) -> Self {
+    iter.fold(Scalar::zero(), |acc, x| acc + x)
+  }
+}
+
+impl Product for Scalar {
+  fn product<I: Iterator<Item = Scalar>>(iter: I) -> Self {
+    iter.fold(Scalar::from(1u8), |acc, x| acc * x)
+  }
+}
+
+impl<'a> Product<&'a Scalar> for Scalar {
+  fn product<I: Iterator<Item = &'a Scalar>>(iter: I) -> Self {
+    iter.fold(Scalar::from(1u8), |acc, x| acc * x)
   }
 }
 
@@ -365,6 +543,12 @@ impl PartialEq for Scalar {
   }
 }
 impl Eq for Scalar {} // Scalar has total equality
+
+impl Zeroize for Scalar {
+  fn zeroize(&mut self) {
+    self.d.zeroize();
+  }
+}
  
 #[cfg(test)]
 mod tests {
@@ -373,14 +557,113 @@ mod tests {
   #[test]
   fn test_int_conv() {
     let a = Scalar::from(5u32);
-    let a_u64: u64 = a.into();
+    let a_u64: u64 = a.try_into().unwrap();
     assert_eq!(a_u64, 5u64);
 
     let a = Scalar::from(1000u32);
-    let a_u64: u64 = a.into();
+    let a_u64: u64 = a.try_into().unwrap();
     assert_eq!(a_u64, 1000u64);
   }
 
+  #[test]
+  fn test_int_conv_full_width() {
+    let a = Scalar::from(u64::MAX);
+    assert_eq!(u64::try_from(a).unwrap(), u64::MAX);
+    assert!(u32::try_from(a).is_err());
+
+    let a = Scalar::from(u128::MAX);
+    assert_eq!(u128::try_from(a).unwrap(), u128::MAX);
+    assert!(u64::try_from(a).is_err());
+    assert_eq!(a.to_string(), u128::MAX.to_string());
+
+    let a = Scalar::from(usize::MAX);
+    assert_eq!(usize::try_from(a).unwrap(), usize::MAX);
+
+    assert!(u128::try_from(Scalar::from(-1i32)).is_err());
+  }
+
+  #[test]
+  fn test_signed_conv() {
+    assert_eq!(Scalar::from(-1i64), Scalar::from(1u8).neg());
+    assert_eq!(Scalar::from(-5i32) + Scalar::from(5i32), Scalar::zero());
+    assert_eq!(Scalar::from(i128::MIN), Scalar::from(1u128 << 127).neg());
+    assert_eq!(Scalar::from(7i8), Scalar::from(7u8));
+  }
+
+  #[test]
+  fn test_from_bytes_reduced() {
+    let (s, reduced) = Scalar::from_bytes_reduced(&[0x01; 32]);
+    assert!(!reduced);
+    assert_eq!(s.secp256k1_serialize(), vec![0x01; 32]);
+    assert!(Scalar::from_bytes_checked(&[0x01; 32]).is_ok());
+
+    let (s, reduced) = Scalar::from_bytes_reduced(&N);
+    assert!(reduced);
+    assert!(s.is_zero());
+    assert!(Scalar::from_bytes_checked(&N).is_err());
+
+    let (s, reduced) = Scalar::from_bytes_reduced(&[0xff; 32]);
+    assert!(reduced);
+    let exp = Integer::from_digits(&[0xffu8; 32], Order::MsfBe)
+      - Integer::from_digits(&N, Order::MsfBe);
+    assert_eq!(Integer::from(s), exp);
+  }
+
+  #[test]
+  fn test_text_formats() {
+    let a = Scalar::from(0x0102u32);
+    let hex = format!("{:0>64}", "102");
+    assert_eq!(format!("{:x}", a), hex);
+    assert_eq!(format!("{:#x}", a), format!("0x{}", hex));
+    assert_eq!(a.to_hex(), format!("0x{}", hex));
+    assert_eq!(format!("{:X}", Scalar::from(0xabu8)), format!("{:0>64}", "AB"));
+    assert_eq!(a.to_string(), "258");
+
+    let n_minus_1 = Scalar::from(1u8).neg();
+    assert_eq!(
+      n_minus_1.to_hex(),
+      "0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+    );
+    assert_eq!(
+      n_minus_1.to_string(),
+      "115792089237316195423570985008687907852837564279074904382605163141518161494336",
+    );
+  }
+
+  #[test]
+  fn test_from_str() {
+    let a = Scalar::rand();
+    assert_eq!(Scalar::from_str(&a.to_string()).unwrap(), a);
+    assert_eq!(Scalar::from_str(&a.to_hex()).unwrap(), a);
+    assert_eq!(Scalar::from_str(&format!("{:#X}", a)).unwrap(), a);
+    assert_eq!("0x1f".parse::<Scalar>().unwrap(), Scalar::from(31u8));
+
+    assert!(Scalar::from_str("").is_err());
+    assert!(Scalar::from_str("0x").is_err());
+    assert!(Scalar::from_str("-1").is_err());
+    assert!(Scalar::from_str("12a").is_err());
+    let n = format!("0x{}", hex::encode(N));
+    assert!(Scalar::from_str(&n).is_err());
+  }
+
+  #[test]
+  fn test_serde() {
+    let a = Scalar::from(31u8);
+    let json = serde_json::to_string(&a).unwrap();
+    assert_eq!(json, format!("\"{:x}\"", a));
+    assert_eq!(serde_json::from_str::<Scalar>(&json).unwrap(), a);
+
+    let a = Scalar::rand();
+    let bin = bincode::serialize(&a).unwrap();
+    assert_eq!(bin, a.secp256k1_serialize());
+    assert_eq!(bincode::deserialize::<Scalar>(&bin).unwrap(), a);
+
+    // n is not a valid scalar
+    assert!(bincode::deserialize::<Scalar>(&N).is_err());
+    assert!(serde_json::from_str::<Scalar>(&format!("\"{}\"", hex::encode(N))).is_err());
+    assert!(serde_json::from_str::<Scalar>("\"1f\"").is_err());
+  }
+
   #[test]
   fn test_eq() {
     let a = Scalar::from(5u32);
@@ -429,6 +712,57 @@ mod tests {
     assert_eq!(a, a_neg.neg());
   }
 
+  #[test]
+  fn test_neg_trait() {
+    let a = Scalar::from(5u32);
+    assert_eq!(-a, a.neg());
+    assert_eq!(-&a, a.neg());
+    assert_eq!(-(-a), a);
+    assert_eq!(-Scalar::zero(), Scalar::zero());
+  }
+
+  #[test]
+  #[allow(clippy::op_ref)]
+  fn test_div() {
+    let a = Scalar::from(35u32);
+    let b = Scalar::from(7u32);
+    let exp = Scalar::from(5u32);
+    assert_eq!(a / b, exp);
+    assert_eq!(&a / b, exp);
+    assert_eq!(a / &b, exp);
+    assert_eq!(&a / &b, exp);
+    assert_eq!(Scalar::from(1u32) / b, b.inv());
+  }
+
+  #[test]
+  fn test_assign_ops() {
+    let b = Scalar::from(7u32);
+    let mut a = Scalar::from(5u32);
+    a += b;
+    assert_eq!(a, Scalar::from(12u32));
+    a -= &b;
+    assert_eq!(a, Scalar::from(5u32));
+    a *= &b;
+    assert_eq!(a, Scalar::from(35u32));
+    a /= b;
+    assert_eq!(a, Scalar::from(5u32));
+    a -= Scalar::from(6u32);
+    assert_eq!(a, -Scalar::from(1u32));
+  }
+
+  #[test]
+  fn test_sum_product() {
+    let xs: Vec<Scalar> = (1..=5u32).map(Scalar::from).collect();
+    assert_eq!(xs.iter().sum::<Scalar>(), Scalar::from(15u32));
+    assert_eq!(xs.clone().into_iter().sum::<Scalar>(), Scalar::from(15u32));
+    assert_eq!(xs.iter().product::<Scalar>(), Scalar::from(120u32));
+    assert_eq!(xs.into_iter().product::<Scalar>(), Scalar::from(120u32));
+
+    let empty: Vec<Scalar> = vec![];
+    assert_eq!(empty.iter().sum::<Scalar>(), Scalar::zero());
+    assert_eq!(empty.iter().product::<Scalar>(), Scalar::from(1u32));
+  }
+
   #[test]
   fn test_inv() {
     let a = Scalar::from(5u32);
@@ -441,12 +775,40 @@ mod tests {
     assert_eq!(a, a_inv.inv());
   }
 
+  #[test]
+  fn test_batch_inv() {
+    let mut xs: Vec<Scalar> = (0..10).map(|_| Scalar::rand()).collect();
+    xs[3] = Scalar::zero();
+    let invs = Scalar::batch_inv(&xs);
+    assert_eq!(invs.len(), xs.len());
+    for (x, x_inv) in xs.iter().zip(&invs) {
+      assert_eq!(x.inv(), *x_inv);
+    }
+    assert!(invs[3].is_zero());
+    assert!(Scalar::batch_inv(&[]).is_empty());
+  }
+
   #[test]
   fn test_rand() {
     let a = Scalar::rand();
     let b = Scalar::rand();
     assert_ne!(a, b);
   }
+
+  #[test]
+  fn test_rand_with_rng() {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    let mut rng1 = ChaCha20Rng::seed_from_u64(1);
+    let mut rng2 = ChaCha20Rng::seed_from_u64(1);
+    assert_eq!(Scalar::rand_with_rng(&mut rng1), Scalar::rand_with_rng(&mut rng2));
+    assert_ne!(Scalar::rand_with_rng(&mut rng1), Scalar::rand_with_rng(&mut ChaCha20Rng::seed_from_u64(2)));
+
+    let a = Scalar::rand_bits_with_rng(100, &mut ChaCha20Rng::seed_from_u64(3));
+    assert_eq!(a, Scalar::rand_bits_with_rng(100, &mut ChaCha20Rng::seed_from_u64(3)));
+    assert!(a.significant_bits() <= 100);
+  }
 }
 
 
@@ -0,0 +1,468 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+// BIP-340 Schnorr signatures over x-only public keys and the BIP-341 taproot key tweak
+
+use std::fmt;
+use crate::building_block::secp256k1::{
+  affine_point::AffinePoint,
+  field::Field,
+  jacobian_point::JacobianPoint,
+  scalar::Scalar,
+};
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+// SHA256(SHA256(tag) || SHA256(tag) || chunks[0] || chunks[1] || ...)
+pub fn tagged_hash(tag: &str, chunks: &[&[u8]]) -> [u8; 32] {
+  let tag_hash = Sha256::digest(tag.as_bytes());
+  let mut hasher = Sha256::new();
+  hasher.update(tag_hash);
+  hasher.update(tag_hash);
+  for chunk in chunks {
+    hasher.update(chunk);
+  }
+  hasher.finalize().into()
+}
+
+fn to_b32(s: &Scalar) -> [u8; 32] {
+  s.secp256k1_serialize().try_into().unwrap()
+}
+
+// e = hash_BIP0340/challenge(bytes(R) || bytes(P) || m) mod n
+fn challenge(r: &[u8; 32], pk: &XOnlyPublicKey, msg: &[u8]) -> Scalar {
+  let hash = tagged_hash("BIP0340/challenge", &[r, &pk.to_bytes(), msg]);
+  Scalar::from_bytes_reduced(&hash).0
+}
+
+// public key identified by the x-coordinate of the point with even y
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct XOnlyPublicKey {
+  x: Field,
+}
+
+impl XOnlyPublicKey {
+  // x-only key of p and whether p has odd y
+  pub fn from_point(p: &JacobianPoint) -> Result<(Self, bool), String> {
+    let p = p.to_affine();
+    if p.is_infinity() {
+      return Err("x-only public key cannot be the point at infinity".to_string());
+    }
+    Ok((XOnlyPublicKey { x: p.x() }, p.y().is_odd()))
+  }
+
+  pub fn from_secret_key(sk: &Scalar) -> Result<Self, String> {
+    if sk.is_zero() {
+      return Err("Secret key should not be zero".to_string());
+    }
+    Self::from_point(&JacobianPoint::mul_base_secret(sk)).map(|(pk, _)| pk)
+  }
+
+  // rejects x not less than p and x with no point on the curve
+  pub fn from_bytes(buf: &[u8; 32]) -> Result<Self, String> {
+    let x = Field::deserialize(buf)?;
+    if Field::curve_rhs(&x).sqrt().is_none() {
+      return Err("x-only public key is not on the curve".to_string());
+    }
+    Ok(XOnlyPublicKey { x })
+  }
+
+  pub fn to_bytes(self) -> [u8; 32] {
+    self.x.serialize().try_into().unwrap()
+  }
+
+  // lift_x of BIP-340, i.e. the point with even y
+  pub fn to_point(self) -> JacobianPoint {
+    let mut sec1 = vec![0x02];
+    sec1.extend(self.x.serialize());
+    JacobianPoint::from_sec1(&sec1).unwrap()
+  }
+
+  // Q = P + hash_TapTweak(P || merkle_root) * G of BIP-341 and whether Q has odd y.
+  // merkle_root is None for a key with no script path
+  pub fn tap_tweak(&self, merkle_root: Option<&[u8; 32]>) -> Result<(Self, bool), String> {
+    let t = tap_tweak_hash(self, merkle_root)?;
+    let q = self.to_point() + JacobianPoint::mul_base(&t);
+    Self::from_point(&q)
+  }
+}
+
+impl fmt::LowerHex for XOnlyPublicKey {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if f.alternate() {
+      write!(f, "0x")?;
+    }
+    write!(f, "{}", hex::encode(self.to_bytes()))
+  }
+}
+
+fn tap_tweak_hash(pk: &XOnlyPublicKey, merkle_root: Option<&[u8; 32]>) -> Result<Scalar, String> {
+  let pk = pk.to_bytes();
+  let hash = match merkle_root {
+    Some(root) => tagged_hash("TapTweak", &[&pk, root]),
+    None => tagged_hash("TapTweak", &[&pk]),
+  };
+  Scalar::from_bytes_checked(&hash)
+}
+
+// secret key of the output key of XOnlyPublicKey::tap_tweak
+pub fn tap_tweak_secret_key(sk: &Scalar, merkle_root: Option<&[u8; 32]>) -> Result<Scalar, String> {
+  if sk.is_zero() {
+    return Err("Secret key should not be zero".to_string());
+  }
+  let (pk, is_odd) = XOnlyPublicKey::from_point(&JacobianPoint::mul_base_secret(sk))?;
+  let d = if is_odd { -sk } else { *sk };
+  let tweaked = d + tap_tweak_hash(&pk, merkle_root)?;
+  if tweaked.is_zero() {
+    return Err("Tweaked secret key is zero".to_string());
+  }
+  Ok(tweaked)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct SchnorrSignature {
+  r: Field, // x-coordinate of R
+  s: Scalar,
+}
+
+impl SchnorrSignature {
+  pub fn sign(sk: &Scalar, msg: &[u8]) -> Result<Self, String> {
+    Self::sign_with_rng(sk, msg, &mut OsRng)
+  }
+
+  // aux_rand is drawn from rng
+  pub fn sign_with_rng(
+    sk: &Scalar,
+    msg: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Result<Self, String> {
+    let mut aux_rand = [0u8; 32];
+    rng.fill_bytes(&mut aux_rand);
+    Self::sign_with_aux_rand(sk, msg, &aux_rand)
+  }
+
+  // signing algorithm of BIP-340. aux_rand only hardens the nonce against
+  // side channels, so signing with a fixed aux_rand is still secure
+  pub fn sign_with_aux_rand(
+    sk: &Scalar,
+    msg: &[u8],
+    aux_rand: &[u8; 32],
+  ) -> Result<Self, String> {
+    if sk.is_zero() {
+      return Err("Secret key should not be zero".to_string());
+    }
+    let (pk, is_odd) = XOnlyPublicKey::from_point(&JacobianPoint::mul_base_secret(sk))?;
+    let d = if is_odd { -sk } else { *sk };
+
+    // t = bytes(d) xor hash_BIP0340/aux(a)
+    let mut t = to_b32(&d);
+    for (b, h) in t.iter_mut().zip(tagged_hash("BIP0340/aux", &[aux_rand])) {
+      *b ^= h;
+    }
+    let mut rand = tagged_hash("BIP0340/nonce", &[&t, &pk.to_bytes(), msg]);
+    let k = Scalar::from_bytes_reduced(&rand).0;
+    t.zeroize();
+    rand.zeroize();
+    if k.is_zero() {
+      return Err("Nonce is zero".to_string());
+    }
+
+    let (r, is_odd) = XOnlyPublicKey::from_point(&JacobianPoint::mul_base_secret(&k))?;
+    let k = if is_odd { -k } else { k };
+    let e = challenge(&r.to_bytes(), &pk, msg);
+
+    let sig = SchnorrSignature { r: r.x, s: k + e * d };
+    if !sig.verify(&pk, msg) {
+      return Err("Created signature does not verify".to_string());
+    }
+    Ok(sig)
+  }
+
+  // R = s * G - e * P should have even y and x = r
+  pub fn verify(&self, pk: &XOnlyPublicKey, msg: &[u8]) -> bool {
+    let r = self.r.serialize().try_into().unwrap();
+    let e = challenge(&r, pk, msg);
+    let R = JacobianPoint::multi_mul(&[
+      (JacobianPoint::get_base_point(), self.s),
+      (pk.to_point(), -e),
+    ]);
+    let R: AffinePoint = R.into();
+    !R.is_infinity() && !R.y().is_odd() && R.x() == self.r
+  }
+
+  // bytes(r) || bytes(s)
+  pub fn to_bytes(self) -> [u8; 64] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&self.r.serialize());
+    buf[32..].copy_from_slice(&to_b32(&self.s));
+    buf
+  }
+
+  // rejects r not less than p and s not less than n
+  pub fn from_bytes(buf: &[u8; 64]) -> Result<Self, String> {
+    let r = Field::deserialize(&buf[..32])?;
+    let s = Scalar::from_bytes_checked(buf[32..].try_into().unwrap())?;
+    Ok(SchnorrSignature { r, s })
+  }
+}
+
+impl fmt::Debug for SchnorrSignature {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("SchnorrSignature")
+      .field("r", &self.r.to_hex())
+      .field("s", &self.s.to_hex())
+      .finish()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  struct TestVector {
+    sk: &'static str,
+    pk: &'static str,
+    aux_rand: &'static str,
+    msg: &'static str,
+    sig: &'static str,
+    result: bool,
+  }
+
+  const MSG: &str = "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89";
+  const PK: &str = "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659";
+  const SK: &str = "0340034003400340034003400340034003400340034003400340034003400340";
+  const SK_PK: &str = "778CAA53B4393AC467774D09497A87224BF9FAB6F6E68B23086497324D6FD117";
+  const ZERO: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+  // test-vectors.csv of BIP-340. sk is empty for verification-only vectors
+  // and msg of index 18 is 100 bytes of 0x99
+  const TEST_VECTORS: [TestVector; 18] = [
+    TestVector {
+      sk: "0000000000000000000000000000000000000000000000000000000000000003",
+      pk: "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+      aux_rand: ZERO,
+      msg: ZERO,
+      sig: "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+      result: true,
+    },
+    TestVector {
+      sk: "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+      pk: PK,
+      aux_rand: "0000000000000000000000000000000000000000000000000000000000000001",
+      msg: MSG,
+      sig: "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+      result: true,
+    },
+    TestVector {
+      sk: "C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9",
+      pk: "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+      aux_rand: "C87AA53824B4D7AE2EB035A2B5BBBCCC080E76CDC6D1692C4B0B62D798E6D906",
+      msg: "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+      sig: "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+      result: true,
+    },
+    TestVector {
+      sk: "0B432B2677937381AEF05BB02A66ECD012773062CF3FA2549E44F58ED2401710",
+      pk: "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+      aux_rand: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+      msg: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+      sig: "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+      result: true,
+    },
+    TestVector {
+      sk: "",
+      pk: "D69C3509BB99E412E68B0FE8544E72837DFA30746D8BE2AA65975F29D22DC7B9",
+      aux_rand: "",
+      msg: "4DF3C3F68FCC83B27E9D42C90431A72499F17875C81A599B566C9889B9696703",
+      sig: "00000000000000000000003B78CE563F89A0ED9414F5AA28AD0D96D6795F9C6376AFB1548AF603B3EB45C9F8207DEE1060CB71C04E80F593060B07D28308D7F4",
+      result: true,
+    },
+    // public key not on the curve
+    TestVector {
+      sk: "",
+      pk: "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+      aux_rand: "",
+      msg: MSG,
+      sig: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+      result: false,
+    },
+    // R has odd y
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A14602975563CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+      result: false,
+    },
+    // negated message
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+      result: false,
+    },
+    // negated s
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+      result: false,
+    },
+    // R is the point at infinity
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "0000000000000000000000000000000000000000000000000000000000000000123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+      result: false,
+    },
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "00000000000000000000000000000000000000000000000000000000000000017615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+      result: false,
+    },
+    // r is not an x-coordinate on the curve
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+      result: false,
+    },
+    // r = p
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+      result: false,
+    },
+    // s = n
+    TestVector {
+      sk: "",
+      pk: PK,
+      aux_rand: "",
+      msg: MSG,
+      sig: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+      result: false,
+    },
+    // public key is not less than p
+    TestVector {
+      sk: "",
+      pk: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+      aux_rand: "",
+      msg: MSG,
+      sig: "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E17776969E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+      result: false,
+    },
+    // messages of other lengths
+    TestVector {
+      sk: SK,
+      pk: SK_PK,
+      aux_rand: ZERO,
+      msg: "",
+      sig: "71535DB165ECD9FBBC046E5FFAEA61186BB6AD436732FCCC25291A55895464CF6069CE26BF03466228F19A3A62DB8A649F2D560FAC652827D1AF0574E427AB63",
+      result: true,
+    },
+    TestVector {
+      sk: SK,
+      pk: SK_PK,
+      aux_rand: ZERO,
+      msg: "11",
+      sig: "08A20A0AFEF64124649232E0693C583AB1B9934AE63B4C3511F3AE1134C6A303EA3173BFEA6683BD101FA5AA5DBC1996FE7CACFC5A577D33EC14564CEC2BACBF",
+      result: true,
+    },
+    TestVector {
+      sk: SK,
+      pk: SK_PK,
+      aux_rand: ZERO,
+      msg: "0102030405060708090A0B0C0D0E0F1011",
+      sig: "5130F39A4059B43BC7CAC09A19ECE52B5D8699D1A71E3C52DA9AFDB6B50AC370C4A482B77BF960F8681540E25B6771ECE1E5A37FD80E5A51897C5566A97EA5A5",
+      result: true,
+    },
+  ];
+
+  fn b32(s: &str) -> [u8; 32] {
+    hex::decode(s).unwrap().try_into().unwrap()
+  }
+
+  fn check(tv: &TestVector, msg: &[u8]) {
+    let sig_bytes: [u8; 64] = hex::decode(tv.sig).unwrap().try_into().unwrap();
+    if !tv.sk.is_empty() {
+      let sk = Scalar::from_bytes_checked(&b32(tv.sk)).unwrap();
+      let pk = XOnlyPublicKey::from_secret_key(&sk).unwrap();
+      assert_eq!(pk.to_bytes(), b32(tv.pk));
+      let sig = SchnorrSignature::sign_with_aux_rand(&sk, msg, &b32(tv.aux_rand)).unwrap();
+      assert_eq!(sig.to_bytes(), sig_bytes);
+    }
+    let result = XOnlyPublicKey::from_bytes(&b32(tv.pk)).and_then(|pk|
+      SchnorrSignature::from_bytes(&sig_bytes).map(|sig| sig.verify(&pk, msg))
+    );
+    assert_eq!(result.unwrap_or(false), tv.result);
+  }
+
+  #[test]
+  fn test_bip340_vectors() {
+    for tv in &TEST_VECTORS {
+      check(tv, &hex::decode(tv.msg).unwrap());
+    }
+    let tv = TestVector {
+      sk: SK,
+      pk: SK_PK,
+      aux_rand: ZERO,
+      msg: "",
+      sig: "403B12B0D8555A344175EA7EC746566303321E5DBFA8BE6F091635163ECA79A8585ED3E3170807E7C03B720FC54C7B23897FCBA0E9D0B4A06894CFD249F22367",
+      result: true,
+    };
+    check(&tv, &[0x99; 100]);
+  }
+
+  #[test]
+  fn test_sign_verify() {
+    let sk = Scalar::rand();
+    let pk = XOnlyPublicKey::from_secret_key(&sk).unwrap();
+    let sig = SchnorrSignature::sign(&sk, b"message").unwrap();
+    assert!(sig.verify(&pk, b"message"));
+    assert!(!sig.verify(&pk, b"massage"));
+    assert_eq!(SchnorrSignature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+    assert!(SchnorrSignature::sign(&Scalar::zero(), b"message").is_err());
+  }
+
+  // wallet-test-vectors.json of BIP-341
+  #[test]
+  fn test_tap_tweak() {
+    let pk = XOnlyPublicKey::from_bytes(&b32("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d")).unwrap();
+    let (q, _) = pk.tap_tweak(None).unwrap();
+    assert_eq!(q.to_bytes(), b32("53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"));
+
+    let pk = XOnlyPublicKey::from_bytes(&b32("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27")).unwrap();
+    let root = b32("5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21");
+    let (q, _) = pk.tap_tweak(Some(&root)).unwrap();
+    assert_eq!(q.to_bytes(), b32("147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"));
+
+    let sk = Scalar::from_bytes_checked(&b32("6b973d88838f27366ed61c9ad6367663045cb456e28335c109e30717ae0c6baa")).unwrap();
+    let tweaked = tap_tweak_secret_key(&sk, None).unwrap();
+    assert_eq!(to_b32(&tweaked), b32("2405b971772ad26915c8dcdf10f238753a9b837e5f8e6a86fd7c0cce5b7296d9"));
+
+    // a key path spend with the tweaked key verifies under the output key
+    let (q, _) = XOnlyPublicKey::from_secret_key(&sk).unwrap().tap_tweak(None).unwrap();
+    let sig = SchnorrSignature::sign(&tweaked, b"key path").unwrap();
+    assert!(sig.verify(&q, b"key path"));
+  }
+}
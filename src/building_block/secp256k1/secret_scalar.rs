@@ -0,0 +1,135 @@
+#![allow(dead_code)]
+
+use std::{
+  fmt,
+  ops::{Add, Mul},
+};
+use crate::building_block::{
+  curve::CurveScalar,
+  secp256k1::scalar::Scalar,
+};
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
+use zeroize::Zeroize;
+
+// Scalar holding secret material. unlike Scalar it is not Copy,
+// is wiped on drop and does not reveal its value through Debug
+#[derive(Clone)]
+pub struct SecretScalar<S: CurveScalar = Scalar>(S);
+
+impl<S: CurveScalar> SecretScalar<S> {
+  pub fn new(s: S) -> Self {
+    SecretScalar(s)
+  }
+
+  pub fn rand() -> Self {
+    SecretScalar(S::rand_with_rng(&mut OsRng))
+  }
+
+  pub fn rand_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    SecretScalar(S::rand_with_rng(rng))
+  }
+
+  pub fn expose(&self) -> &S {
+    &self.0
+  }
+}
+
+impl<S: CurveScalar> From<S> for SecretScalar<S> {
+  fn from(s: S) -> Self {
+    SecretScalar(s)
+  }
+}
+
+// arithmetic on secrets is done in place so that intermediate
+// values are also wiped on drop
+impl<S: CurveScalar> Mul for &SecretScalar<S> {
+  type Output = SecretScalar<S>;
+
+  fn mul(self, rhs: &SecretScalar<S>) -> SecretScalar<S> {
+    let mut res = self.clone();
+    res.0 *= rhs.0;
+    res
+  }
+}
+
+impl<S: CurveScalar> Mul<&S> for &SecretScalar<S> {
+  type Output = SecretScalar<S>;
+
+  fn mul(self, rhs: &S) -> SecretScalar<S> {
+    let mut res = self.clone();
+    res.0 *= *rhs;
+    res
+  }
+}
+
+impl<S: CurveScalar> Add<&S> for SecretScalar<S> {
+  type Output = SecretScalar<S>;
+
+  fn add(mut self, rhs: &S) -> SecretScalar<S> {
+    self.0 += *rhs;
+    self
+  }
+}
+
+impl<S: CurveScalar> Add for SecretScalar<S> {
+  type Output = SecretScalar<S>;
+
+  fn add(mut self, rhs: SecretScalar<S>) -> SecretScalar<S> {
+    self.0 += rhs.0;
+    self
+  }
+}
+
+impl<S: CurveScalar> Zeroize for SecretScalar<S> {
+  fn zeroize(&mut self) {
+    self.0.zeroize();
+  }
+}
+
+impl<S: CurveScalar> Drop for SecretScalar<S> {
+  fn drop(&mut self) {
+    self.zeroize();
+  }
+}
+
+impl<S: CurveScalar> fmt::Debug for SecretScalar<S> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "SecretScalar(<redacted>)")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_debug_is_redacted() {
+    let s = SecretScalar::new(Scalar::from(12345u32));
+    let debug = format!("{:?}", s);
+    assert_eq!(debug, "SecretScalar(<redacted>)");
+    assert!(!debug.contains("12345"));
+  }
+
+  #[test]
+  fn test_zeroize() {
+    let mut s: SecretScalar = SecretScalar::rand();
+    assert!(!s.expose().is_zero());
+    s.zeroize();
+    assert_eq!(s.expose().d, [0; 4]);
+  }
+
+  #[test]
+  fn test_arithmetic() {
+    let a = SecretScalar::new(Scalar::from(3u32));
+    let b = SecretScalar::new(Scalar::from(5u32));
+    let c = Scalar::from(7u32);
+    assert_eq!(*(&a * &b).expose(), Scalar::from(15u32));
+    assert_eq!(*(&a * &c).expose(), Scalar::from(21u32));
+    assert_eq!(*(&a * &b + &c).expose(), Scalar::from(22u32));
+    assert_eq!(*(&a * &b + &b * &c).expose(), Scalar::from(50u32));
+  }
+}
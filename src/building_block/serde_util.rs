@@ -0,0 +1,121 @@
+use std::fmt;
+use serde::{
+  de::{
+    self,
+    SeqAccess,
+    Visitor,
+  },
+  ser::SerializeTuple,
+  Deserialize,
+  Deserializer,
+  Serialize,
+  Serializer,
+};
+
+// byte strings are written as hex in human-readable formats such as JSON
+// and as raw bytes in binary formats such as bincode
+
+fn decode_hex<E: de::Error>(s: &str) -> Result<Vec<u8>, E> {
+  let digits = s.strip_prefix("0x").unwrap_or(s);
+  hex::decode(digits).map_err(|e| E::custom(format!("Invalid hex string {}: {}", s, e)))
+}
+
+// L bytes without a length prefix in binary formats
+pub fn serialize_fixed<S: Serializer, const L: usize>(
+  buf: &[u8; L],
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  if serializer.is_human_readable() {
+    return serializer.serialize_str(&hex::encode(buf));
+  }
+  let mut tup = serializer.serialize_tuple(L)?;
+  for b in buf {
+    tup.serialize_element(b)?;
+  }
+  tup.end()
+}
+
+struct FixedBytesVisitor<const L: usize>;
+
+impl<'de, const L: usize> Visitor<'de> for FixedBytesVisitor<L> {
+  type Value = [u8; L];
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} bytes", L)
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    let mut buf = [0u8; L];
+    for (i, b) in buf.iter_mut().enumerate() {
+      *b = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+    }
+    Ok(buf)
+  }
+}
+
+pub fn deserialize_fixed<'de, D: Deserializer<'de>, const L: usize>(
+  deserializer: D,
+) -> Result<[u8; L], D::Error> {
+  if !deserializer.is_human_readable() {
+    return deserializer.deserialize_tuple(L, FixedBytesVisitor::<L>);
+  }
+  let buf = decode_hex::<D::Error>(&String::deserialize(deserializer)?)?;
+  buf.as_slice().try_into().map_err(|_| {
+    de::Error::custom(format!("Expected {} bytes, but got {} bytes", L, buf.len()))
+  })
+}
+
+// variable-length counterpart of serialize_fixed
+pub fn serialize_bytes<S: Serializer>(
+  buf: &[u8],
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  if serializer.is_human_readable() {
+    serializer.serialize_str(&hex::encode(buf))
+  } else {
+    buf.serialize(serializer)
+  }
+}
+
+pub fn deserialize_bytes<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+  if deserializer.is_human_readable() {
+    decode_hex(&String::deserialize(deserializer)?)
+  } else {
+    Vec::<u8>::deserialize(deserializer)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct Wrapper {
+    #[serde(serialize_with = "serialize_fixed", deserialize_with = "deserialize_fixed")]
+    fixed: [u8; 3],
+    #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
+    var: Vec<u8>,
+  }
+
+  #[test]
+  fn test_roundtrip() {
+    let w = Wrapper { fixed: [1, 2, 0xff], var: vec![0xab; 5] };
+
+    let json = serde_json::to_string(&w).unwrap();
+    assert_eq!(json, r#"{"fixed":"0102ff","var":"ababababab"}"#);
+    assert_eq!(serde_json::from_str::<Wrapper>(&json).unwrap(), w);
+
+    let bin = bincode::serialize(&w).unwrap();
+    assert_eq!(bin.len(), 3 + 8 + 5);
+    assert_eq!(bincode::deserialize::<Wrapper>(&bin).unwrap(), w);
+  }
+
+  #[test]
+  fn test_invalid_input() {
+    assert!(serde_json::from_str::<Wrapper>(r#"{"fixed":"0102","var":""}"#).is_err());
+    assert!(serde_json::from_str::<Wrapper>(r#"{"fixed":"0102zz","var":""}"#).is_err());
+    assert!(serde_json::from_str::<Wrapper>(r#"{"fixed":"0x0102ff","var":"0x"}"#).is_ok());
+  }
+}
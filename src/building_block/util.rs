@@ -2,18 +2,25 @@
 
 use rand::{
   rngs::OsRng,
+  CryptoRng,
   RngCore,
+  SeedableRng,
 };
+use rand_chacha::ChaCha20Rng;
 use rug::{
-  rand::{MutRandState, RandState},
+  integer::Order,
+  rand::{MutRandState, RandGen, RandState},
   Complete,
   Integer,
 };
 use sha2::{Digest, Sha256};
-use crate::building_block::secp256k1::scalar::Scalar;
 
 pub fn gen_random_binary_val() -> bool {
-  if OsRng.next_u32() as u8 % 2 == 0 { false } else { true }
+  gen_random_binary_val_with_rng(&mut OsRng)
+}
+
+pub fn gen_random_binary_val_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> bool {
+  rng.next_u32() % 2 == 1
 }
 
 pub fn get_num_wires(depth: usize) -> usize {
@@ -45,14 +52,36 @@ pub fn xor_vecs(v1: &Vec<u8>, v2: &Vec<u8>) -> Vec<u8> {
 }
 
 pub fn get_32_byte_rng<'a>() -> RandState<'a> {
-  let mut rng = RandState::new();
-  let seed = {
-    let mut random_bytes = [0u8; 32];
-    OsRng.fill_bytes(&mut random_bytes);
-    Integer::from_digits(&random_bytes, rug::integer::Order::Msf)
-  };
-  rng.seed(&seed);
-  rng
+  get_32_byte_rng_with_rng(&mut OsRng)
+}
+
+// RandState over ChaCha20 seeded with 32 bytes drawn from rng
+pub fn get_32_byte_rng_with_rng<'a>(rng: &mut (impl RngCore + CryptoRng)) -> RandState<'a> {
+  RandState::new_custom_boxed(Box::new(ChaChaRandGen::new(rng)))
+}
+
+// RandState::new() is GMP's Mersenne Twister whose output becomes predictable
+// once enough of it is observed. this is the generator behind get_32_byte_rng
+pub struct ChaChaRandGen(ChaCha20Rng);
+
+impl ChaChaRandGen {
+  pub fn new(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    ChaChaRandGen(ChaCha20Rng::from_seed(seed))
+  }
+}
+
+impl RandGen for ChaChaRandGen {
+  fn gen(&mut self) -> u32 {
+    self.0.next_u32()
+  }
+
+  // restarts the stream from the hash of seed
+  fn seed(&mut self, seed: &Integer) {
+    let digest = Sha256::digest(seed.to_digits::<u8>(Order::Msf));
+    self.0 = ChaCha20Rng::from_seed(digest.into());
+  }
 }
 
 pub fn gen_random_number(
@@ -73,13 +102,6 @@ pub fn gen_random_prime(
   n.next_prime()
 }
 
-pub fn bitcoin_hasher(m: &Scalar) -> Scalar {
-  let mut hasher = Sha256::new();
-  hasher.update(m.secp256k1_serialize());
-  let hash = hasher.finalize();
-  Scalar::secp256k1_deserialize(&hash[0..32]).unwrap()
-}
-
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -148,4 +170,25 @@ mod tests {
     let dec = xor_vecs(&lhs, &enc);
     assert!(are_vecs_equal(&dec, &ser_out));
   }
+
+  #[test]
+  fn test_chacha_rand_state() {
+    let seed = 1;
+    let mut rand_state = get_32_byte_rng_with_rng(&mut ChaCha20Rng::seed_from_u64(seed));
+
+    // follows the ChaCha20 stream seeded with the first 32 bytes of rng
+    let mut chacha = {
+      let mut buf = [0u8; 32];
+      ChaCha20Rng::seed_from_u64(seed).fill_bytes(&mut buf);
+      ChaCha20Rng::from_seed(buf)
+    };
+    for _ in 0..4 {
+      assert_eq!(gen_random_number(32, &mut rand_state), chacha.next_u32());
+    }
+
+    let mut rand_state2 = get_32_byte_rng_with_rng(&mut ChaCha20Rng::seed_from_u64(seed));
+    rand_state.seed(&Integer::from(7));
+    rand_state2.seed(&Integer::from(7));
+    assert_eq!(gen_random_prime(256, &mut rand_state), gen_random_prime(256, &mut rand_state2));
+  }
 }
@@ -2,7 +2,12 @@
 
 use crate::building_block::{
   wire_label::WireLabel,
-  util::gen_random_binary_val,
+  util::gen_random_binary_val_with_rng,
+};
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
 };
 
 // A Wire consists of two WireLabels
@@ -15,12 +20,16 @@ pub struct Wire {
 
 impl Wire {
   pub fn new(k: usize, index: usize) -> Self {
-    let p = gen_random_binary_val();  
+    Wire::new_with_rng(k, index, &mut OsRng)
+  }
+
+  pub fn new_with_rng(k: usize, index: usize, rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    let p = gen_random_binary_val_with_rng(rng);
     Wire {
       index,
       labels: [
-        WireLabel::new(index, false, p, k),
-        WireLabel::new(index, true, !p, k),
+        WireLabel::new_with_rng(index, false, p, k, rng),
+        WireLabel::new_with_rng(index, true, !p, k, rng),
       ]
     }
   }
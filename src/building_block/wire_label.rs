@@ -1,4 +1,5 @@
-use crate::building_block::util::gen_random_binary_val;
+use crate::building_block::util::gen_random_binary_val_with_rng;
+use rand::{rngs::OsRng, CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -11,7 +12,17 @@ pub struct WireLabel {
 
 impl WireLabel {
   pub fn new(wire_index: usize, b: bool, p: bool, key_size: usize) -> Self {
-    let k: Vec<u8> = std::iter::repeat_with(|| gen_random_binary_val() as u8)
+    WireLabel::new_with_rng(wire_index, b, p, key_size, &mut OsRng)
+  }
+
+  pub fn new_with_rng(
+    wire_index: usize,
+    b: bool,
+    p: bool,
+    key_size: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Self {
+    let k: Vec<u8> = std::iter::repeat_with(|| gen_random_binary_val_with_rng(rng) as u8)
       .take(key_size)
       .collect();
     WireLabel { wire_index, b, k, p }
@@ -1,6 +1,11 @@
 #![allow(non_snake_case)]
 
 use crate::building_block::wire::Wire;
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
 use std::collections::HashSet;
 
 #[derive(Debug)]
@@ -20,8 +25,12 @@ impl Wires {
   }
 
   pub fn create(&mut self, is_input: bool) -> usize {
+    self.create_with_rng(is_input, &mut OsRng)
+  }
+
+  pub fn create_with_rng(&mut self, is_input: bool, rng: &mut (impl RngCore + CryptoRng)) -> usize {
     let index = self.wires.len();
-    let wire = Wire::new(self.K, index);
+    let wire = Wire::new_with_rng(self.K, index, rng);
     self.wires.push(wire);
 
     if is_input {
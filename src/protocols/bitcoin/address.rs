@@ -0,0 +1,159 @@
+#![allow(dead_code)]
+
+// P2PKH, P2WPKH and P2TR addresses
+
+use crate::{
+  building_block::secp256k1::{
+    jacobian_point::JacobianPoint,
+    schnorr::XOnlyPublicKey,
+  },
+  protocols::bitcoin::{
+    base58,
+    bech32,
+    keys::compressed_public_key,
+    network::Network,
+    util::hash160,
+  },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address {
+  P2pkh([u8; 20]), // hash160 of the public key
+  P2wpkh([u8; 20]), // hash160 of the compressed public key
+  P2tr(XOnlyPublicKey), // output key
+}
+
+impl Address {
+  pub fn p2pkh(pk: &JacobianPoint) -> Result<Self, String> {
+    Ok(Address::P2pkh(hash160(&compressed_public_key(pk)?)))
+  }
+
+  pub fn p2wpkh(pk: &JacobianPoint) -> Result<Self, String> {
+    Ok(Address::P2wpkh(hash160(&compressed_public_key(pk)?)))
+  }
+
+  // output key tweaked from the internal key as BIP-341 defines. merkle_root
+  // is None for a key with no script path as BIP-86 recommends
+  pub fn p2tr(internal_key: &JacobianPoint, merkle_root: Option<&[u8; 32]>) -> Result<Self, String> {
+    let (internal_key, _) = XOnlyPublicKey::from_point(internal_key)?;
+    let (output_key, _) = internal_key.tap_tweak(merkle_root)?;
+    Ok(Address::P2tr(output_key))
+  }
+
+  pub fn script_pubkey(&self) -> Vec<u8> {
+    match self {
+      // OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG
+      Address::P2pkh(hash) => [&[0x76, 0xa9, 0x14], &hash[..], &[0x88, 0xac]].concat(),
+      // OP_0 <20 bytes>
+      Address::P2wpkh(hash) => [&[0x00, 0x14], &hash[..]].concat(),
+      // OP_1 <32 bytes>
+      Address::P2tr(output_key) => [&[0x51, 0x20], &output_key.to_bytes()[..]].concat(),
+    }
+  }
+
+  pub fn encode(&self, network: Network) -> String {
+    match self {
+      Address::P2pkh(hash) => base58::encode_check(&[&[network.p2pkh_prefix()], &hash[..]].concat()),
+      Address::P2wpkh(hash) => bech32::encode_segwit_address(network.bech32_hrp(), 0, hash).unwrap(),
+      Address::P2tr(output_key) =>
+        bech32::encode_segwit_address(network.bech32_hrp(), 1, &output_key.to_bytes()).unwrap(),
+    }
+  }
+
+  // fails for addresses of other networks and other address types
+  pub fn decode(s: &str, network: Network) -> Result<Self, String> {
+    let hrp = network.bech32_hrp();
+    if s.to_lowercase().starts_with(&format!("{}1", hrp)) {
+      let (version, program) = bech32::decode_segwit_address(hrp, s)?;
+      return match (version, program.len()) {
+        (0, 20) => Ok(Address::P2wpkh(program.try_into().unwrap())),
+        (1, 32) => Ok(Address::P2tr(XOnlyPublicKey::from_bytes(&program.try_into().unwrap())?)),
+        _ => Err(format!("Unsupported witness version {} program of {} bytes: {}", version, program.len(), s)),
+      };
+    }
+
+    let buf = base58::decode_check(s)?;
+    if buf.len() != 21 {
+      return Err(format!("Invalid base58 address length {}: {}", buf.len(), s));
+    }
+    if buf[0] != network.p2pkh_prefix() {
+      return Err(format!("Unsupported address prefix {:#04x} on {:?}: {}", buf[0], network, s));
+    }
+    Ok(Address::P2pkh(buf[1..].try_into().unwrap()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::secp256k1::scalar::Scalar;
+
+  fn g() -> JacobianPoint {
+    JacobianPoint::mul_base(&Scalar::from(1u32))
+  }
+
+  fn check(addr: &Address, network: Network, s: &str) {
+    assert_eq!(addr.encode(network), s);
+    assert_eq!(Address::decode(s, network).unwrap(), *addr);
+  }
+
+  #[test]
+  fn test_p2pkh() {
+    let addr = Address::p2pkh(&g()).unwrap();
+    assert_eq!(hex::encode(addr.script_pubkey()), "76a914751e76e8199196d454941c45d1b3a323f1433bd688ac");
+    check(&addr, Network::Mainnet, "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+    check(&addr, Network::Testnet, "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r");
+    check(&addr, Network::Regtest, "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r");
+
+    assert!(Address::decode("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH", Network::Testnet).is_err());
+    assert!(Address::decode("1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMh", Network::Mainnet).is_err());
+    // P2SH
+    assert!(Address::decode("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", Network::Mainnet).is_err());
+    assert!(Address::p2pkh(&JacobianPoint::point_at_infinity()).is_err());
+  }
+
+  // addresses of BIP-173 and BIP-84
+  #[test]
+  fn test_p2wpkh() {
+    let addr = Address::p2wpkh(&g()).unwrap();
+    assert_eq!(hex::encode(addr.script_pubkey()), "0014751e76e8199196d454941c45d1b3a323f1433bd6");
+    check(&addr, Network::Mainnet, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+    check(&addr, Network::Testnet, "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+    check(&addr, Network::Regtest, "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080");
+
+    let pk = JacobianPoint::from_sec1(
+      &hex::decode("0330d54fd0dd420a6e5f8d3624f5f3482cae350f79d5f0753bf5beef9c2d91af3c").unwrap(),
+    ).unwrap();
+    check(&Address::p2wpkh(&pk).unwrap(), Network::Mainnet, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+
+    assert!(Address::decode("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", Network::Testnet).is_err());
+    assert!(Address::decode("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx", Network::Regtest).is_err());
+    // P2WSH
+    assert!(Address::decode(
+      "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", Network::Testnet,
+    ).is_err());
+  }
+
+  // first receiving address of BIP-86
+  #[test]
+  fn test_p2tr() {
+    let internal_key = XOnlyPublicKey::from_bytes(&hex::decode(
+      "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115",
+    ).unwrap().try_into().unwrap()).unwrap();
+    let addr = Address::p2tr(&internal_key.to_point(), None).unwrap();
+    assert_eq!(
+      hex::encode(addr.script_pubkey()),
+      "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c",
+    );
+    check(&addr, Network::Mainnet, "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+
+    // the internal key and its negation have the same address
+    let neg = -internal_key.to_point();
+    assert_eq!(Address::p2tr(&neg, None).unwrap(), addr);
+
+    // x-coordinate not on the curve
+    assert!(Address::decode(
+      &bech32::encode_segwit_address("bc", 1, &[0x00; 32]).unwrap(), Network::Mainnet,
+    ).is_err());
+  }
+}
@@ -0,0 +1,109 @@
+#![allow(dead_code)]
+
+// base58 and base58check of Bitcoin
+
+use zeroize::Zeroize;
+use crate::protocols::bitcoin::util::sha256d;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// each leading zero byte becomes a leading '1'
+pub fn encode(data: &[u8]) -> String {
+  let num_zeros = data.iter().take_while(|b| **b == 0).count();
+
+  // base-58 digits in little-endian
+  let mut digits: Vec<u8> = vec![];
+  for b in &data[num_zeros..] {
+    let mut carry = *b as u32;
+    for digit in digits.iter_mut() {
+      carry += (*digit as u32) << 8;
+      *digit = (carry % 58) as u8;
+      carry /= 58;
+    }
+    while carry > 0 {
+      digits.push((carry % 58) as u8);
+      carry /= 58;
+    }
+  }
+  let mut s = "1".repeat(num_zeros);
+  s.extend(digits.iter().rev().map(|d| ALPHABET[*d as usize] as char));
+  s
+}
+
+pub fn decode(s: &str) -> Result<Vec<u8>, String> {
+  let num_zeros = s.chars().take_while(|c| *c == '1').count();
+
+  // bytes in little-endian
+  let mut bytes: Vec<u8> = vec![];
+  for c in s.chars().skip(num_zeros) {
+    let mut carry = ALPHABET.iter().position(|x| *x as char == c)
+      .ok_or(format!("Invalid base58 character {:?}", c))? as u32;
+    for b in bytes.iter_mut() {
+      carry += (*b as u32) * 58;
+      *b = carry as u8;
+      carry >>= 8;
+    }
+    while carry > 0 {
+      bytes.push(carry as u8);
+      carry >>= 8;
+    }
+  }
+  let mut buf = vec![0u8; num_zeros];
+  buf.extend(bytes.iter().rev());
+  Ok(buf)
+}
+
+// data followed by the first 4 bytes of sha256d(data)
+pub fn encode_check(data: &[u8]) -> String {
+  let mut buf = data.to_vec();
+  buf.extend(&sha256d(data)[..4]);
+  encode(&buf)
+}
+
+// the string is not included in errors since it may be a secret key
+pub fn decode_check(s: &str) -> Result<Vec<u8>, String> {
+  let mut buf = decode(s)?;
+  if buf.len() < 4 {
+    return Err(format!("base58check string is too short: {} bytes", buf.len()));
+  }
+  let checksum = buf.split_off(buf.len() - 4);
+  if checksum != sha256d(&buf)[..4] {
+    buf.zeroize();
+    return Err("Invalid base58check checksum".to_string());
+  }
+  Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_decode() {
+    for (data, s) in [
+      ("", ""),
+      ("61", "2g"),
+      ("626262", "a3gV"),
+      ("636363", "aPEr"),
+      ("00000000000000000000", "1111111111"),
+      ("00eb15231dfceb60925886b67d065299925915aeb172c06647", "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L"),
+      ("516b6fcd0f", "ABnLTmg"),
+      ("572e4794", "3EFU7m"),
+    ] {
+      let data = hex::decode(data).unwrap();
+      assert_eq!(encode(&data), s);
+      assert_eq!(decode(s).unwrap(), data);
+    }
+    assert!(decode("0OIl").is_err());
+  }
+
+  #[test]
+  fn test_check() {
+    let s = encode_check(&[0x00; 21]);
+    assert_eq!(s, "1111111111111111111114oLvT2");
+    assert_eq!(decode_check(&s).unwrap(), vec![0x00; 21]);
+
+    assert!(decode_check("1111111111111111111114oLvT3").is_err());
+    assert!(decode_check("2g").is_err());
+  }
+}
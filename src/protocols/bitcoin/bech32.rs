@@ -0,0 +1,229 @@
+#![allow(dead_code)]
+
+// bech32 of BIP-173, bech32m of BIP-350 and segwit addresses
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+  Bech32,
+  Bech32m,
+}
+
+impl Variant {
+  // value the polymod of a valid string results in
+  fn constant(self) -> u32 {
+    match self {
+      Variant::Bech32 => 1,
+      Variant::Bech32m => 0x2bc830a3,
+    }
+  }
+}
+
+fn polymod(values: &[u8]) -> u32 {
+  const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+  let mut chk = 1u32;
+  for v in values {
+    let b = chk >> 25;
+    chk = ((chk & 0x1ffffff) << 5) ^ *v as u32;
+    for (i, g) in GEN.iter().enumerate() {
+      if (b >> i) & 1 == 1 {
+        chk ^= g;
+      }
+    }
+  }
+  chk
+}
+
+// high bits of the hrp characters, 0 and the low bits of them
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+  let mut v: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+  v.push(0);
+  v.extend(hrp.bytes().map(|c| c & 31));
+  v
+}
+
+// data is a list of 5-bit values
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> String {
+  let mut values = hrp_expand(hrp);
+  values.extend(data);
+  values.extend([0u8; 6]);
+  let pm = polymod(&values) ^ variant.constant();
+  let checksum = (0..6).map(|i| ((pm >> (5 * (5 - i))) & 31) as u8);
+
+  let mut s = format!("{}1", hrp);
+  s.extend(data.iter().copied().chain(checksum).map(|x| CHARSET[x as usize] as char));
+  s
+}
+
+// lower-case hrp, the 5-bit data without the checksum and the variant
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), String> {
+  if s.len() > 90 {
+    return Err(format!("bech32 string is longer than 90 characters: {}", s));
+  }
+  if s.bytes().any(|c| !(33..=126).contains(&c)) {
+    return Err(format!("Invalid character in bech32 string: {}", s));
+  }
+  if s.bytes().any(|c| c.is_ascii_lowercase()) && s.bytes().any(|c| c.is_ascii_uppercase()) {
+    return Err(format!("bech32 string should not be mixed case: {}", s));
+  }
+  let s = s.to_lowercase();
+  let pos = s.rfind('1').ok_or(format!("Missing separator: {}", s))?;
+  if pos == 0 || pos + 7 > s.len() {
+    return Err(format!("Invalid hrp or data length: {}", s));
+  }
+  let hrp = &s[..pos];
+  let data = s[pos + 1..].bytes()
+    .map(|c| CHARSET.iter().position(|x| *x == c).map(|x| x as u8))
+    .collect::<Option<Vec<u8>>>()
+    .ok_or(format!("Invalid data character: {}", s))?;
+
+  let mut values = hrp_expand(hrp);
+  values.extend(&data);
+  let variant = match polymod(&values) {
+    x if x == Variant::Bech32.constant() => Variant::Bech32,
+    x if x == Variant::Bech32m.constant() => Variant::Bech32m,
+    _ => return Err(format!("Invalid checksum: {}", s)),
+  };
+  Ok((hrp.to_string(), data[..data.len() - 6].to_vec(), variant))
+}
+
+// regroups from_bits-bit values into to_bits-bit values. without padding,
+// leftover bits should be fewer than from_bits and all zero
+pub fn convert_bits(data: &[u8], from_bits: u32, to_bits: u32, pad: bool) -> Result<Vec<u8>, String> {
+  let mut acc = 0u32;
+  let mut bits = 0u32;
+  let max = (1u32 << to_bits) - 1;
+  let mut buf = vec![];
+  for x in data {
+    if (*x as u32) >> from_bits != 0 {
+      return Err(format!("Value {} does not fit in {} bits", x, from_bits));
+    }
+    acc = (acc << from_bits) | *x as u32;
+    bits += from_bits;
+    while bits >= to_bits {
+      bits -= to_bits;
+      buf.push(((acc >> bits) & max) as u8);
+    }
+  }
+  if pad {
+    if bits > 0 {
+      buf.push(((acc << (to_bits - bits)) & max) as u8);
+    }
+  } else if bits >= from_bits || ((acc << (to_bits - bits)) & max) != 0 {
+    return Err("Invalid padding".to_string());
+  }
+  Ok(buf)
+}
+
+// version 0 uses bech32 and later versions use bech32m
+pub fn encode_segwit_address(hrp: &str, version: u8, program: &[u8]) -> Result<String, String> {
+  check_witness_program(version, program)?;
+  let variant = if version == 0 { Variant::Bech32 } else { Variant::Bech32m };
+  let mut data = vec![version];
+  data.extend(convert_bits(program, 8, 5, true)?);
+  Ok(encode(hrp, &data, variant))
+}
+
+// witness version and program
+pub fn decode_segwit_address(hrp: &str, s: &str) -> Result<(u8, Vec<u8>), String> {
+  let (decoded_hrp, data, variant) = decode(s)?;
+  if decoded_hrp != hrp {
+    return Err(format!("Expected hrp {}, but got {}", hrp, decoded_hrp));
+  }
+  if data.is_empty() {
+    return Err(format!("Missing witness version: {}", s));
+  }
+  let version = data[0];
+  let expected_variant = if version == 0 { Variant::Bech32 } else { Variant::Bech32m };
+  if variant != expected_variant {
+    return Err(format!("Witness version {} should use {:?}: {}", version, expected_variant, s));
+  }
+  let program = convert_bits(&data[1..], 5, 8, false)?;
+  check_witness_program(version, &program)?;
+  Ok((version, program))
+}
+
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), String> {
+  if version > 16 {
+    return Err(format!("Invalid witness version {}", version));
+  }
+  if program.len() < 2 || program.len() > 40 {
+    return Err(format!("Invalid witness program length {}", program.len()));
+  }
+  if version == 0 && program.len() != 20 && program.len() != 32 {
+    return Err(format!("Invalid witness v0 program length {}", program.len()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // valid and invalid checksums of BIP-173 and BIP-350
+  #[test]
+  fn test_checksum() {
+    for (s, variant) in [
+      ("A12UEL5L", Variant::Bech32),
+      ("a12uel5l", Variant::Bech32),
+      ("an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs", Variant::Bech32),
+      ("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw", Variant::Bech32),
+      ("split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w", Variant::Bech32),
+      ("?1ezyfcl", Variant::Bech32),
+      ("A1LQFN3A", Variant::Bech32m),
+      ("a1lqfn3a", Variant::Bech32m),
+      ("abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", Variant::Bech32m),
+      ("split1checkupstagehandshakeupstreamerranterredcaperredlc445v", Variant::Bech32m),
+      ("?1v759aa", Variant::Bech32m),
+    ] {
+      let (hrp, data, v) = decode(s).unwrap();
+      assert_eq!(v, variant);
+      assert_eq!(encode(&hrp, &data, variant), s.to_lowercase());
+    }
+
+    for s in [
+      "\x201nwldj5", // hrp character out of range
+      "pzry9x0s0muk", // no separator
+      "1pzry9x0s0muk", // empty hrp
+      "x1b4n0q5v", // invalid data character
+      "li1dgmt3", // too short checksum
+      "A1G7SGD8", // checksum calculated with upper-case hrp
+      "a1lqfn3A", // mixed case
+      "an84characterslonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1569pvx",
+    ] {
+      assert!(decode(s).is_err(), "{:?}", s);
+    }
+  }
+
+  #[test]
+  fn test_segwit_address() {
+    for (hrp, s, script_pubkey) in [
+      ("bc", "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4", "0014751e76e8199196d454941c45d1b3a323f1433bd6"),
+      ("tb", "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"),
+      ("bc", "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y", "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6"),
+      ("bc", "BC1SW50QGDZ25J", "6002751e"),
+      ("bc", "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs", "5210751e76e8199196d454941c45d1b3a323"),
+      ("bc", "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0", "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+    ] {
+      let (version, program) = decode_segwit_address(hrp, s).unwrap();
+      let script_pubkey = hex::decode(script_pubkey).unwrap();
+      let op_version = if version == 0 { 0 } else { 0x50 + version };
+      assert_eq!(script_pubkey[0], op_version);
+      assert_eq!(script_pubkey[2..], program[..]);
+      assert_eq!(encode_segwit_address(hrp, version, &program).unwrap(), s.to_lowercase());
+    }
+
+    for (hrp, s) in [
+      ("tb", "tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut"), // wrong hrp
+      ("bc", "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"), // v1 with bech32
+      ("bc", "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL"), // v16 with bech32
+      ("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh"), // v0 with bech32m
+      ("bc", "bc1pw5dgrnzv"), // program of 1 byte
+      ("bc", "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P"), // v0 program of 16 bytes
+      ("bc", "bc1gmk9yu"), // empty data
+    ] {
+      assert!(decode_segwit_address(hrp, s).is_err(), "{:?}", s);
+    }
+  }
+}
@@ -0,0 +1,124 @@
+#![allow(dead_code)]
+
+// consensus encoding of integers and byte strings
+
+// 1, 3, 5 or 9 bytes depending on n
+pub fn write_compact_size(buf: &mut Vec<u8>, n: u64) {
+  if n < 0xfd {
+    buf.push(n as u8);
+  } else if n <= 0xffff {
+    buf.push(0xfd);
+    buf.extend((n as u16).to_le_bytes());
+  } else if n <= 0xffff_ffff {
+    buf.push(0xfe);
+    buf.extend((n as u32).to_le_bytes());
+  } else {
+    buf.push(0xff);
+    buf.extend(n.to_le_bytes());
+  }
+}
+
+// compact size of the length followed by data
+pub fn write_var_bytes(buf: &mut Vec<u8>, data: &[u8]) {
+  write_compact_size(buf, data.len() as u64);
+  buf.extend(data);
+}
+
+pub struct Reader<'a> {
+  buf: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Reader<'a> {
+  pub fn new(buf: &'a [u8]) -> Self {
+    Reader { buf, pos: 0 }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.pos == self.buf.len()
+  }
+
+  // fails unless the whole buffer has been read
+  pub fn finish(&self) -> Result<(), String> {
+    if !self.is_empty() {
+      return Err(format!("{} trailing bytes", self.buf.len() - self.pos));
+    }
+    Ok(())
+  }
+
+  pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+    if self.buf.len() - self.pos < n {
+      return Err(format!("Expected {} bytes, but only {} left", n, self.buf.len() - self.pos));
+    }
+    let bytes = &self.buf[self.pos..self.pos + n];
+    self.pos += n;
+    Ok(bytes)
+  }
+
+  pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+    Ok(self.read_bytes(N)?.try_into().unwrap())
+  }
+
+  pub fn peek_u8(&self) -> Option<u8> {
+    self.buf.get(self.pos).copied()
+  }
+
+  pub fn read_u8(&mut self) -> Result<u8, String> {
+    Ok(self.read_bytes(1)?[0])
+  }
+
+  pub fn read_u32(&mut self) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(self.read_array()?))
+  }
+
+  pub fn read_u64(&mut self) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(self.read_array()?))
+  }
+
+  // rejects non-canonical encodings
+  pub fn read_compact_size(&mut self) -> Result<u64, String> {
+    let (n, min) = match self.read_u8()? {
+      0xfd => (u16::from_le_bytes(self.read_array()?) as u64, 0xfd),
+      0xfe => (u32::from_le_bytes(self.read_array()?) as u64, 0x10000),
+      0xff => (u64::from_le_bytes(self.read_array()?), 0x1_0000_0000),
+      n => return Ok(n as u64),
+    };
+    if n < min {
+      return Err(format!("Non-canonical compact size {}", n));
+    }
+    Ok(n)
+  }
+
+  pub fn read_var_bytes(&mut self) -> Result<&'a [u8], String> {
+    let n = self.read_compact_size()?;
+    self.read_bytes(n.try_into().map_err(|_| format!("Length {} is too large", n))?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_compact_size() {
+    for (n, s) in [
+      (0u64, "00"),
+      (0xfc, "fc"),
+      (0xfd, "fdfd00"),
+      (0xffff, "fdffff"),
+      (0x10000, "fe00000100"),
+      (0x1_0000_0000, "ff0000000001000000"),
+    ] {
+      let mut buf = vec![];
+      write_compact_size(&mut buf, n);
+      assert_eq!(hex::encode(&buf), s);
+
+      let mut r = Reader::new(&buf);
+      assert_eq!(r.read_compact_size().unwrap(), n);
+      assert!(r.finish().is_ok());
+    }
+    assert!(Reader::new(&[0xfd, 0xfc, 0x00]).read_compact_size().is_err());
+    assert!(Reader::new(&[0xfe, 0xff, 0xff, 0x00, 0x00]).read_compact_size().is_err());
+    assert!(Reader::new(&[0x02, 0x00]).read_var_bytes().is_err());
+  }
+}
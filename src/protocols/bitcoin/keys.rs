@@ -0,0 +1,428 @@
+#![allow(dead_code)]
+
+// WIF secret keys and BIP-32 extended keys
+
+use std::fmt;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroize;
+use crate::{
+  building_block::{
+    curve::CurveScalar,
+    secp256k1::{
+      jacobian_point::JacobianPoint,
+      scalar::Scalar,
+    },
+  },
+  protocols::bitcoin::{
+    base58,
+    network::Network,
+    util::hash160,
+  },
+};
+
+// prefix || secret key || 0x01 if the public key is compressed
+pub fn to_wif(sk: &Scalar, network: Network, compressed: bool) -> String {
+  let mut buf = vec![network.wif_prefix()];
+  buf.extend(sk.to_bytes());
+  if compressed {
+    buf.push(0x01);
+  }
+  let s = base58::encode_check(&buf);
+  buf.zeroize();
+  s
+}
+
+// secret key and whether the public key is compressed
+pub fn from_wif(s: &str, network: Network) -> Result<(Scalar, bool), String> {
+  let mut buf = base58::decode_check(s)?;
+  let res = parse_wif(&buf, network);
+  buf.zeroize();
+  res
+}
+
+fn parse_wif(buf: &[u8], network: Network) -> Result<(Scalar, bool), String> {
+  let compressed = match (buf.len(), buf.last()) {
+    (34, Some(0x01)) => true,
+    (33, _) => false,
+    _ => return Err("Invalid WIF length".to_string()),
+  };
+  if buf[0] != network.wif_prefix() {
+    return Err(format!("Invalid WIF prefix {:#04x} on {:?}", buf[0], network));
+  }
+  let sk = Scalar::from_bytes_checked(buf[1..33].try_into().unwrap())?;
+  if sk.is_zero() {
+    return Err("Secret key should not be zero".to_string());
+  }
+  Ok((sk, compressed))
+}
+
+pub(crate) fn compressed_public_key(pk: &JacobianPoint) -> Result<[u8; 33], String> {
+  pk.to_sec1(true).try_into()
+    .map_err(|_| "Public key should not be the point at infinity".to_string())
+}
+
+// common fields of xpub and xprv
+fn serialize(
+  version: [u8; 4],
+  depth: u8,
+  parent_fingerprint: &[u8; 4],
+  child_number: u32,
+  chain_code: &[u8; 32],
+  key: &[u8; 33],
+) -> [u8; 78] {
+  let mut buf = [0u8; 78];
+  buf[..4].copy_from_slice(&version);
+  buf[4] = depth;
+  buf[5..9].copy_from_slice(parent_fingerprint);
+  buf[9..13].copy_from_slice(&child_number.to_be_bytes());
+  buf[13..45].copy_from_slice(chain_code);
+  buf[45..].copy_from_slice(key);
+  buf
+}
+
+// checks the version and the consistency of the master key fields
+fn deserialize(s: &str, version: [u8; 4]) -> Result<[u8; 78], String> {
+  let mut buf = base58::decode_check(s)?;
+  if buf.len() != 78 {
+    let len = buf.len();
+    buf.zeroize();
+    return Err(format!("Extended key should be 78 bytes, but got {}", len));
+  }
+  let mut res = [0u8; 78];
+  res.copy_from_slice(&buf);
+  buf.zeroize();
+
+  if res[..4] != version {
+    let err = format!("Expected version {}, but got {}", hex::encode(version), hex::encode(&res[..4]));
+    res.zeroize();
+    return Err(err);
+  }
+  if res[4] == 0 && res[5..13] != [0u8; 8] {
+    res.zeroize();
+    return Err("Master key should have zero parent fingerprint and child number".to_string());
+  }
+  Ok(res)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPublicKey {
+  pub depth: u8,
+  pub parent_fingerprint: [u8; 4],
+  pub child_number: u32, // hardened if the most significant bit is set
+  pub chain_code: [u8; 32],
+  pub public_key: JacobianPoint,
+}
+
+impl ExtendedPublicKey {
+  // first 4 bytes of hash160 of the compressed public key
+  pub fn fingerprint(&self) -> Result<[u8; 4], String> {
+    let hash = hash160(&compressed_public_key(&self.public_key)?);
+    Ok(hash[..4].try_into().unwrap())
+  }
+
+  pub fn encode(&self, network: Network) -> Result<String, String> {
+    let buf = serialize(
+      network.xpub_version(),
+      self.depth,
+      &self.parent_fingerprint,
+      self.child_number,
+      &self.chain_code,
+      &compressed_public_key(&self.public_key)?,
+    );
+    Ok(base58::encode_check(&buf))
+  }
+
+  pub fn decode(s: &str, network: Network) -> Result<Self, String> {
+    let buf = deserialize(s, network.xpub_version())?;
+    if buf[45] != 0x02 && buf[45] != 0x03 {
+      return Err(format!("Invalid public key prefix {:#04x}", buf[45]));
+    }
+    Ok(ExtendedPublicKey {
+      depth: buf[4],
+      parent_fingerprint: buf[5..9].try_into().unwrap(),
+      child_number: u32::from_be_bytes(buf[9..13].try_into().unwrap()),
+      chain_code: buf[13..45].try_into().unwrap(),
+      public_key: JacobianPoint::from_sec1(&buf[45..])?,
+    })
+  }
+}
+
+#[derive(Clone)]
+pub struct ExtendedPrivateKey {
+  pub depth: u8,
+  pub parent_fingerprint: [u8; 4],
+  pub child_number: u32,
+  pub chain_code: [u8; 32],
+  pub secret_key: Scalar,
+}
+
+impl ExtendedPrivateKey {
+  // master key of BIP-32. IL of HMAC-SHA512("Bitcoin seed", seed) is the
+  // secret key and IR the chain code
+  pub fn from_seed(seed: &[u8]) -> Result<Self, String> {
+    if seed.len() < 16 || seed.len() > 64 {
+      return Err(format!("Seed should be 16 to 64 bytes, but got {}", seed.len()));
+    }
+    let mut mac = Hmac::<Sha512>::new_from_slice(b"Bitcoin seed").unwrap();
+    mac.update(seed);
+    let mut i: [u8; 64] = mac.finalize().into_bytes().into();
+
+    let sk = Scalar::from_bytes_checked(i[..32].try_into().unwrap());
+    let chain_code = i[32..].try_into().unwrap();
+    i.zeroize();
+    let secret_key = sk?;
+    if secret_key.is_zero() {
+      return Err("Master secret key is zero".to_string());
+    }
+    Ok(ExtendedPrivateKey {
+      depth: 0,
+      parent_fingerprint: [0; 4],
+      child_number: 0,
+      chain_code,
+      secret_key,
+    })
+  }
+
+  pub fn to_public(&self) -> ExtendedPublicKey {
+    ExtendedPublicKey {
+      depth: self.depth,
+      parent_fingerprint: self.parent_fingerprint,
+      child_number: self.child_number,
+      chain_code: self.chain_code,
+      public_key: JacobianPoint::mul_base_secret(&self.secret_key),
+    }
+  }
+
+  // the key is 0x00 || secret key
+  pub fn encode(&self, network: Network) -> String {
+    let mut key = [0u8; 33];
+    key[1..].copy_from_slice(&self.secret_key.to_bytes());
+    let mut buf = serialize(
+      network.xprv_version(),
+      self.depth,
+      &self.parent_fingerprint,
+      self.child_number,
+      &self.chain_code,
+      &key,
+    );
+    let s = base58::encode_check(&buf);
+    key.zeroize();
+    buf.zeroize();
+    s
+  }
+
+  pub fn decode(s: &str, network: Network) -> Result<Self, String> {
+    let mut buf = deserialize(s, network.xprv_version())?;
+    let res = Self::parse(&buf);
+    buf.zeroize();
+    res
+  }
+
+  fn parse(buf: &[u8; 78]) -> Result<Self, String> {
+    if buf[45] != 0x00 {
+      return Err(format!("Invalid private key prefix {:#04x}", buf[45]));
+    }
+    let secret_key = Scalar::from_bytes_checked(buf[46..].try_into().unwrap())?;
+    if secret_key.is_zero() {
+      return Err("Secret key should not be zero".to_string());
+    }
+    Ok(ExtendedPrivateKey {
+      depth: buf[4],
+      parent_fingerprint: buf[5..9].try_into().unwrap(),
+      child_number: u32::from_be_bytes(buf[9..13].try_into().unwrap()),
+      chain_code: buf[13..45].try_into().unwrap(),
+      secret_key,
+    })
+  }
+}
+
+// the secret key and chain code are compared without short-circuiting
+impl PartialEq for ExtendedPrivateKey {
+  fn eq(&self, other: &Self) -> bool {
+    let mut lhs = self.secret_key.to_bytes();
+    let mut rhs = other.secret_key.to_bytes();
+    let diff = lhs.iter().chain(&self.chain_code)
+      .zip(rhs.iter().chain(&other.chain_code))
+      .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    lhs.zeroize();
+    rhs.zeroize();
+
+    diff == 0
+      && self.depth == other.depth
+      && self.parent_fingerprint == other.parent_fingerprint
+      && self.child_number == other.child_number
+  }
+}
+
+impl Eq for ExtendedPrivateKey {}
+
+// only the public part is shown
+impl fmt::Debug for ExtendedPrivateKey {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let public_key = self.to_public().public_key.to_sec1(true);
+    f.debug_struct("ExtendedPrivateKey")
+      .field("depth", &self.depth)
+      .field("parent_fingerprint", &hex::encode(self.parent_fingerprint))
+      .field("child_number", &self.child_number)
+      .field("public_key", &hex::encode(public_key))
+      .field("chain_code", &"<redacted>")
+      .field("secret_key", &"<redacted>")
+      .finish()
+  }
+}
+
+impl Drop for ExtendedPrivateKey {
+  fn drop(&mut self) {
+    self.secret_key.zeroize();
+    self.chain_code.zeroize();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_wif() {
+    let sk = Scalar::from(1u32);
+    for (network, compressed, s) in [
+      (Network::Mainnet, true, "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"),
+      (Network::Mainnet, false, "5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf"),
+      (Network::Testnet, true, "cMahea7zqjxrtgAbB7LSGbcQUr1uX1ojuat9jZodMN87JcbXMTcA"),
+      (Network::Regtest, true, "cMahea7zqjxrtgAbB7LSGbcQUr1uX1ojuat9jZodMN87JcbXMTcA"),
+    ] {
+      assert_eq!(to_wif(&sk, network, compressed), s);
+      assert_eq!(from_wif(s, network).unwrap(), (sk, compressed));
+    }
+    assert!(from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn", Network::Testnet).is_err());
+    assert!(from_wif(&base58::encode_check(&[0x80; 32]), Network::Mainnet).is_err());
+
+    let mut buf = vec![0x80];
+    buf.extend([0u8; 32]);
+    assert!(from_wif(&base58::encode_check(&buf), Network::Mainnet).is_err());
+  }
+
+  // test vector 1 of BIP-32 with the seed 000102030405060708090a0b0c0d0e0f
+  const VECTOR_1: [(&str, &str); 4] = [
+    ( // m
+      "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+      "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+    ),
+    ( // m/0H
+      "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+      "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+    ),
+    ( // m/0H/1
+      "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+      "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+    ),
+    ( // m/0H/1/2H
+      "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+      "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+    ),
+  ];
+
+  #[test]
+  fn test_extended_keys() {
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    let master = ExtendedPrivateKey::from_seed(&seed).unwrap();
+    assert_eq!(master.encode(Network::Mainnet), VECTOR_1[0].1);
+
+    let mut parent: Option<ExtendedPublicKey> = None;
+    for (xpub_str, xprv_str) in VECTOR_1 {
+      let xprv = ExtendedPrivateKey::decode(xprv_str, Network::Mainnet).unwrap();
+      let xpub = ExtendedPublicKey::decode(xpub_str, Network::Mainnet).unwrap();
+      assert_eq!(xprv.to_public(), xpub);
+      assert_eq!(xprv.encode(Network::Mainnet), xprv_str);
+      assert_eq!(xpub.encode(Network::Mainnet).unwrap(), xpub_str);
+
+      if let Some(parent) = parent {
+        assert_eq!(xpub.depth, parent.depth + 1);
+        assert_eq!(xpub.parent_fingerprint, parent.fingerprint().unwrap());
+      }
+      parent = Some(xpub);
+    }
+    let xpub = parent.unwrap();
+    assert_eq!(xpub.child_number, 0x80000002);
+
+    // tpub on testnet and regtest
+    let tpub = "tpubDDRojdS4jYQXNugn4t2WLrZ7mjfAyoVQu7MLk4eurqFCbrc7cHLZX8W5YRS8ZskGR9k9t3PqVv68bVBjAyW4nWM9pTGRddt3GQftg6MVQsm";
+    assert_eq!(xpub.encode(Network::Testnet).unwrap(), tpub);
+    assert_eq!(ExtendedPublicKey::decode(tpub, Network::Regtest).unwrap(), xpub);
+    assert!(ExtendedPublicKey::decode(tpub, Network::Mainnet).is_err());
+    assert!(ExtendedPrivateKey::decode(VECTOR_1[0].0, Network::Mainnet).is_err());
+    assert!(ExtendedPublicKey::decode(VECTOR_1[0].1, Network::Mainnet).is_err());
+
+    assert!(ExtendedPrivateKey::from_seed(&[0; 15]).is_err());
+  }
+
+  // test vector 5 of BIP-32
+  #[test]
+  fn test_invalid_extended_keys() {
+    let n = Network::Mainnet;
+    for (s, err) in [
+      // pubkey version / prvkey mismatch
+      ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6LBpB85b3D2yc8sfvZU521AAwdZafEz7mnzBBsz4wKY5fTtTQBm", "Invalid public key prefix 0x00"),
+      // invalid pubkey prefix 04
+      ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Txnt3siSujt9RCVYsx4qHZGc62TG4McvMGcAUjeuwZdduYEvFn", "Invalid public key prefix 0x04"),
+      // invalid pubkey prefix 01
+      ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6N8ZMMXctdiCjxTNq964yKkwrkBJJwpzZS4HS2fxvyYUA4q2Xe4", "Invalid public key prefix 0x01"),
+      // zero depth with non-zero parent fingerprint
+      ("xpub661no6RGEX3uJkY4bNnPcw4URcQTrSibUZ4NqJEw5eBkv7ovTwgiT91XX27VbEXGENhYRCf7hyEbWrR3FewATdCEebj6znwMfQkhRYHRLpJ", "Master key should have zero parent fingerprint and child number"),
+      // zero depth with non-zero index
+      ("xpub661MyMwAuDcm6CRQ5N4qiHKrJ39Xe1R1NyfouMKTTWcguwVcfrZJaNvhpebzGerh7gucBvzEQWRugZDuDXjNDRmXzSZe4c7mnTK97pTvGS8", "Master key should have zero parent fingerprint and child number"),
+      // unknown extended key version
+      ("DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHPmHJiEDXkTiJTVV9rHEBUem2mwVbbNfvT2MTcAqj3nesx8uBf9", "Expected version 0488b21e, but got 01010101"),
+      // invalid pubkey 020000000000000000000000000000000000000000000000000000000000000007
+      ("xpub661MyMwAqRbcEYS8w7XLSVeEsBXy79zSzH1J8vCdxAZningWLdN3zgtU6Q5JXayek4PRsn35jii4veMimro1xefsM58PgBMrvdYre8QyULY", "SEC1 x-coordinate is not on the curve"),
+    ] {
+      assert_eq!(ExtendedPublicKey::decode(s, n).unwrap_err(), err);
+    }
+    for (s, err) in [
+      // prvkey version / pubkey mismatch
+      ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGTQQD3dC4H2D5GBj7vWvSQaaBv5cxi9gafk7NF3pnBju6dwKvH", "Invalid private key prefix 0x03"),
+      // invalid prvkey prefix 04
+      ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFGpWnsj83BHtEy5Zt8CcDr1UiRXuWCmTQLxEK9vbz5gPstX92JQ", "Invalid private key prefix 0x04"),
+      // invalid prvkey prefix 01
+      ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD9y5gkZ6Eq3Rjuahrv17fEQ3Qen6J", "Invalid private key prefix 0x01"),
+      // zero depth with non-zero parent fingerprint
+      ("xprv9s2SPatNQ9Vc6GTbVMFPFo7jsaZySyzk7L8n2uqKXJen3KUmvQNTuLh3fhZMBoG3G4ZW1N2kZuHEPY53qmbZzCHshoQnNf4GvELZfqTUrcv", "Master key should have zero parent fingerprint and child number"),
+      // zero depth with non-zero index
+      ("xprv9s21ZrQH4r4TsiLvyLXqM9P7k1K3EYhA1kkD6xuquB5i39AU8KF42acDyL3qsDbU9NmZn6MsGSUYZEsuoePmjzsB3eFKSUEh3Gu1N3cqVUN", "Master key should have zero parent fingerprint and child number"),
+      // unknown extended key version
+      ("DMwo58pR1QLEFihHiXPVykYB6fJmsTeHvyTp7hRThAtCX8CvYzgPcn8XnmdfHGMQzT7ayAmfo4z3gY5KfbrZWZ6St24UVf2Qgo6oujFktLHdHY4", "Expected version 0488ade4, but got 01010101"),
+      // private key 0 not in 1..n-1
+      ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzF93Y5wvzdUayhgkkFoicQZcP3y52uPPxFnfoLZB21Teqt1VvEHx", "Secret key should not be zero"),
+      // private key n not in 1..n-1
+      ("xprv9s21ZrQH143K24Mfq5zL5MhWK9hUhhGbd45hLXo2Pq2oqzMMo63oStZzFAzHGBP2UuGCqWLTAPLcMtD5SDKr24z3aiUvKr9bJpdrcLg1y3G", "Serialized Scalar is not less than the group order"),
+      // invalid checksum
+      ("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHL", "Invalid base58check checksum"),
+    ] {
+      assert_eq!(ExtendedPrivateKey::decode(s, n).unwrap_err(), err);
+    }
+
+    // truncated key
+    let mut buf = base58::decode_check(VECTOR_1[1].0).unwrap();
+    buf.truncate(77);
+    assert!(ExtendedPublicKey::decode(&base58::encode_check(&buf), n).is_err());
+  }
+
+  #[test]
+  fn test_extended_private_key_debug_and_eq() {
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    let xprv = ExtendedPrivateKey::from_seed(&seed).unwrap();
+    let debug = format!("{:?}", xprv);
+    assert!(debug.contains("0339a36013301597daef41fbe593a02cc513d0b55527ec2df1050e2e8ff49c85c2"));
+    assert!(!debug.contains(&hex::encode(xprv.secret_key.to_bytes())));
+    assert!(!debug.contains(&hex::encode(xprv.chain_code)));
+
+    assert_eq!(xprv.clone(), xprv);
+    let mut other = xprv.clone();
+    other.chain_code[31] ^= 1;
+    assert_ne!(other, xprv);
+    let mut other = xprv.clone();
+    other.secret_key = Scalar::from(1u32);
+    assert_ne!(other, xprv);
+  }
+}
@@ -0,0 +1,10 @@
+pub mod address;
+pub mod base58;
+pub mod bech32;
+pub mod encoding;
+pub mod keys;
+pub mod network;
+pub mod psbt;
+pub mod sighash;
+pub mod transaction;
+pub mod util;
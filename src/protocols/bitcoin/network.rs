@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+  Mainnet,
+  Testnet,
+  Regtest,
+}
+
+// regtest shares the base58 prefixes of testnet and differs only in the bech32 hrp
+impl Network {
+  pub fn bech32_hrp(self) -> &'static str {
+    match self {
+      Network::Mainnet => "bc",
+      Network::Testnet => "tb",
+      Network::Regtest => "bcrt",
+    }
+  }
+
+  pub fn p2pkh_prefix(self) -> u8 {
+    match self {
+      Network::Mainnet => 0x00,
+      _ => 0x6f,
+    }
+  }
+
+  pub fn wif_prefix(self) -> u8 {
+    match self {
+      Network::Mainnet => 0x80,
+      _ => 0xef,
+    }
+  }
+
+  // xpub and tpub
+  pub fn xpub_version(self) -> [u8; 4] {
+    match self {
+      Network::Mainnet => [0x04, 0x88, 0xb2, 0x1e],
+      _ => [0x04, 0x35, 0x87, 0xcf],
+    }
+  }
+
+  // xprv and tprv
+  pub fn xprv_version(self) -> [u8; 4] {
+    match self {
+      Network::Mainnet => [0x04, 0x88, 0xad, 0xe4],
+      _ => [0x04, 0x35, 0x83, 0x94],
+    }
+  }
+}
@@ -0,0 +1,853 @@
+#![allow(dead_code)]
+
+// partially signed bitcoin transactions of BIP-174 (version 0) and BIP-370 (version 2)
+
+use std::{
+  collections::BTreeMap,
+  future::Future,
+};
+use sha2::{Digest, Sha256};
+use crate::{
+  building_block::{
+    message_digest::MessageDigest,
+    secp256k1::{
+      jacobian_point::JacobianPoint,
+      schnorr::XOnlyPublicKey,
+    },
+  },
+  protocols::{
+    bitcoin::{
+      encoding::{
+        write_compact_size,
+        write_var_bytes,
+        Reader,
+      },
+      sighash::{
+        p2wpkh_script_code,
+        segwit_v0_sighash,
+        taproot_key_spend_sighash,
+        SIGHASH_ALL,
+        SIGHASH_DEFAULT,
+      },
+      transaction::{
+        OutPoint,
+        Transaction,
+        TxIn,
+        TxOut,
+      },
+      util::hash160,
+    },
+    gg18::signature::Signature,
+  },
+};
+
+const MAGIC: &[u8; 5] = b"psbt\xff";
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u8 = 0x06;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const PSBT_IN_WITNESS_SCRIPT: u8 = 0x05;
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+// key (key type || key data) to value
+type Map = BTreeMap<Vec<u8>, Vec<u8>>;
+
+fn read_map(r: &mut Reader) -> Result<Map, String> {
+  let mut map = Map::new();
+  loop {
+    let key = r.read_var_bytes()?;
+    if key.is_empty() {
+      return Ok(map);
+    }
+    let value = r.read_var_bytes()?;
+    if map.insert(key.to_vec(), value.to_vec()).is_some() {
+      return Err(format!("Duplicate key {}", hex::encode(key)));
+    }
+  }
+}
+
+fn write_map(buf: &mut Vec<u8>, map: &Map) {
+  for (key, value) in map {
+    write_var_bytes(buf, key);
+    write_var_bytes(buf, value);
+  }
+  buf.push(0x00);
+}
+
+// removes the value of the key that consists only of key_type
+fn take(map: &mut Map, key_type: u8) -> Option<Vec<u8>> {
+  map.remove(&vec![key_type])
+}
+
+// removes the keys of key_type with key data
+fn take_all(map: &mut Map, key_type: u8) -> Vec<(Vec<u8>, Vec<u8>)> {
+  let keys: Vec<Vec<u8>> = map.keys().filter(|k| k.len() > 1 && k[0] == key_type).cloned().collect();
+  keys.into_iter().map(|k| {
+    let value = map.remove(&k).unwrap();
+    (k[1..].to_vec(), value)
+  }).collect()
+}
+
+// fails if a key that consists only of one of key_types is present
+fn reject(map: &Map, key_types: &[u8], version: u32) -> Result<(), String> {
+  match map.keys().find(|k| k.len() == 1 && key_types.contains(&k[0])) {
+    Some(key) => Err(format!("Key type {:#04x} is not allowed in version {}", key[0], version)),
+    None => Ok(()),
+  }
+}
+
+fn parse_u32(value: &[u8], name: &str) -> Result<u32, String> {
+  Ok(u32::from_le_bytes(value.try_into().map_err(|_| format!("{} should be 4 bytes", name))?))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PsbtInput {
+  pub previous_output: OutPoint,
+  pub sequence: Option<u32>, // 0xffffffff if omitted in version 2
+  pub required_time_locktime: Option<u32>,
+  pub required_height_locktime: Option<u32>,
+  pub non_witness_utxo: Option<Transaction>,
+  pub witness_utxo: Option<TxOut>,
+  pub partial_sigs: BTreeMap<Vec<u8>, Vec<u8>>, // public key to DER signature || sighash type
+  pub sighash_type: Option<u32>,
+  pub witness_script: Option<Vec<u8>>,
+  pub unknown: Map,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PsbtOutput {
+  pub amount: u64,
+  pub script: Vec<u8>,
+  pub unknown: Map,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Psbt {
+  pub version: u32, // 0 or 2
+  pub tx_version: i32,
+  pub fallback_locktime: Option<u32>, // lock time of the unsigned transaction in version 0
+  pub inputs: Vec<PsbtInput>,
+  pub outputs: Vec<PsbtOutput>,
+  pub unknown: Map,
+}
+
+impl PsbtInput {
+  fn from_map(mut map: Map, version: u32) -> Result<Self, String> {
+    let mut input = PsbtInput::default();
+    if version == 0 {
+      reject(&map, &[
+        PSBT_IN_PREVIOUS_TXID,
+        PSBT_IN_OUTPUT_INDEX,
+        PSBT_IN_SEQUENCE,
+        PSBT_IN_REQUIRED_TIME_LOCKTIME,
+        PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+      ], version)?;
+    } else {
+      let txid = take(&mut map, PSBT_IN_PREVIOUS_TXID).ok_or("Missing previous txid")?;
+      let vout = take(&mut map, PSBT_IN_OUTPUT_INDEX).ok_or("Missing output index")?;
+      input.previous_output = OutPoint {
+        txid: txid.try_into().map_err(|_| "Previous txid should be 32 bytes")?,
+        vout: parse_u32(&vout, "Output index")?,
+      };
+      input.sequence = take(&mut map, PSBT_IN_SEQUENCE)
+        .map(|x| parse_u32(&x, "Sequence")).transpose()?;
+      input.required_time_locktime = take(&mut map, PSBT_IN_REQUIRED_TIME_LOCKTIME)
+        .map(|x| parse_u32(&x, "Required time locktime")).transpose()?;
+      input.required_height_locktime = take(&mut map, PSBT_IN_REQUIRED_HEIGHT_LOCKTIME)
+        .map(|x| parse_u32(&x, "Required height locktime")).transpose()?;
+      if input.required_time_locktime.is_some_and(|x| x < 500_000_000) {
+        return Err("Required time locktime should be 500000000 or more".to_string());
+      }
+      if input.required_height_locktime.is_some_and(|x| x == 0 || x >= 500_000_000) {
+        return Err("Required height locktime should be between 1 and 499999999".to_string());
+      }
+    }
+
+    input.non_witness_utxo = take(&mut map, PSBT_IN_NON_WITNESS_UTXO).map(|x| Transaction::decode(&x)).transpose()?;
+    input.witness_utxo = take(&mut map, PSBT_IN_WITNESS_UTXO).map(|x| {
+      let mut r = Reader::new(&x);
+      let txout = TxOut::decode(&mut r)?;
+      r.finish()?;
+      Ok::<TxOut, String>(txout)
+    }).transpose()?;
+    for (pk, sig) in take_all(&mut map, PSBT_IN_PARTIAL_SIG) {
+      JacobianPoint::from_sec1(&pk)?;
+      input.partial_sigs.insert(pk, sig);
+    }
+    input.sighash_type = take(&mut map, PSBT_IN_SIGHASH_TYPE)
+      .map(|x| parse_u32(&x, "Sighash type")).transpose()?;
+    input.witness_script = take(&mut map, PSBT_IN_WITNESS_SCRIPT);
+    input.unknown = map;
+    Ok(input)
+  }
+
+  fn to_map(&self, version: u32) -> Map {
+    let mut map = self.unknown.clone();
+    if version != 0 {
+      map.insert(vec![PSBT_IN_PREVIOUS_TXID], self.previous_output.txid.to_vec());
+      map.insert(vec![PSBT_IN_OUTPUT_INDEX], self.previous_output.vout.to_le_bytes().to_vec());
+      for (key_type, value) in [
+        (PSBT_IN_SEQUENCE, self.sequence),
+        (PSBT_IN_REQUIRED_TIME_LOCKTIME, self.required_time_locktime),
+        (PSBT_IN_REQUIRED_HEIGHT_LOCKTIME, self.required_height_locktime),
+      ] {
+        if let Some(value) = value {
+          map.insert(vec![key_type], value.to_le_bytes().to_vec());
+        }
+      }
+    }
+    if let Some(tx) = &self.non_witness_utxo {
+      map.insert(vec![PSBT_IN_NON_WITNESS_UTXO], tx.encode());
+    }
+    if let Some(txout) = &self.witness_utxo {
+      let mut buf = vec![];
+      txout.encode(&mut buf);
+      map.insert(vec![PSBT_IN_WITNESS_UTXO], buf);
+    }
+    for (pk, sig) in &self.partial_sigs {
+      map.insert([&[PSBT_IN_PARTIAL_SIG], &pk[..]].concat(), sig.clone());
+    }
+    if let Some(sighash_type) = self.sighash_type {
+      map.insert(vec![PSBT_IN_SIGHASH_TYPE], sighash_type.to_le_bytes().to_vec());
+    }
+    if let Some(script) = &self.witness_script {
+      map.insert(vec![PSBT_IN_WITNESS_SCRIPT], script.clone());
+    }
+    map
+  }
+}
+
+impl PsbtOutput {
+  fn from_map(mut map: Map, version: u32) -> Result<Self, String> {
+    if version == 0 {
+      reject(&map, &[PSBT_OUT_AMOUNT, PSBT_OUT_SCRIPT], version)?;
+      return Ok(PsbtOutput { unknown: map, ..Default::default() });
+    }
+    let amount = take(&mut map, PSBT_OUT_AMOUNT).ok_or("Missing output amount")?;
+    let script = take(&mut map, PSBT_OUT_SCRIPT).ok_or("Missing output script")?;
+    Ok(PsbtOutput {
+      amount: u64::from_le_bytes(amount.try_into().map_err(|_| "Output amount should be 8 bytes")?),
+      script,
+      unknown: map,
+    })
+  }
+
+  fn to_map(&self, version: u32) -> Map {
+    let mut map = self.unknown.clone();
+    if version != 0 {
+      map.insert(vec![PSBT_OUT_AMOUNT], self.amount.to_le_bytes().to_vec());
+      map.insert(vec![PSBT_OUT_SCRIPT], self.script.clone());
+    }
+    map
+  }
+}
+
+impl Psbt {
+  pub fn decode(buf: &[u8]) -> Result<Self, String> {
+    let mut r = Reader::new(buf);
+    if r.read_bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+      return Err("Missing PSBT magic bytes".to_string());
+    }
+    let mut global = read_map(&mut r)?;
+    let version = take(&mut global, PSBT_GLOBAL_VERSION)
+      .map(|x| parse_u32(&x, "PSBT version")).transpose()?.unwrap_or(0);
+
+    // the unsigned transaction is only present in version 0
+    let (tx_version, fallback_locktime, unsigned_tx, num_inputs, num_outputs) = match version {
+      0 => {
+        reject(&global, &[
+          PSBT_GLOBAL_TX_VERSION,
+          PSBT_GLOBAL_FALLBACK_LOCKTIME,
+          PSBT_GLOBAL_INPUT_COUNT,
+          PSBT_GLOBAL_OUTPUT_COUNT,
+          PSBT_GLOBAL_TX_MODIFIABLE,
+        ], version)?;
+        let tx = Transaction::decode(&take(&mut global, PSBT_GLOBAL_UNSIGNED_TX).ok_or("Missing unsigned transaction")?)?;
+        if tx.inputs.iter().any(|x| !x.script_sig.is_empty() || !x.witness.is_empty()) {
+          return Err("Unsigned transaction should not have scriptSigs or witnesses".to_string());
+        }
+        let (num_inputs, num_outputs) = (tx.inputs.len() as u64, tx.outputs.len() as u64);
+        (tx.version, Some(tx.lock_time), Some(tx), num_inputs, num_outputs)
+      },
+      2 => {
+        reject(&global, &[PSBT_GLOBAL_UNSIGNED_TX], version)?;
+        let tx_version = take(&mut global, PSBT_GLOBAL_TX_VERSION).ok_or("Missing transaction version")?;
+        let fallback_locktime = take(&mut global, PSBT_GLOBAL_FALLBACK_LOCKTIME)
+          .map(|x| parse_u32(&x, "Fallback locktime")).transpose()?;
+        let mut count = |key_type, name| -> Result<u64, String> {
+          let value = take(&mut global, key_type).ok_or(format!("Missing {} count", name))?;
+          let mut r = Reader::new(&value);
+          let n = r.read_compact_size()?;
+          r.finish()?;
+          Ok(n)
+        };
+        let num_inputs = count(PSBT_GLOBAL_INPUT_COUNT, "input")?;
+        let num_outputs = count(PSBT_GLOBAL_OUTPUT_COUNT, "output")?;
+        (
+          i32::from_le_bytes(tx_version.try_into().map_err(|_| "Transaction version should be 4 bytes")?),
+          fallback_locktime,
+          None,
+          num_inputs,
+          num_outputs,
+        )
+      },
+      _ => return Err(format!("Unsupported PSBT version {}", version)),
+    };
+
+    // the counts are untrusted in version 2, so the maps are read one by
+    // one instead of allocating for the counts upfront. fields of version 0
+    // come from the unsigned transaction
+    let mut inputs = vec![];
+    for i in 0..num_inputs {
+      let mut input = PsbtInput::from_map(read_map(&mut r)?, version)?;
+      if let Some(tx) = &unsigned_tx {
+        input.previous_output = tx.inputs[i as usize].previous_output;
+        input.sequence = Some(tx.inputs[i as usize].sequence);
+      }
+      inputs.push(input);
+    }
+    let mut outputs = vec![];
+    for i in 0..num_outputs {
+      let mut output = PsbtOutput::from_map(read_map(&mut r)?, version)?;
+      if let Some(tx) = &unsigned_tx {
+        output.amount = tx.outputs[i as usize].value;
+        output.script = tx.outputs[i as usize].script_pubkey.clone();
+      }
+      outputs.push(output);
+    }
+    r.finish()?;
+
+    for input in &inputs {
+      if let Some(tx) = &input.non_witness_utxo {
+        if tx.txid() != input.previous_output.txid {
+          return Err("Non-witness UTXO does not match the previous txid".to_string());
+        }
+      }
+    }
+    let psbt = Psbt { version, tx_version, fallback_locktime, inputs, outputs, unknown: global };
+    psbt.lock_time()?;
+    Ok(psbt)
+  }
+
+  pub fn encode(&self) -> Vec<u8> {
+    let mut global = self.unknown.clone();
+    if self.version == 0 {
+      global.insert(vec![PSBT_GLOBAL_UNSIGNED_TX], self.unsigned_tx().unwrap().encode_without_witness());
+    } else {
+      global.insert(vec![PSBT_GLOBAL_VERSION], self.version.to_le_bytes().to_vec());
+      global.insert(vec![PSBT_GLOBAL_TX_VERSION], self.tx_version.to_le_bytes().to_vec());
+      if let Some(locktime) = self.fallback_locktime {
+        global.insert(vec![PSBT_GLOBAL_FALLBACK_LOCKTIME], locktime.to_le_bytes().to_vec());
+      }
+      for (key_type, n) in [
+        (PSBT_GLOBAL_INPUT_COUNT, self.inputs.len()),
+        (PSBT_GLOBAL_OUTPUT_COUNT, self.outputs.len()),
+      ] {
+        let mut buf = vec![];
+        write_compact_size(&mut buf, n as u64);
+        global.insert(vec![key_type], buf);
+      }
+    }
+
+    let mut buf = MAGIC.to_vec();
+    write_map(&mut buf, &global);
+    for input in &self.inputs {
+      write_map(&mut buf, &input.to_map(self.version));
+    }
+    for output in &self.outputs {
+      write_map(&mut buf, &output.to_map(self.version));
+    }
+    buf
+  }
+
+  // lock time of version 0 or the one BIP-370 determines from the required
+  // lock times of the inputs, preferring height if both are possible
+  pub fn lock_time(&self) -> Result<u32, String> {
+    if self.version == 0 {
+      return Ok(self.fallback_locktime.unwrap_or(0));
+    }
+    let with_locktime: Vec<&PsbtInput> = self.inputs.iter()
+      .filter(|x| x.required_time_locktime.is_some() || x.required_height_locktime.is_some())
+      .collect();
+    if with_locktime.is_empty() {
+      return Ok(self.fallback_locktime.unwrap_or(0));
+    }
+    if with_locktime.iter().all(|x| x.required_height_locktime.is_some()) {
+      return Ok(with_locktime.iter().filter_map(|x| x.required_height_locktime).max().unwrap());
+    }
+    if with_locktime.iter().all(|x| x.required_time_locktime.is_some()) {
+      return Ok(with_locktime.iter().filter_map(|x| x.required_time_locktime).max().unwrap());
+    }
+    Err("Inputs require incompatible lock times".to_string())
+  }
+
+  pub fn unsigned_tx(&self) -> Result<Transaction, String> {
+    Ok(Transaction {
+      version: self.tx_version,
+      inputs: self.inputs.iter().map(|x| TxIn {
+        previous_output: x.previous_output,
+        script_sig: vec![],
+        sequence: x.sequence.unwrap_or(0xffffffff),
+        witness: vec![],
+      }).collect(),
+      outputs: self.outputs.iter().map(|x| TxOut {
+        value: x.amount,
+        script_pubkey: x.script.clone(),
+      }).collect(),
+      lock_time: self.lock_time()?,
+    })
+  }
+
+  // witness UTXO or the output of the non-witness UTXO the input spends
+  pub fn spent_output(&self, index: usize) -> Result<TxOut, String> {
+    let input = self.inputs.get(index).ok_or(format!("Input {} does not exist", index))?;
+    if let Some(txout) = &input.witness_utxo {
+      return Ok(txout.clone());
+    }
+    input.non_witness_utxo.as_ref()
+      .and_then(|tx| tx.outputs.get(input.previous_output.vout as usize))
+      .cloned()
+      .ok_or(format!("Input {} has no UTXO", index))
+  }
+
+  // BIP-143 sighash of P2WPKH and P2WSH inputs and BIP-341 key path sighash
+  // of P2TR inputs with the sighash type of the input
+  pub fn sighash(&self, index: usize) -> Result<[u8; 32], String> {
+    let tx = self.unsigned_tx()?;
+    let spent = self.spent_output(index)?;
+    let script = &spent.script_pubkey;
+    let sighash_type = self.inputs[index].sighash_type;
+
+    match (script.len(), script.first(), script.get(1)) {
+      // OP_0 <20 bytes>
+      (22, Some(0x00), Some(0x14)) => {
+        let script_code = p2wpkh_script_code(script[2..].try_into().unwrap());
+        segwit_v0_sighash(&tx, index, &script_code, spent.value, sighash_type.unwrap_or(SIGHASH_ALL))
+      },
+      // OP_0 <32 bytes>
+      (34, Some(0x00), Some(0x20)) => {
+        let witness_script = self.inputs[index].witness_script.as_ref()
+          .ok_or(format!("P2WSH input {} has no witness script", index))?;
+        if Sha256::digest(witness_script)[..] != script[2..] {
+          return Err(format!("Witness script does not match the P2WSH output of input {}", index));
+        }
+        segwit_v0_sighash(&tx, index, witness_script, spent.value, sighash_type.unwrap_or(SIGHASH_ALL))
+      },
+      // OP_1 <32 bytes>
+      (34, Some(0x51), Some(0x20)) => {
+        let prevouts = (0..self.inputs.len()).map(|i| self.spent_output(i)).collect::<Result<Vec<_>, _>>()?;
+        taproot_key_spend_sighash(&tx, index, &prevouts, sighash_type.unwrap_or(SIGHASH_DEFAULT))
+      },
+      _ => Err(format!("Unsupported script of input {}: {}", index, hex::encode(script))),
+    }
+  }
+
+  // whether the input spends a P2WPKH output of pk, a P2WSH output whose
+  // witness script has pk or a P2TR output whose internal key is pk. false if
+  // the input has no UTXO
+  pub fn requires_signature(&self, index: usize, pk: &JacobianPoint) -> Result<bool, String> {
+    let spent = match self.spent_output(index) {
+      Ok(spent) => spent,
+      Err(_) => return Ok(false),
+    };
+    let script = &spent.script_pubkey;
+    let sec1 = pk.to_sec1(true);
+    if sec1.len() != 33 {
+      return Err("Public key should not be the point at infinity".to_string());
+    }
+
+    if script.len() == 22 && script[..2] == [0x00, 0x14] {
+      return Ok(script[2..] == hash160(&sec1));
+    }
+    if script.len() == 34 && script[..2] == [0x00, 0x20] {
+      let push = [&[0x21], &sec1[..]].concat();
+      return Ok(self.inputs[index].witness_script.as_ref()
+        .is_some_and(|ws| ws.windows(push.len()).any(|x| x == push)));
+    }
+    if script.len() == 34 && script[..2] == [0x51, 0x20] {
+      let (internal_key, _) = XOnlyPublicKey::from_point(pk)?;
+      let (output_key, _) = internal_key.tap_tweak(None)?;
+      return Ok(script[2..] == output_key.to_bytes());
+    }
+    Ok(false)
+  }
+
+  // adds DER(sig) || sighash type in the low-S form after verifying sig
+  pub fn add_partial_sig(&mut self, index: usize, pk: &JacobianPoint, sig: &Signature) -> Result<(), String> {
+    let sighash = self.sighash(index)?;
+    if !sig.verify(pk, &sighash, MessageDigest::Prehashed) {
+      return Err(format!("Invalid signature for input {}", index));
+    }
+    let mut sig = sig.clone();
+    sig.normalize_s();
+    let mut value = sig.to_der();
+    value.push(self.inputs[index].sighash_type.unwrap_or(SIGHASH_ALL) as u8);
+    self.inputs[index].partial_sigs.insert(pk.to_sec1(true), value);
+    Ok(())
+  }
+
+  // feeds the sighash of each input pk should sign to sign, e.g. a round of
+  // the threshold Signer, and adds the resulting signatures. returns the
+  // indices of the signed inputs and of the skipped taproot inputs, which
+  // need BIP-340 signatures rather than ECDSA ones. the PSBT is left
+  // unchanged on failure
+  pub async fn sign_inputs<F, Fut>(&mut self, pk: &JacobianPoint, mut sign: F) -> Result<(Vec<usize>, Vec<usize>), String>
+  where
+    F: FnMut(usize, [u8; 32]) -> Fut,
+    Fut: Future<Output = Result<Signature, String>>,
+  {
+    // validate all inputs before the first signing round
+    let mut sighashes = vec![];
+    let mut taproot = vec![];
+    for index in 0..self.inputs.len() {
+      if !self.requires_signature(index, pk)? || self.inputs[index].partial_sigs.contains_key(&pk.to_sec1(true)) {
+        continue;
+      }
+      if self.spent_output(index)?.script_pubkey[0] == 0x51 {
+        taproot.push(index);
+        continue;
+      }
+      sighashes.push((index, self.sighash(index)?));
+    }
+
+    let mut sigs = vec![];
+    for (index, sighash) in &sighashes {
+      sigs.push(sign(*index, *sighash).await?);
+    }
+    let mut psbt = self.clone();
+    for ((index, _), sig) in sighashes.iter().zip(&sigs) {
+      psbt.add_partial_sig(*index, pk, sig)?;
+    }
+    *self = psbt;
+    Ok((sighashes.into_iter().map(|(index, _)| index).collect(), taproot))
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    building_block::secp256k1::scalar::Scalar,
+    protocols::{
+      bitcoin::address::Address,
+      gg18::simple_signer::SimpleSigner,
+    },
+  };
+
+  fn decode(s: &str) -> Result<Psbt, String> {
+    Psbt::decode(&hex::decode(s).unwrap())
+  }
+
+  // test vectors of BIP-174
+  #[test]
+  fn test_bip174_vectors() {
+    // a network transaction, an unsigned transaction with scriptSigs,
+    // no unsigned transaction and duplicate unsigned transactions
+    for s in [
+      "0200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf6000000006a473044022070b2245123e6bf474d60c5b50c043d4c691a5d2435f09a34a7662a9dc251790a022001329ca9dacf280bdf30740ec0390422422c81cb45839457aeb76fc12edd95b3012102657d118d3357b8e0f4c2cd46db7b39f6d9c38d9a70abcb9b2de5dc8dbfe4ce31feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300",
+      "70736274ff0100fd0a010200000002ab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be4000000006a47304402204759661797c01b036b25928948686218347d89864b719e1f7fcf57d1e511658702205309eabf56aa4d8891ffd111fdf1336f3a29da866d7f8486d75546ceedaf93190121035cdc61fc7ba971c0b501a646a2a83b102cb43881217ca682dc86e2d73fa88292feffffffab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40100000000feffffff02603bea0b000000001976a914768a40bbd740cbe81d988e71de2a4d5c71396b1d88ac8e240000000000001976a9146f4620b553fa095e721b9ee0efe9fa039cca459788ac00000000000001012000e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787010416001485d13537f2e265405a34dbafa9e3dda01fb82308000000",
+      "70736274ff000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab30000000000",
+      "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab30000000001003f0200000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000ffffffff010000000000000000036a010000000000000000",
+    ] {
+      assert!(decode(s).is_err());
+    }
+
+    // non-witness UTXO, P2SH-P2WPKH input with a redeem script and unknown input key
+    for s in [
+      "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab300000000000000",
+      "70736274ff0100a00200000002ab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40000000000feffffffab0949a08c5af7c49b8212f417e2f15ab3f5c33dcf153821a8139f877a5b7be40100000000feffffff02603bea0b000000001976a914768a40bbd740cbe81d988e71de2a4d5c71396b1d88ac8e240000000000001976a9146f4620b553fa095e721b9ee0efe9fa039cca459788ac000000000001076a47304402204759661797c01b036b25928948686218347d89864b719e1f7fcf57d1e511658702205309eabf56aa4d8891ffd111fdf1336f3a29da866d7f8486d75546ceedaf93190121035cdc61fc7ba971c0b501a646a2a83b102cb43881217ca682dc86e2d73fa882920001012000e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787010416001485d13537f2e265405a34dbafa9e3dda01fb82308000000",
+      "70736274ff01003f0200000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000ffffffff010000000000000000036a010000000000000a0f0102030405060708090f0102030405060708090a0b0c0d0e0f0000",
+    ] {
+      let psbt = decode(s).unwrap();
+      assert_eq!(hex::encode(psbt.encode()), s);
+    }
+
+    let psbt = decode("70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be22e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab300000000000000").unwrap();
+    assert_eq!(psbt.spent_output(0).unwrap().value, 200_000_000);
+    assert_eq!(psbt.lock_time().unwrap(), 1257139);
+  }
+
+  // native P2WPKH example of BIP-143 as a PSBT
+  fn bip143_psbt() -> Psbt {
+    let tx = Transaction::decode(&hex::decode(
+      "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000",
+    ).unwrap()).unwrap();
+    let mut buf = MAGIC.to_vec();
+    write_map(&mut buf, &Map::from([(vec![PSBT_GLOBAL_UNSIGNED_TX], tx.encode())]));
+    buf.extend([0x00; 4]); // empty input and output maps
+    let mut psbt = Psbt::decode(&buf).unwrap();
+    psbt.inputs[1].witness_utxo = Some(TxOut {
+      value: 600_000_000,
+      script_pubkey: hex::decode("00141d0f172a0ecb48aee1be1f2687d2963ae33f71a1").unwrap(),
+    });
+    psbt
+  }
+
+  fn bip143_secret_key() -> Scalar {
+    let buf = hex::decode("619c335025c7f4012e556c2a58b2506e30b8511b53ade95ea316fd8c3286feb9").unwrap();
+    Scalar::from_bytes_checked(&buf.try_into().unwrap()).unwrap()
+  }
+
+  #[tokio::test]
+  async fn test_sign_inputs() {
+    let mut psbt = bip143_psbt();
+    assert_eq!(hex::encode(psbt.sighash(1).unwrap()), "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670");
+    assert!(psbt.sighash(0).is_err());
+
+    let sk = bip143_secret_key();
+    let pk = JacobianPoint::mul_base(&sk);
+    let signed = psbt.sign_inputs(&pk, |_, sighash| async move {
+      SimpleSigner::sign(&sighash, MessageDigest::Prehashed, &sk)
+    }).await.unwrap();
+    assert_eq!(signed, (vec![1], vec![]));
+
+    // the signature of BIP-143 as both use RFC 6979 nonces
+    let psbt = Psbt::decode(&psbt.encode()).unwrap();
+    assert_eq!(
+      hex::encode(&psbt.inputs[1].partial_sigs[&pk.to_sec1(true)]),
+      "304402203609e17b84f6a7d30c80bfa610b5b4542f32a8a0d5447a12fb1366d7f01cc44a0220573a954c4518331561406f90300e8f3358f51928d43c212a8caed02de67eebee01",
+    );
+
+    // signed inputs are skipped
+    let mut psbt = psbt;
+    let signed = psbt.sign_inputs(&pk, |_, sighash| async move {
+      SimpleSigner::sign(&sighash, MessageDigest::Prehashed, &sk)
+    }).await.unwrap();
+    assert_eq!(signed, (vec![], vec![]));
+
+    // a signature by another key is rejected
+    let sig = SimpleSigner::sign(&psbt.sighash(1).unwrap(), MessageDigest::Prehashed, &Scalar::from(1u32)).unwrap();
+    assert!(psbt.add_partial_sig(1, &pk, &sig).is_err());
+  }
+
+  #[tokio::test]
+  async fn test_p2wsh_and_p2tr() {
+    let sk = bip143_secret_key();
+    let pk = JacobianPoint::mul_base(&sk);
+    let other = JacobianPoint::mul_base(&Scalar::from(1u32));
+
+    // 2-of-2 multisig of pk and other
+    let witness_script = [
+      &[0x52, 0x21][..], &pk.to_sec1(true), &[0x21], &other.to_sec1(true), &[0x52, 0xae],
+    ].concat();
+    let p2wsh = [&[0x00, 0x20][..], &Sha256::digest(&witness_script)[..]].concat();
+
+    let mut psbt = bip143_psbt();
+    psbt.inputs[1].witness_utxo = Some(TxOut { value: 600_000_000, script_pubkey: p2wsh });
+    assert!(psbt.sighash(1).is_err());
+    psbt.inputs[1].witness_script = Some(witness_script);
+    psbt.inputs[1].sighash_type = Some(SIGHASH_ALL | 0x80);
+    assert!(psbt.requires_signature(1, &pk).unwrap());
+    assert!(!psbt.requires_signature(1, &JacobianPoint::mul_base(&Scalar::from(2u32))).unwrap());
+
+    let signed = psbt.sign_inputs(&pk, |_, sighash| async move {
+      SimpleSigner::sign(&sighash, MessageDigest::Prehashed, &sk)
+    }).await.unwrap();
+    assert_eq!(signed, (vec![1], vec![]));
+    assert_eq!(psbt.inputs[1].partial_sigs[&pk.to_sec1(true)].last(), Some(&0x81));
+
+    // taproot inputs need BIP-340 signatures, so only the other inputs are signed
+    let p2tr = Address::p2tr(&pk, None).unwrap().script_pubkey();
+    psbt.inputs[0].witness_utxo = Some(TxOut { value: 1000, script_pubkey: p2tr });
+    psbt.inputs[1].partial_sigs.clear();
+    assert!(psbt.requires_signature(0, &pk).unwrap());
+    assert!(psbt.sighash(0).is_ok());
+    let mut calls = vec![];
+    let signed = psbt.sign_inputs(&pk, |index, sighash| {
+      calls.push(index);
+      async move { SimpleSigner::sign(&sighash, MessageDigest::Prehashed, &sk) }
+    }).await.unwrap();
+    assert_eq!(signed, (vec![1], vec![0]));
+    assert_eq!(calls, vec![1]);
+    assert!(psbt.inputs[0].partial_sigs.is_empty());
+    assert!(psbt.inputs[1].partial_sigs.contains_key(&pk.to_sec1(true)));
+  }
+
+  #[tokio::test]
+  async fn test_sign_inputs_failure() {
+    let sk = bip143_secret_key();
+    let pk = JacobianPoint::mul_base(&sk);
+    let mut psbt = bip143_psbt();
+    let p2wpkh = Address::p2wpkh(&pk).unwrap().script_pubkey();
+    psbt.inputs[0].witness_utxo = Some(TxOut { value: 1000, script_pubkey: p2wpkh });
+
+    // the first input is not signed when the signing of the second one fails
+    let before = psbt.clone();
+    let res = psbt.sign_inputs(&pk, |index, sighash| async move {
+      if index == 1 {
+        return Err("Signing failed".to_string());
+      }
+      SimpleSigner::sign(&sighash, MessageDigest::Prehashed, &sk)
+    }).await;
+    assert_eq!(res.unwrap_err(), "Signing failed");
+    assert_eq!(psbt, before);
+
+    let signed = psbt.sign_inputs(&pk, |_, sighash| async move {
+      SimpleSigner::sign(&sighash, MessageDigest::Prehashed, &sk)
+    }).await.unwrap();
+    assert_eq!(signed, (vec![0, 1], vec![]));
+  }
+
+  #[test]
+  fn test_version_2() {
+    let mut psbt = bip143_psbt();
+    let sighash = psbt.sighash(1).unwrap();
+    psbt.version = 2;
+
+    let buf = psbt.encode();
+    let decoded = Psbt::decode(&buf).unwrap();
+    assert_eq!(decoded, psbt);
+    assert_eq!(decoded.unsigned_tx().unwrap(), bip143_psbt().unsigned_tx().unwrap());
+    assert_eq!(decoded.sighash(1).unwrap(), sighash);
+
+    // height is preferred when both are possible
+    psbt.inputs[0].required_height_locktime = Some(100);
+    psbt.inputs[0].required_time_locktime = Some(600_000_000);
+    psbt.inputs[1].required_height_locktime = Some(200);
+    assert_eq!(psbt.lock_time().unwrap(), 200);
+    psbt.inputs[1].required_height_locktime = None;
+    psbt.inputs[1].required_time_locktime = Some(500_000_000);
+    assert_eq!(psbt.lock_time().unwrap(), 600_000_000);
+    psbt.inputs[0].required_time_locktime = None;
+    assert!(psbt.lock_time().is_err());
+    assert!(Psbt::decode(&psbt.encode()).is_err());
+
+
+    // input and output counts larger than the maps that follow
+    for count in ["01", "fdffff", "ffffffffffffffffff"] {
+      let mut global = Map::new();
+      global.insert(vec![PSBT_GLOBAL_TX_VERSION], 2u32.to_le_bytes().to_vec());
+      global.insert(vec![PSBT_GLOBAL_INPUT_COUNT], hex::decode(count).unwrap());
+      global.insert(vec![PSBT_GLOBAL_OUTPUT_COUNT], hex::decode(count).unwrap());
+      global.insert(vec![PSBT_GLOBAL_VERSION], 2u32.to_le_bytes().to_vec());
+      let mut buf = MAGIC.to_vec();
+      write_map(&mut buf, &global);
+      assert!(Psbt::decode(&buf).is_err());
+    }
+  }
+
+  // global, input and output maps of a PSBT
+  fn to_maps(psbt: &Psbt) -> Vec<Map> {
+    let buf = psbt.encode();
+    let mut r = Reader::new(&buf[MAGIC.len()..]);
+    (0..1 + psbt.inputs.len() + psbt.outputs.len()).map(|_| read_map(&mut r).unwrap()).collect()
+  }
+
+  fn from_maps(maps: &[Map]) -> Vec<u8> {
+    let mut buf = MAGIC.to_vec();
+    for map in maps {
+      write_map(&mut buf, map);
+    }
+    buf
+  }
+
+  // the valid and invalid cases BIP-370 lists for its test vectors, built on
+  // the BIP-143 example with 2 inputs and 2 outputs
+  #[test]
+  fn test_bip370_cases() {
+    let v0 = to_maps(&bip143_psbt());
+    let mut psbt = bip143_psbt();
+    psbt.version = 2;
+    let v2 = to_maps(&psbt);
+    let (global, input, output) = (0, 1, 3);
+    let u32_le = |x: u32| x.to_le_bytes().to_vec();
+    type Case<'a> = Box<dyn Fn(&mut Vec<Map>) + 'a>;
+
+    // valid ones round-trip
+    let valid: Vec<Case> = vec![
+      Box::new(|_| {}),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_FALLBACK_LOCKTIME], u32_le(1)); }),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_TX_MODIFIABLE], vec![0x03]); }),
+      Box::new(|m| { m[input].insert(vec![PSBT_IN_SEQUENCE], u32_le(0xfffffffe)); }),
+      Box::new(|m| { m[input].insert(vec![PSBT_IN_REQUIRED_TIME_LOCKTIME], u32_le(500_000_000)); }),
+      Box::new(|m| { m[input].insert(vec![PSBT_IN_REQUIRED_HEIGHT_LOCKTIME], u32_le(499_999_999)); }),
+      Box::new(|m| {
+        m[input].insert(vec![PSBT_IN_REQUIRED_TIME_LOCKTIME], u32_le(500_000_000));
+        m[input].insert(vec![PSBT_IN_REQUIRED_HEIGHT_LOCKTIME], u32_le(1));
+      }),
+    ];
+    for f in valid {
+      let mut maps = v2.clone();
+      f(&mut maps);
+      let buf = from_maps(&maps);
+      assert_eq!(Psbt::decode(&buf).unwrap().encode(), buf);
+    }
+
+    // version 2 without the required fields, with fields of version 0 or
+    // with the lock times out of range
+    let invalid: Vec<Case> = vec![
+      Box::new(|m| { m[global].remove(&vec![PSBT_GLOBAL_TX_VERSION]); }),
+      Box::new(|m| { m[global].remove(&vec![PSBT_GLOBAL_INPUT_COUNT]); }),
+      Box::new(|m| { m[global].remove(&vec![PSBT_GLOBAL_OUTPUT_COUNT]); }),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_UNSIGNED_TX], v0[global][&vec![PSBT_GLOBAL_UNSIGNED_TX]].clone()); }),
+      Box::new(|m| { m[input].remove(&vec![PSBT_IN_PREVIOUS_TXID]); }),
+      Box::new(|m| { m[input].remove(&vec![PSBT_IN_OUTPUT_INDEX]); }),
+      Box::new(|m| { m[output].remove(&vec![PSBT_OUT_AMOUNT]); }),
+      Box::new(|m| { m[output].remove(&vec![PSBT_OUT_SCRIPT]); }),
+      Box::new(|m| { m[input].insert(vec![PSBT_IN_REQUIRED_TIME_LOCKTIME], u32_le(499_999_999)); }),
+      Box::new(|m| { m[input].insert(vec![PSBT_IN_REQUIRED_HEIGHT_LOCKTIME], u32_le(500_000_000)); }),
+      // counts that don't match the number of maps
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_INPUT_COUNT], vec![1]); }),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_INPUT_COUNT], vec![3]); }),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_OUTPUT_COUNT], vec![1]); }),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_OUTPUT_COUNT], vec![3]); }),
+      // fields of version 2 in version 0
+      Box::new(|m| { m[global].remove(&vec![PSBT_GLOBAL_VERSION]); }),
+      Box::new(|m| { m[global].insert(vec![PSBT_GLOBAL_VERSION], u32_le(0)); }),
+    ];
+    for f in invalid {
+      let mut maps = v2.clone();
+      f(&mut maps);
+      assert!(Psbt::decode(&from_maps(&maps)).is_err());
+    }
+
+    // and in the maps of version 0
+    for (index, key_type, value) in [
+      (global, PSBT_GLOBAL_TX_VERSION, u32_le(2)),
+      (global, PSBT_GLOBAL_FALLBACK_LOCKTIME, u32_le(0)),
+      (global, PSBT_GLOBAL_INPUT_COUNT, vec![2]),
+      (global, PSBT_GLOBAL_OUTPUT_COUNT, vec![2]),
+      (global, PSBT_GLOBAL_TX_MODIFIABLE, vec![0x03]),
+      (input, PSBT_IN_PREVIOUS_TXID, vec![0; 32]),
+      (input, PSBT_IN_OUTPUT_INDEX, u32_le(0)),
+      (input, PSBT_IN_SEQUENCE, u32_le(0xffffffff)),
+      (input, PSBT_IN_REQUIRED_TIME_LOCKTIME, u32_le(500_000_000)),
+      (input, PSBT_IN_REQUIRED_HEIGHT_LOCKTIME, u32_le(1)),
+      (output, PSBT_OUT_AMOUNT, 0u64.to_le_bytes().to_vec()),
+      (output, PSBT_OUT_SCRIPT, vec![0x6a]),
+    ] {
+      let mut maps = v0.clone();
+      maps[index].insert(vec![key_type], value);
+      assert!(Psbt::decode(&from_maps(&maps)).is_err());
+    }
+  }
+
+  // version 0 is encoded without PSBT_GLOBAL_VERSION as BIP-174 reads a
+  // missing one as 0, so an explicit 0 is dropped
+  #[test]
+  fn test_explicit_version_0() {
+    let mut maps = to_maps(&bip143_psbt());
+    let buf = from_maps(&maps);
+    maps[0].insert(vec![PSBT_GLOBAL_VERSION], 0u32.to_le_bytes().to_vec());
+    let psbt = Psbt::decode(&from_maps(&maps)).unwrap();
+    assert_eq!(psbt, bip143_psbt());
+    assert_eq!(psbt.encode(), buf);
+  }
+}
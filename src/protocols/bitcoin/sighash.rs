@@ -0,0 +1,243 @@
+#![allow(dead_code)]
+
+// signature hashes of segwit v0 (BIP-143) and taproot key path spending (BIP-341)
+
+use sha2::{Digest, Sha256};
+use crate::{
+  building_block::secp256k1::schnorr::tagged_hash,
+  protocols::bitcoin::{
+    encoding::write_var_bytes,
+    transaction::{Transaction, TxOut},
+    util::sha256d,
+  },
+};
+
+pub const SIGHASH_DEFAULT: u32 = 0x00; // taproot only. same as SIGHASH_ALL
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+// ALL, NONE or SINGLE optionally combined with ANYONECANPAY
+fn check_sighash_type(sighash_type: u32, allow_default: bool) -> Result<(), String> {
+  let base = sighash_type & !SIGHASH_ANYONECANPAY;
+  if (allow_default && sighash_type == SIGHASH_DEFAULT) ||
+    (sighash_type & !0x83 == 0 && (SIGHASH_ALL..=SIGHASH_SINGLE).contains(&base)) {
+    return Ok(());
+  }
+  Err(format!("Invalid sighash type {:#04x}", sighash_type))
+}
+
+// the hashes and the components they commit to
+struct Components {
+  prevouts: Vec<u8>,
+  amounts: Vec<u8>,
+  script_pubkeys: Vec<u8>,
+  sequences: Vec<u8>,
+  outputs: Vec<u8>,
+}
+
+impl Components {
+  fn new(tx: &Transaction, prevouts: &[TxOut]) -> Self {
+    let mut c = Components {
+      prevouts: vec![],
+      amounts: vec![],
+      script_pubkeys: vec![],
+      sequences: vec![],
+      outputs: vec![],
+    };
+    for input in &tx.inputs {
+      c.prevouts.extend(input.previous_output.txid);
+      c.prevouts.extend(input.previous_output.vout.to_le_bytes());
+      c.sequences.extend(input.sequence.to_le_bytes());
+    }
+    for prevout in prevouts {
+      c.amounts.extend(prevout.value.to_le_bytes());
+      write_var_bytes(&mut c.script_pubkeys, &prevout.script_pubkey);
+    }
+    for output in &tx.outputs {
+      output.encode(&mut c.outputs);
+    }
+    c
+  }
+}
+
+// script code of a P2WPKH input, i.e. the P2PKH script of the key hash
+pub fn p2wpkh_script_code(pubkey_hash: &[u8; 20]) -> Vec<u8> {
+  [&[0x76, 0xa9, 0x14], &pubkey_hash[..], &[0x88, 0xac]].concat()
+}
+
+// double SHA-256 of the BIP-143 preimage. script_code is the witness script
+// of a P2WSH input or p2wpkh_script_code of a P2WPKH input
+pub fn segwit_v0_sighash(
+  tx: &Transaction,
+  index: usize,
+  script_code: &[u8],
+  amount: u64,
+  sighash_type: u32,
+) -> Result<[u8; 32], String> {
+  check_sighash_type(sighash_type, false)?;
+  let input = tx.inputs.get(index).ok_or(format!("Input {} does not exist", index))?;
+  let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+  let base = sighash_type & 0x1f;
+
+  let c = Components::new(tx, &[]);
+  let hash_prevouts = if anyone_can_pay { [0u8; 32] } else { sha256d(&c.prevouts) };
+  let hash_sequence = if anyone_can_pay || base != SIGHASH_ALL { [0u8; 32] } else { sha256d(&c.sequences) };
+  let hash_outputs = match tx.outputs.get(index) {
+    _ if base == SIGHASH_ALL => sha256d(&c.outputs),
+    Some(output) if base == SIGHASH_SINGLE => {
+      let mut buf = vec![];
+      output.encode(&mut buf);
+      sha256d(&buf)
+    },
+    _ => [0u8; 32],
+  };
+
+  let mut buf = tx.version.to_le_bytes().to_vec();
+  buf.extend(hash_prevouts);
+  buf.extend(hash_sequence);
+  buf.extend(input.previous_output.txid);
+  buf.extend(input.previous_output.vout.to_le_bytes());
+  write_var_bytes(&mut buf, script_code);
+  buf.extend(amount.to_le_bytes());
+  buf.extend(input.sequence.to_le_bytes());
+  buf.extend(hash_outputs);
+  buf.extend(tx.lock_time.to_le_bytes());
+  buf.extend(sighash_type.to_le_bytes());
+  Ok(sha256d(&buf))
+}
+
+// hash_TapSighash(0x00 || SigMsg) of BIP-341 for key path spending without
+// an annex. prevouts are the outputs spent by all inputs of tx
+pub fn taproot_key_spend_sighash(
+  tx: &Transaction,
+  index: usize,
+  prevouts: &[TxOut],
+  sighash_type: u32,
+) -> Result<[u8; 32], String> {
+  check_sighash_type(sighash_type, true)?;
+  if prevouts.len() != tx.inputs.len() {
+    return Err(format!("Expected {} prevouts, but got {}", tx.inputs.len(), prevouts.len()));
+  }
+  let input = tx.inputs.get(index).ok_or(format!("Input {} does not exist", index))?;
+  let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+  let base = sighash_type & 0x03;
+  let sha256 = |x: &[u8]| -> [u8; 32] { Sha256::digest(x).into() };
+
+  let c = Components::new(tx, prevouts);
+  let mut msg = vec![0x00, sighash_type as u8]; // epoch and hash type
+  msg.extend(tx.version.to_le_bytes());
+  msg.extend(tx.lock_time.to_le_bytes());
+  if !anyone_can_pay {
+    msg.extend(sha256(&c.prevouts));
+    msg.extend(sha256(&c.amounts));
+    msg.extend(sha256(&c.script_pubkeys));
+    msg.extend(sha256(&c.sequences));
+  }
+  if base != SIGHASH_NONE && base != SIGHASH_SINGLE {
+    msg.extend(sha256(&c.outputs));
+  }
+  msg.push(0x00); // spend type of key path spending without an annex
+  if anyone_can_pay {
+    msg.extend(input.previous_output.txid);
+    msg.extend(input.previous_output.vout.to_le_bytes());
+    prevouts[index].encode(&mut msg);
+    msg.extend(input.sequence.to_le_bytes());
+  } else {
+    msg.extend((index as u32).to_le_bytes());
+  }
+  if base == SIGHASH_SINGLE {
+    let output = tx.outputs.get(index).ok_or(format!("Input {} has no output for SIGHASH_SINGLE", index))?;
+    let mut buf = vec![];
+    output.encode(&mut buf);
+    msg.extend(sha256(&buf));
+  }
+  Ok(tagged_hash("TapSighash", &[&msg]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::protocols::bitcoin::encoding::Reader;
+
+  // native P2WPKH example of BIP-143
+  #[test]
+  fn test_segwit_v0_sighash() {
+    let tx = Transaction::decode(&hex::decode(
+      "0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000",
+    ).unwrap()).unwrap();
+    let c = Components::new(&tx, &[]);
+    assert_eq!(hex::encode(sha256d(&c.prevouts)), "96b827c8483d4e9b96712b6713a7b68d6e8003a781feba36c31143470b4efd37");
+    assert_eq!(hex::encode(sha256d(&c.sequences)), "52b0a642eea2fb7ae638c36f6252b6750293dbe574a806984b8e4d8548339a3b");
+    assert_eq!(hex::encode(sha256d(&c.outputs)), "863ef3e1a92afbfdb97f31ad0fc7683ee943e9abcf2501590ff8f6551f47e5e5");
+
+    let pubkey_hash = hex::decode("1d0f172a0ecb48aee1be1f2687d2963ae33f71a1").unwrap();
+    let script_code = p2wpkh_script_code(&pubkey_hash.try_into().unwrap());
+    let sighash = segwit_v0_sighash(&tx, 1, &script_code, 600_000_000, SIGHASH_ALL).unwrap();
+    assert_eq!(hex::encode(sighash), "c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670");
+
+    assert!(segwit_v0_sighash(&tx, 1, &script_code, 600_000_000, SIGHASH_DEFAULT).is_err());
+    assert!(segwit_v0_sighash(&tx, 1, &script_code, 600_000_000, 0x04).is_err());
+    assert!(segwit_v0_sighash(&tx, 2, &script_code, 600_000_000, SIGHASH_ALL).is_err());
+  }
+
+  // key path spending cases of the Bitcoin Core test framework. prevouts
+  // are given as a list of outputs
+  #[test]
+  fn test_taproot_key_spend_sighash() {
+    for (tx, prevouts, index, sighash_type, sighash) in [
+      (
+        "020000000164eb050a5e3da0c2a65e4786f26d753b7bc69691fabccafb11f7acef36641f1846010000003101b2b404392a22000000000017a9147f2bde86fe78bf68a0544a4f290e12f0b7e0a08c87580200000000000017a91425d11723074ecfb96a0a83c3956bfaf362ae0c908758020000000000001600147e20f938993641de67bb0cdd71682aa34c4d29ad5802000000000000160014c64984dc8761acfa99418bd6bedc79b9287d652d72000000",
+        "01365724000000000023542156b39dab4f8f3508e0432cfb41fab110170acaa2d4c42539cb90a4dc7c093bc500",
+        0, SIGHASH_DEFAULT,
+        "33ca0ebfb4a945eeee9569fc0f5040221275f88690b7f8592ada88ce3bdf6703",
+      ),
+      (
+        "0200000002fff49be59befe7566050737910f6ccdc5e749c7f8860ddc140386463d88c5ad0f3000000002cf68eb4a3d67f9d4c079249f7e4f27b8854815cb1ed13842d4fbf395f9e217fd605ee24090100000065235d9203f458520000000000160014b6d48333bb13b4c644e57c43a9a26df3a44b785e58020000000000001976a914eea9461a9e1e3f765d3af3e726162e0229fe3eb688ac58020000000000001976a9143a8869c9f2b5ea1d4ff3aeeb6a8fb2fffb1ad5fe88ac0ad7125c",
+        "02591f220000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece48fb310000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece",
+        1, SIGHASH_ALL,
+        "626ab955d58c9a8a600a0c580549d06dc7da4e802eb2a531f62a588e430967a8",
+      ),
+      (
+        "0200000001350005f65aa830ced2079df348e2d8c2bdb4f10e2dde6a161d8a07b40d1ad87dae000000001611d0d603d9dc0e000000000017a914459b6d7d6bbb4d8837b4bf7e9a4556f952da2f5c8758020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88ac58020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88aca71c1f4f",
+        "01c4811000000000002251201bf9297d0a2968ae6693aadd0fa514717afefd218087a239afb7418e2d22e65c",
+        0, SIGHASH_ALL | SIGHASH_ANYONECANPAY,
+        "dfa9437f9c9a1d1f9af271f79f2f5482f287cdb0d2e03fa92c8a9b216cc6061c",
+      ),
+      (
+        "020000000185bed1a6da2bffbd60ec681a1bfb71c5111d6395b99b3f8b2bf90167111bcb18f5010000007c83ace802ded24a00000000001600142c4698f9f7a773866879755aa78c516fb332af8e5802000000000000160014d38639dfbac4259323b98a472405db0c461b31fa61073747",
+        "0144c84d0000000000225120e3f2107989c88e67296ab2faca930efa2e3a5bd3ff0904835a11c9e807458621",
+        0, SIGHASH_NONE,
+        "3129de36a5d05fff97ffca31eb75fcccbbbc27b3147a7a36a9e4b45d8b625067",
+      ),
+      (
+        "eb93dbb901028c8515589dac980b6e7f8e4088b77ed866ca0d6d210a7218b6fd0f6b22dd6d7300000000eb4740a9047efc0e0000000000160014913da2128d8fcf292b3691db0e187414aa1783825802000000000000160014913da2128d8fcf292b3691db0e187414aa178382580200000000000017a9143dd27f01c6f7ef9bb9159937b17f17065ed01a0c875802000000000000160014d7630e19df70ada9905ede1722b800c0005f246641000000",
+        "013fed110000000000225120eb536ae8c33580290630fc495046e998086a64f8f33b93b07967d9029b265c55",
+        0, SIGHASH_NONE | SIGHASH_ANYONECANPAY,
+        "2441e8b0e063a2083ee790f14f2045022f07258ddde5ee01de543c9e789d80ae",
+      ),
+      (
+        "02000000017836b409a5fed32211407e44b971591f2032053f14701fb5b3a30c0ff382f2cc9c0100000061ac55f60288fb5600000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ac58020000000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ace4000000",
+        "01efa558000000000022512007071ea3dc7e331b0687d0193d1e6d6ed10e645ef36f10ef8831d5e522ac9e80",
+        0, SIGHASH_SINGLE,
+        "30239345177cadd0e3ea413d49803580abb6cb27971b481b7788a78d35117a88",
+      ),
+      (
+        "0100000001aa6deae89d5e0aaca58714fc76ef6f3c8284224888089232d4e663843ed3ab3eae010000008b6657a60450cb4c0000000000160014a3d42b5413ef0c0701c4702f3cd7d4df222c147058020000000000001976a91430b4ed8723a4ee8992aa2c8814cfe5c3ad0ab9d988ac5802000000000000160014365b1166a6ed0a5e8e9dff17a6d00bbb43454bc758020000000000001976a914bc98c51a84fe7fad5dc380eb8b39586eff47241688ac4f313247",
+        "0107af4e00000000002251202c36d243dfc06cb56a248e62df27ecba7417307511a81ae61aa41c597a929c69",
+        0, SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+        "bf9c83f26c6dd16449e4921f813f551c4218e86f2ec906ca8611175b41b566df",
+      ),
+    ] {
+      let tx = Transaction::decode(&hex::decode(tx).unwrap()).unwrap();
+      let buf = hex::decode(prevouts).unwrap();
+      let mut r = Reader::new(&buf);
+      let prevouts: Vec<TxOut> = (0..r.read_compact_size().unwrap())
+        .map(|_| TxOut::decode(&mut r).unwrap())
+        .collect();
+      let actual = taproot_key_spend_sighash(&tx, index, &prevouts, sighash_type).unwrap();
+      assert_eq!(hex::encode(actual), sighash);
+    }
+  }
+}
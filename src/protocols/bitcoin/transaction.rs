@@ -0,0 +1,193 @@
+#![allow(dead_code)]
+
+// Bitcoin transactions in the consensus encoding of BIP-144
+
+use crate::protocols::bitcoin::{
+  encoding::{
+    write_compact_size,
+    write_var_bytes,
+    Reader,
+  },
+  util::sha256d,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OutPoint {
+  pub txid: [u8; 32], // in the internal byte order, i.e. reversed from the hex notation
+  pub vout: u32,
+}
+
+impl OutPoint {
+  fn encode(&self, buf: &mut Vec<u8>) {
+    buf.extend(self.txid);
+    buf.extend(self.vout.to_le_bytes());
+  }
+
+  fn decode(r: &mut Reader) -> Result<Self, String> {
+    Ok(OutPoint { txid: r.read_array()?, vout: r.read_u32()? })
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxIn {
+  pub previous_output: OutPoint,
+  pub script_sig: Vec<u8>,
+  pub sequence: u32,
+  pub witness: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOut {
+  pub value: u64, // in satoshis
+  pub script_pubkey: Vec<u8>,
+}
+
+impl TxOut {
+  pub fn encode(&self, buf: &mut Vec<u8>) {
+    buf.extend(self.value.to_le_bytes());
+    write_var_bytes(buf, &self.script_pubkey);
+  }
+
+  pub fn decode(r: &mut Reader) -> Result<Self, String> {
+    Ok(TxOut { value: r.read_u64()?, script_pubkey: r.read_var_bytes()?.to_vec() })
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+  pub version: i32,
+  pub inputs: Vec<TxIn>,
+  pub outputs: Vec<TxOut>,
+  pub lock_time: u32,
+}
+
+impl Transaction {
+  // with the marker, flag and witnesses if any input has a witness
+  pub fn encode(&self) -> Vec<u8> {
+    self.encode_with(self.inputs.iter().any(|x| !x.witness.is_empty()))
+  }
+
+  // legacy encoding from which the txid is computed
+  pub fn encode_without_witness(&self) -> Vec<u8> {
+    self.encode_with(false)
+  }
+
+  fn encode_with(&self, with_witness: bool) -> Vec<u8> {
+    let mut buf = self.version.to_le_bytes().to_vec();
+    if with_witness {
+      buf.extend([0x00, 0x01]);
+    }
+    write_compact_size(&mut buf, self.inputs.len() as u64);
+    for input in &self.inputs {
+      input.previous_output.encode(&mut buf);
+      write_var_bytes(&mut buf, &input.script_sig);
+      buf.extend(input.sequence.to_le_bytes());
+    }
+    write_compact_size(&mut buf, self.outputs.len() as u64);
+    for output in &self.outputs {
+      output.encode(&mut buf);
+    }
+    if with_witness {
+      for input in &self.inputs {
+        write_compact_size(&mut buf, input.witness.len() as u64);
+        for item in &input.witness {
+          write_var_bytes(&mut buf, item);
+        }
+      }
+    }
+    buf.extend(self.lock_time.to_le_bytes());
+    buf
+  }
+
+  pub fn decode(buf: &[u8]) -> Result<Self, String> {
+    let mut r = Reader::new(buf);
+    let tx = Self::decode_from(&mut r)?;
+    r.finish()?;
+    Ok(tx)
+  }
+
+  // a zero input count followed by 0x01 is the segwit marker and flag
+  pub fn decode_from(r: &mut Reader) -> Result<Self, String> {
+    let version = i32::from_le_bytes(r.read_array()?);
+    let mut num_inputs = r.read_compact_size()?;
+    let mut has_witness = false;
+    if num_inputs == 0 && r.peek_u8() == Some(0x01) {
+      r.read_u8()?;
+      has_witness = true;
+      num_inputs = r.read_compact_size()?;
+    }
+
+    let mut inputs = vec![];
+    for _ in 0..num_inputs {
+      inputs.push(TxIn {
+        previous_output: OutPoint::decode(r)?,
+        script_sig: r.read_var_bytes()?.to_vec(),
+        sequence: r.read_u32()?,
+        witness: vec![],
+      });
+    }
+    let mut outputs = vec![];
+    for _ in 0..r.read_compact_size()? {
+      outputs.push(TxOut::decode(r)?);
+    }
+    if has_witness {
+      for input in inputs.iter_mut() {
+        for _ in 0..r.read_compact_size()? {
+          input.witness.push(r.read_var_bytes()?.to_vec());
+        }
+      }
+      if inputs.iter().all(|x| x.witness.is_empty()) {
+        return Err("Segwit transaction has no witness".to_string());
+      }
+    }
+    Ok(Transaction { version, inputs, outputs, lock_time: r.read_u32()? })
+  }
+
+  pub fn txid(&self) -> [u8; 32] {
+    sha256d(&self.encode_without_witness())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encode_decode() {
+    let mut tx = Transaction {
+      version: 2,
+      inputs: vec![TxIn {
+        previous_output: OutPoint { txid: [0x11; 32], vout: 1 },
+        script_sig: vec![],
+        sequence: 0xfffffffd,
+        witness: vec![vec![0x01, 0x02], vec![]],
+      }],
+      outputs: vec![TxOut { value: 1000, script_pubkey: vec![0x51] }],
+      lock_time: 100,
+    };
+    let buf = tx.encode();
+    assert_eq!(&buf[4..6], &[0x00, 0x01]);
+    assert_eq!(Transaction::decode(&buf).unwrap(), tx);
+
+    // the txid does not commit to the witness
+    let txid = tx.txid();
+    tx.inputs[0].witness.clear();
+    assert_eq!(tx.txid(), txid);
+    assert_eq!(tx.encode(), tx.encode_without_witness());
+    assert_eq!(Transaction::decode(&tx.encode()).unwrap(), tx);
+
+    // segwit marker with no witness and trailing bytes
+    let mut buf = buf.clone();
+    let len = buf.len();
+    buf[len - 9] = 0x00;
+    buf.drain(len - 8..len - 4);
+    assert_eq!(Transaction::decode(&buf).unwrap_err(), "Segwit transaction has no witness");
+    let mut buf = tx.encode();
+    buf.push(0x00);
+    assert!(Transaction::decode(&buf).is_err());
+
+    // no inputs and outputs
+    let tx = Transaction::decode(&hex::decode("02000000000000000000").unwrap()).unwrap();
+    assert!(tx.inputs.is_empty() && tx.outputs.is_empty());
+  }
+}
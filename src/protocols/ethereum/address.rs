@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use crate::{
+  building_block::{
+    message_digest::MessageDigest,
+    secp256k1::jacobian_point::JacobianPoint,
+  },
+  protocols::{
+    ethereum::util::keccak256,
+    gg18::signature::Signature,
+  },
+};
+
+// last 20 bytes of keccak256(x || y) of the public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+  pub fn from_public_key(pk: &JacobianPoint) -> Result<Self, String> {
+    let sec1 = pk.to_sec1(false);
+    if sec1.len() != 65 {
+      return Err("Public key should not be the point at infinity".to_string());
+    }
+    let hash = keccak256(&sec1[1..]);
+    Ok(Address(hash[12..].try_into().unwrap()))
+  }
+
+  // address of the key that signed the 32-byte digest
+  pub fn recover(digest: &[u8; 32], sig: &Signature) -> Result<Self, String> {
+    let pk = sig.recover(digest, MessageDigest::Prehashed)?;
+    Self::from_public_key(&pk)
+  }
+
+  // accepts 40 hex digits with or without 0x. mixed-case input should
+  // carry a valid EIP-55 checksum
+  pub fn from_hex(s: &str) -> Result<Self, String> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    let buf = hex::decode(digits).map_err(|e| format!("Invalid address {}: {}", s, e))?;
+    let addr = Address(buf.try_into().map_err(|_| format!("Address should be 20 bytes: {}", s))?);
+
+    let is_mixed_case =
+      digits.chars().any(|c| c.is_ascii_lowercase()) &&
+      digits.chars().any(|c| c.is_ascii_uppercase());
+    if is_mixed_case && addr.to_checksum_string()[2..] != *digits {
+      return Err(format!("Invalid EIP-55 checksum: {}", s));
+    }
+    Ok(addr)
+  }
+
+  // EIP-55: a hex letter is upper-cased if the corresponding nibble of
+  // keccak256 of the lower-case hex address is 8 or more
+  pub fn to_checksum_string(self) -> String {
+    let lower = hex::encode(self.0);
+    let hash = keccak256(lower.as_bytes());
+    let digits: String = lower.chars().enumerate().map(|(i, c)| {
+      let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0xf;
+      if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+    }).collect();
+    format!("0x{}", digits)
+  }
+}
+
+impl fmt::Display for Address {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.to_checksum_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::secp256k1::scalar::Scalar;
+
+  #[test]
+  fn test_from_public_key() {
+    let pk = JacobianPoint::mul_base(&Scalar::from(1u32));
+    let addr = Address::from_public_key(&pk).unwrap();
+    assert_eq!(addr.to_string(), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+    assert!(Address::from_public_key(&JacobianPoint::point_at_infinity()).is_err());
+  }
+
+  // examples of EIP-55
+  #[test]
+  fn test_checksum() {
+    for s in [
+      "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+      "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+      "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+      "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ] {
+      let addr = Address::from_hex(s).unwrap();
+      assert_eq!(addr.to_checksum_string(), s);
+      assert_eq!(Address::from_hex(&s.to_lowercase()).unwrap(), addr);
+    }
+    assert!(Address::from_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+    assert!(Address::from_hex("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea").is_err());
+  }
+}
@@ -0,0 +1,88 @@
+#![allow(dead_code)]
+
+// EIP-191 personal_sign messages and r || s || v signatures
+
+use crate::{
+  building_block::{
+    curve::CurveScalar,
+    secp256k1::curve::Secp256k1,
+  },
+  protocols::{
+    ethereum::util::keccak256,
+    gg18::signature::Signature,
+  },
+};
+
+// keccak256("\x19Ethereum Signed Message:\n" || len(msg) || msg)
+pub fn personal_sign_hash(msg: &[u8]) -> [u8; 32] {
+  let mut buf = format!("\x19Ethereum Signed Message:\n{}", msg.len()).into_bytes();
+  buf.extend(msg);
+  keccak256(&buf)
+}
+
+// low-S form of sig and the y-parity of its R. Ethereum cannot express
+// R whose x-coordinate is not less than n
+pub fn normalize(sig: &Signature) -> Result<(Signature, u8), String> {
+  let mut sig = sig.clone();
+  sig.normalize_s();
+  match sig.recovery_id {
+    Some(recovery_id) if recovery_id < 2 => Ok((sig, recovery_id)),
+    Some(recovery_id) => Err(format!("Recovery id {} cannot be expressed in Ethereum", recovery_id)),
+    None => Err("Signature has no recovery id".to_string()),
+  }
+}
+
+// r || s || v where v = 27 + y-parity
+pub fn to_rsv(sig: &Signature) -> Result<[u8; 65], String> {
+  let (sig, y_parity) = normalize(sig)?;
+  let mut buf = [0u8; 65];
+  buf[..32].copy_from_slice(&sig.r.to_bytes());
+  buf[32..64].copy_from_slice(&sig.s.to_bytes());
+  buf[64] = 27 + y_parity;
+  Ok(buf)
+}
+
+pub fn from_rsv(buf: &[u8; 65]) -> Result<Signature, String> {
+  if buf[64] != 27 && buf[64] != 28 {
+    return Err(format!("Invalid v {}", buf[64]));
+  }
+  let sig = Signature::<Secp256k1>::from_bytes(buf[..64].try_into().unwrap())?;
+  Ok(Signature::new_recoverable(&sig.r, &sig.s, buf[64] - 27))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    building_block::{
+      message_digest::MessageDigest,
+      secp256k1::{
+        jacobian_point::JacobianPoint,
+        scalar::Scalar,
+      },
+    },
+    protocols::{
+      ethereum::address::Address,
+      gg18::simple_signer::SimpleSigner,
+    },
+  };
+
+  #[test]
+  fn test_personal_sign() {
+    let hash = personal_sign_hash(b"hello");
+    assert_eq!(hex::encode(hash), "50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750");
+
+    let sk = Scalar::from(1u32);
+    let addr = Address::from_public_key(&JacobianPoint::mul_base(&sk)).unwrap();
+    let sig = SimpleSigner::sign(&hash, MessageDigest::Prehashed, &sk).unwrap();
+
+    let rsv = to_rsv(&sig).unwrap();
+    assert!(rsv[64] == 27 || rsv[64] == 28);
+    let sig = from_rsv(&rsv).unwrap();
+    assert_eq!(Address::recover(&hash, &sig).unwrap(), addr);
+
+    let mut rsv = rsv;
+    rsv[64] = 29;
+    assert!(from_rsv(&rsv).is_err());
+  }
+}
@@ -0,0 +1,6 @@
+pub mod address;
+pub mod message;
+pub mod rlp;
+pub mod transaction;
+pub mod typed_data;
+pub mod util;
@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+// recursive length prefix encoding of Ethereum
+
+use rug::{
+  integer::Order,
+  Integer,
+};
+use crate::protocols::ethereum::address::Address;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RlpItem {
+  Bytes(Vec<u8>),
+  List(Vec<RlpItem>),
+}
+
+impl RlpItem {
+  // big-endian without leading zeroes. 0 is the empty string. integers of
+  // Ethereum are uint256
+  pub fn uint(i: &Integer) -> Result<Self, String> {
+    if *i < 0 || i.significant_bits() > 256 {
+      return Err(format!("RLP integer should be in [0, 2^256), but got {}", i));
+    }
+    Ok(RlpItem::Bytes(i.to_digits::<u8>(Order::MsfBe)))
+  }
+
+  pub fn encode(&self) -> Vec<u8> {
+    match self {
+      RlpItem::Bytes(bytes) => {
+        if bytes.len() == 1 && bytes[0] < 0x80 {
+          return bytes.clone();
+        }
+        let mut buf = Self::encode_length(bytes.len(), 0x80);
+        buf.extend(bytes);
+        buf
+      },
+      RlpItem::List(items) => {
+        let payload: Vec<u8> = items.iter().flat_map(|x| x.encode()).collect();
+        let mut buf = Self::encode_length(payload.len(), 0xc0);
+        buf.extend(payload);
+        buf
+      },
+    }
+  }
+
+  // offset + len if len < 56, otherwise offset + 55 + the length of len
+  // followed by len in big-endian
+  fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+      return vec![offset + len as u8];
+    }
+    let len_bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    let mut buf = vec![offset + 55 + len_bytes.len() as u8];
+    buf.extend(len_bytes);
+    buf
+  }
+}
+
+impl From<u64> for RlpItem {
+  fn from(x: u64) -> Self {
+    RlpItem::Bytes(x.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect())
+  }
+}
+
+impl From<&[u8]> for RlpItem {
+  fn from(x: &[u8]) -> Self {
+    RlpItem::Bytes(x.to_vec())
+  }
+}
+
+// None is the empty string as in the to field of contract creation
+impl From<Option<Address>> for RlpItem {
+  fn from(x: Option<Address>) -> Self {
+    match x {
+      Some(addr) => RlpItem::Bytes(addr.0.to_vec()),
+      None => RlpItem::Bytes(vec![]),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn encode(item: RlpItem) -> String {
+    hex::encode(item.encode())
+  }
+
+  // examples of the RLP section of the Ethereum wiki
+  #[test]
+  fn test_encode() {
+    let b = |s: &str| RlpItem::Bytes(s.as_bytes().to_vec());
+    assert_eq!(encode(b("dog")), "83646f67");
+    assert_eq!(encode(RlpItem::List(vec![b("cat"), b("dog")])), "c88363617483646f67");
+    assert_eq!(encode(b("")), "80");
+    assert_eq!(encode(RlpItem::List(vec![])), "c0");
+    assert_eq!(encode(RlpItem::from(0)), "80");
+    assert_eq!(encode(RlpItem::Bytes(vec![0])), "00");
+    assert_eq!(encode(RlpItem::from(15)), "0f");
+    assert_eq!(encode(RlpItem::from(1024)), "820400");
+
+    // [ [], [[]], [ [], [[]] ] ]
+    let empty = || RlpItem::List(vec![]);
+    let set = RlpItem::List(vec![
+      empty(),
+      RlpItem::List(vec![empty()]),
+      RlpItem::List(vec![empty(), RlpItem::List(vec![empty()])]),
+    ]);
+    assert_eq!(encode(set), "c7c0c1c0c3c0c1c0");
+
+    let lorem = b("Lorem ipsum dolor sit amet, consectetur adipisicing elit");
+    let mut expected = "b838".to_string();
+    expected.push_str(&hex::encode("Lorem ipsum dolor sit amet, consectetur adipisicing elit"));
+    assert_eq!(encode(lorem), expected);
+  }
+
+  #[test]
+  fn test_uint() {
+    let max = (Integer::from(1) << 256) - 1;
+    assert_eq!(encode(RlpItem::uint(&max).unwrap()), format!("a0{}", "ff".repeat(32)));
+    assert_eq!(encode(RlpItem::uint(&Integer::ZERO).unwrap()), "80");
+    assert!(RlpItem::uint(&(max + 1)).is_err());
+    assert!(RlpItem::uint(&Integer::from(-1)).is_err());
+  }
+}
@@ -0,0 +1,332 @@
+#![allow(dead_code)]
+
+// legacy (EIP-155), EIP-2930 and EIP-1559 transactions
+
+use rug::Integer;
+use crate::{
+  building_block::curve::CurveScalar,
+  protocols::{
+    ethereum::{
+      address::Address,
+      message::normalize,
+      rlp::RlpItem,
+      util::keccak256,
+    },
+    gg18::signature::Signature,
+  },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessListItem {
+  pub address: Address,
+  pub storage_keys: Vec<[u8; 32]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyTransaction {
+  pub chain_id: Option<u64>, // None for transactions before EIP-155
+  pub nonce: u64,
+  pub gas_price: Integer,
+  pub gas_limit: u64,
+  pub to: Option<Address>, // None for contract creation
+  pub value: Integer,
+  pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip2930Transaction {
+  pub chain_id: u64,
+  pub nonce: u64,
+  pub gas_price: Integer,
+  pub gas_limit: u64,
+  pub to: Option<Address>,
+  pub value: Integer,
+  pub data: Vec<u8>,
+  pub access_list: Vec<AccessListItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip1559Transaction {
+  pub chain_id: u64,
+  pub nonce: u64,
+  pub max_priority_fee_per_gas: Integer,
+  pub max_fee_per_gas: Integer,
+  pub gas_limit: u64,
+  pub to: Option<Address>,
+  pub value: Integer,
+  pub data: Vec<u8>,
+  pub access_list: Vec<AccessListItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transaction {
+  Legacy(LegacyTransaction),
+  Eip2930(Eip2930Transaction),
+  Eip1559(Eip1559Transaction),
+}
+
+fn access_list_item(access_list: &[AccessListItem]) -> RlpItem {
+  RlpItem::List(access_list.iter().map(|x| RlpItem::List(vec![
+    RlpItem::from(&x.address.0[..]),
+    RlpItem::List(x.storage_keys.iter().map(|k| RlpItem::from(&k[..])).collect()),
+  ])).collect())
+}
+
+impl Transaction {
+  // EIP-2718 type byte. None for legacy transactions
+  fn tx_type(&self) -> Option<u8> {
+    match self {
+      Transaction::Legacy(_) => None,
+      Transaction::Eip2930(_) => Some(1),
+      Transaction::Eip1559(_) => Some(2),
+    }
+  }
+
+  // fields other than the signature. fails if an amount is out of uint256
+  fn fields(&self) -> Result<Vec<RlpItem>, String> {
+    Ok(match self {
+      Transaction::Legacy(tx) => vec![
+        RlpItem::from(tx.nonce),
+        RlpItem::uint(&tx.gas_price)?,
+        RlpItem::from(tx.gas_limit),
+        RlpItem::from(tx.to),
+        RlpItem::uint(&tx.value)?,
+        RlpItem::from(&tx.data[..]),
+      ],
+      Transaction::Eip2930(tx) => vec![
+        RlpItem::from(tx.chain_id),
+        RlpItem::from(tx.nonce),
+        RlpItem::uint(&tx.gas_price)?,
+        RlpItem::from(tx.gas_limit),
+        RlpItem::from(tx.to),
+        RlpItem::uint(&tx.value)?,
+        RlpItem::from(&tx.data[..]),
+        access_list_item(&tx.access_list),
+      ],
+      Transaction::Eip1559(tx) => vec![
+        RlpItem::from(tx.chain_id),
+        RlpItem::from(tx.nonce),
+        RlpItem::uint(&tx.max_priority_fee_per_gas)?,
+        RlpItem::uint(&tx.max_fee_per_gas)?,
+        RlpItem::from(tx.gas_limit),
+        RlpItem::from(tx.to),
+        RlpItem::uint(&tx.value)?,
+        RlpItem::from(&tx.data[..]),
+        access_list_item(&tx.access_list),
+      ],
+    })
+  }
+
+  // type byte followed by the RLP of fields
+  fn encode(&self, fields: Vec<RlpItem>) -> Vec<u8> {
+    let mut buf: Vec<u8> = self.tx_type().into_iter().collect();
+    buf.extend(RlpItem::List(fields).encode());
+    buf
+  }
+
+  // legacy transactions with a chain id append [chain_id, 0, 0] as EIP-155 defines
+  pub fn signing_payload(&self) -> Result<Vec<u8>, String> {
+    let mut fields = self.fields()?;
+    if let Transaction::Legacy(LegacyTransaction { chain_id: Some(chain_id), .. }) = self {
+      fields.extend([RlpItem::from(*chain_id), RlpItem::from(0), RlpItem::from(0)]);
+    }
+    Ok(self.encode(fields))
+  }
+
+  // digest to sign with MessageDigest::Prehashed
+  pub fn sighash(&self) -> Result<[u8; 32], String> {
+    Ok(keccak256(&self.signing_payload()?))
+  }
+
+  // v is 35 + 2 * chain_id + y-parity for legacy transactions with a chain id,
+  // 27 + y-parity for those without it and y-parity for typed transactions
+  pub fn encode_signed(&self, sig: &Signature) -> Result<Vec<u8>, String> {
+    let (sig, y_parity) = normalize(sig)?;
+    let v = match self {
+      Transaction::Legacy(LegacyTransaction { chain_id: Some(chain_id), .. }) =>
+        chain_id.checked_mul(2).and_then(|x| x.checked_add(35 + y_parity as u64))
+          .ok_or(format!("Chain id {} is too large for v of EIP-155", chain_id))?,
+      Transaction::Legacy(_) => 27 + y_parity as u64,
+      _ => y_parity as u64,
+    };
+    let mut fields = self.fields()?;
+    fields.extend([
+      RlpItem::from(v),
+      RlpItem::uint(&sig.r.to_integer())?,
+      RlpItem::uint(&sig.s.to_integer())?,
+    ]);
+    Ok(self.encode(fields))
+  }
+
+  pub fn recover_sender(&self, sig: &Signature) -> Result<Address, String> {
+    Address::recover(&self.sighash()?, sig)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    building_block::{
+      message_digest::MessageDigest,
+      secp256k1::{
+        jacobian_point::JacobianPoint,
+        scalar::Scalar,
+      },
+    },
+    protocols::gg18::simple_signer::SimpleSigner,
+  };
+
+  fn secret_key() -> Scalar {
+    Scalar::from_bytes_checked(&[0x46; 32]).unwrap()
+  }
+
+  // signs with the RFC 6979 nonce of secret_key() and checks the encoding
+  fn check(tx: &Transaction, sighash: &str, signed: &str) {
+    assert_eq!(hex::encode(tx.sighash().unwrap()), sighash);
+
+    let sk = secret_key();
+    let sig = SimpleSigner::sign(&tx.sighash().unwrap(), MessageDigest::Prehashed, &sk).unwrap();
+    assert_eq!(hex::encode(tx.encode_signed(&sig).unwrap()), signed);
+
+    let addr = Address::from_public_key(&JacobianPoint::mul_base(&sk)).unwrap();
+    assert_eq!(tx.recover_sender(&sig).unwrap(), addr);
+  }
+
+  fn ether(x: u64) -> Integer {
+    Integer::from(x) * Integer::from(10u64.pow(18))
+  }
+
+  fn gwei(x: u64) -> Integer {
+    Integer::from(x) * Integer::from(10u64.pow(9))
+  }
+
+  fn to() -> Option<Address> {
+    Some(Address([0x35; 20]))
+  }
+
+  // example of EIP-155
+  #[test]
+  fn test_eip155() {
+    let tx = Transaction::Legacy(LegacyTransaction {
+      chain_id: Some(1),
+      nonce: 9,
+      gas_price: gwei(20),
+      gas_limit: 21000,
+      to: to(),
+      value: ether(1),
+      data: vec![],
+    });
+    assert_eq!(
+      hex::encode(tx.signing_payload().unwrap()),
+      "ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080",
+    );
+    check(
+      &tx,
+      "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53",
+      "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+    );
+  }
+
+  #[test]
+  fn test_pre_eip155_contract_creation() {
+    let tx = Transaction::Legacy(LegacyTransaction {
+      chain_id: None,
+      nonce: 0,
+      gas_price: Integer::from(1),
+      gas_limit: 100000,
+      to: None,
+      value: Integer::ZERO,
+      data: vec![0x60, 0x00],
+    });
+    let sk = secret_key();
+    let sig = SimpleSigner::sign(&tx.sighash().unwrap(), MessageDigest::Prehashed, &sk).unwrap();
+    assert_eq!(
+      hex::encode(tx.encode_signed(&sig).unwrap()),
+      "f84e8001830186a080808260001ca073e7565651c2790fbb82913d71a32bc0505cdfe18f7d9eb2cbaf68b474d2cd4ba016812b790e3be12ec4e5c3c086fdb8d4c252c4461c7da580cd4f3b3013926031",
+    );
+  }
+
+  #[test]
+  fn test_eip2930() {
+    let mut key_3 = [0u8; 32];
+    key_3[31] = 3;
+    let mut key_7 = [0u8; 32];
+    key_7[31] = 7;
+    let tx = Transaction::Eip2930(Eip2930Transaction {
+      chain_id: 1,
+      nonce: 0,
+      gas_price: gwei(20),
+      gas_limit: 21000,
+      to: to(),
+      value: ether(1),
+      data: vec![],
+      access_list: vec![AccessListItem {
+        address: Address::from_hex("0xde0b295669a9fd93d5f28d9ec85e40f4cb697bae").unwrap(),
+        storage_keys: vec![key_3, key_7],
+      }],
+    });
+    check(
+      &tx,
+      "c0e016c37c9f7385d60d0bbba4d45d59cbc2f6b1eb9b6a1c62ba295d4c23493d",
+      "01f8ca01808504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080f85bf85994de0b295669a9fd93d5f28d9ec85e40f4cb697baef842a00000000000000000000000000000000000000000000000000000000000000003a0000000000000000000000000000000000000000000000000000000000000000780a09a50362d90ef91c4991e54a964debbe4b0e0424f6a27de208f76a9e66697378aa00c2851e66e660fc9dca19b49d9254265b75e3c5979aaf3e50d0061e83c69e291",
+    );
+  }
+
+  #[test]
+  fn test_eip1559() {
+    let tx = Transaction::Eip1559(Eip1559Transaction {
+      chain_id: 1,
+      nonce: 9,
+      max_priority_fee_per_gas: gwei(2),
+      max_fee_per_gas: gwei(100),
+      gas_limit: 21000,
+      to: to(),
+      value: ether(1),
+      data: vec![],
+      access_list: vec![],
+    });
+    assert_eq!(
+      hex::encode(tx.signing_payload().unwrap()),
+      "02f00109847735940085174876e800825208943535353535353535353535353535353535353535880de0b6b3a764000080c0",
+    );
+    check(
+      &tx,
+      "d6a0cf4cd9a39c3e71eb5638a92a3a9cfee2004137b97a49aff978c9e2094ede",
+      "02f8730109847735940085174876e800825208943535353535353535353535353535353535353535880de0b6b3a764000080c080a0262ae4928c1d9449769124b9ab0a22ae502cc0935e6e47ae44aa2c142a51960aa060a9a373f45bb1195d1a1693714d5a5434068c64d75ddb60d1890c776169590c",
+    );
+
+    // a high-S signature is normalized and its parity flipped
+    let sig: Signature = SimpleSigner::sign(&tx.sighash().unwrap(), MessageDigest::Prehashed, &secret_key()).unwrap();
+    let high_s: Signature = Signature::new_recoverable(&sig.r, &-sig.s, sig.recovery_id.unwrap() ^ 1);
+    assert_eq!(tx.encode_signed(&high_s).unwrap(), tx.encode_signed(&sig).unwrap());
+    assert!(tx.encode_signed(&Signature::new(&sig.r, &sig.s)).is_err());
+  }
+
+  #[test]
+  fn test_out_of_range_fields() {
+    let legacy = LegacyTransaction {
+      chain_id: Some(1),
+      nonce: 0,
+      gas_price: gwei(20),
+      gas_limit: 21000,
+      to: to(),
+      value: ether(1),
+      data: vec![],
+    };
+    let sig: Signature = SimpleSigner::sign(&[1; 32], MessageDigest::Prehashed, &secret_key()).unwrap();
+
+    // amounts out of uint256
+    for value in [Integer::from(-1), Integer::from(1) << 256] {
+      let tx = Transaction::Legacy(LegacyTransaction { value, ..legacy.clone() });
+      assert!(tx.sighash().is_err());
+      assert!(tx.encode_signed(&sig).is_err());
+    }
+
+    // v of EIP-155 overflows u64
+    let tx = Transaction::Legacy(LegacyTransaction { chain_id: Some(u64::MAX / 2), ..legacy.clone() });
+    assert!(tx.encode_signed(&sig).is_err());
+    let tx = Transaction::Legacy(LegacyTransaction { chain_id: Some((u64::MAX - 36) / 2), ..legacy });
+    assert!(tx.encode_signed(&sig).is_ok());
+  }
+}
@@ -0,0 +1,303 @@
+#![allow(dead_code)]
+
+// EIP-712 typed structured data hashing
+
+use std::collections::{BTreeMap, BTreeSet};
+use rug::{
+  integer::Order,
+  Integer,
+};
+use crate::protocols::ethereum::{
+  address::Address,
+  util::keccak256,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypedValue {
+  Uint(Integer),
+  Int(Integer),
+  Address(Address),
+  Bool(bool),
+  Bytes(Vec<u8>), // bytes and bytes1 to bytes32
+  String(String),
+  Array(Vec<TypedValue>),
+  Struct(BTreeMap<String, TypedValue>), // member name to value
+}
+
+// struct types, each being a list of (member name, member type)
+#[derive(Debug, Clone, Default)]
+pub struct TypedData {
+  types: BTreeMap<String, Vec<(String, String)>>,
+}
+
+// element type of an array type, e.g. Person of Person[] or Person[2]
+fn array_elem_type(ty: &str) -> Option<&str> {
+  if ty.ends_with(']') {
+    ty.rfind('[').map(|i| &ty[..i])
+  } else {
+    None
+  }
+}
+
+// length of a fixed-size array type, e.g. 2 of Person[2] and None of Person[]
+fn array_len(ty: &str) -> Result<Option<usize>, String> {
+  let len = &ty[ty.rfind('[').unwrap() + 1..ty.len() - 1];
+  if len.is_empty() {
+    return Ok(None);
+  }
+  len.parse::<usize>().map(Some).map_err(|_| format!("Invalid array length of {}", ty))
+}
+
+// bit length of uintN/intN and the byte length of bytesN
+fn type_size(ty: &str, prefix: &str) -> Option<u32> {
+  ty.strip_prefix(prefix).and_then(|x| x.parse::<u32>().ok())
+}
+
+impl TypedData {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // EIP712Domain needs to be added as well to compute the domain separator
+  pub fn add_type(&mut self, name: &str, members: &[(&str, &str)]) {
+    let members = members.iter().map(|(n, t)| (n.to_string(), t.to_string())).collect();
+    self.types.insert(name.to_string(), members);
+  }
+
+  fn members(&self, name: &str) -> Result<&Vec<(String, String)>, String> {
+    self.types.get(name).ok_or(format!("Unknown struct type {}", name))
+  }
+
+  // struct types that name references directly or indirectly, name included
+  fn collect_deps(&self, name: &str, deps: &mut BTreeSet<String>) -> Result<(), String> {
+    if deps.contains(name) {
+      return Ok(());
+    }
+    deps.insert(name.to_string());
+    for (_, ty) in self.members(name)? {
+      let mut ty = ty.as_str();
+      while let Some(elem) = array_elem_type(ty) {
+        ty = elem;
+      }
+      if self.types.contains_key(ty) {
+        self.collect_deps(ty, deps)?;
+      }
+    }
+    Ok(())
+  }
+
+  // name(type member,...) followed by the referenced struct types in alphabetical order
+  pub fn encode_type(&self, name: &str) -> Result<String, String> {
+    let mut deps = BTreeSet::new();
+    self.collect_deps(name, &mut deps)?;
+    deps.remove(name);
+
+    let mut s = String::new();
+    for ty in std::iter::once(name.to_string()).chain(deps) {
+      let members: Vec<String> = self.members(&ty)?.iter()
+        .map(|(n, t)| format!("{} {}", t, n))
+        .collect();
+      s.push_str(&format!("{}({})", ty, members.join(",")));
+    }
+    Ok(s)
+  }
+
+  pub fn type_hash(&self, name: &str) -> Result<[u8; 32], String> {
+    Ok(keccak256(self.encode_type(name)?.as_bytes()))
+  }
+
+  // keccak256(typeHash || encodeData(value))
+  pub fn hash_struct(&self, name: &str, value: &TypedValue) -> Result<[u8; 32], String> {
+    let fields = match value {
+      TypedValue::Struct(fields) => fields,
+      _ => return Err(format!("Value of {} should be a struct", name)),
+    };
+    let mut buf = self.type_hash(name)?.to_vec();
+    for (member, ty) in self.members(name)? {
+      let value = fields.get(member).ok_or(format!("Missing member {} of {}", member, name))?;
+      buf.extend(self.encode_value(ty, value)?);
+    }
+    Ok(keccak256(&buf))
+  }
+
+  // keccak256("\x19\x01" || hashStruct(domain) || hashStruct(message))
+  pub fn signing_hash(
+    &self,
+    domain: &TypedValue,
+    primary_type: &str,
+    message: &TypedValue,
+  ) -> Result<[u8; 32], String> {
+    let mut buf = vec![0x19, 0x01];
+    buf.extend(self.hash_struct("EIP712Domain", domain)?);
+    buf.extend(self.hash_struct(primary_type, message)?);
+    Ok(keccak256(&buf))
+  }
+
+  // 32-byte encoding of a member value
+  fn encode_value(&self, ty: &str, value: &TypedValue) -> Result<[u8; 32], String> {
+    let mismatch = || format!("Value {:?} does not match type {}", value, ty);
+
+    if let Some(elem_ty) = array_elem_type(ty) {
+      let elems = match value {
+        TypedValue::Array(elems) => elems,
+        _ => return Err(mismatch()),
+      };
+      if array_len(ty)?.is_some_and(|len| len != elems.len()) {
+        return Err(mismatch());
+      }
+      let mut buf = Vec::new();
+      for elem in elems {
+        buf.extend(self.encode_value(elem_ty, elem)?);
+      }
+      return Ok(keccak256(&buf));
+    }
+    if self.types.contains_key(ty) {
+      return self.hash_struct(ty, value);
+    }
+
+    let mut word = [0u8; 32];
+    match (ty, value) {
+      ("string", TypedValue::String(s)) => return Ok(keccak256(s.as_bytes())),
+      ("bytes", TypedValue::Bytes(b)) => return Ok(keccak256(b)),
+      ("bool", TypedValue::Bool(b)) => word[31] = *b as u8,
+      ("address", TypedValue::Address(addr)) => word[12..].copy_from_slice(&addr.0),
+      (_, TypedValue::Bytes(b)) if type_size(ty, "bytes").is_some() => {
+        let len = type_size(ty, "bytes").unwrap() as usize;
+        if len == 0 || len > 32 || b.len() != len {
+          return Err(mismatch());
+        }
+        word[..len].copy_from_slice(b);
+      },
+      (_, TypedValue::Uint(i)) if type_size(ty, "uint").is_some() => {
+        let bits = type_size(ty, "uint").unwrap();
+        if bits == 0 || bits > 256 || !bits.is_multiple_of(8) || *i < 0 || i.significant_bits() > bits {
+          return Err(mismatch());
+        }
+        let digits = i.to_digits::<u8>(Order::MsfBe);
+        word[32 - digits.len()..].copy_from_slice(&digits);
+      },
+      (_, TypedValue::Int(i)) if type_size(ty, "int").is_some() => {
+        let bits = type_size(ty, "int").unwrap();
+        if bits == 0 || bits > 256 || !bits.is_multiple_of(8) {
+          return Err(mismatch());
+        }
+        let bound = Integer::from(1) << (bits - 1);
+        if *i >= bound || *i < -bound {
+          return Err(mismatch());
+        }
+        // two's complement in 256 bits
+        let i = if *i < 0 { (Integer::from(1) << 256) + i } else { i.clone() };
+        let digits = i.to_digits::<u8>(Order::MsfBe);
+        word[32 - digits.len()..].copy_from_slice(&digits);
+      },
+      _ => return Err(mismatch()),
+    }
+    Ok(word)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    building_block::{
+      message_digest::MessageDigest,
+      secp256k1::scalar::Scalar,
+    },
+    protocols::{
+      ethereum::message::to_rsv,
+      gg18::simple_signer::SimpleSigner,
+    },
+  };
+
+  fn structure(members: &[(&str, TypedValue)]) -> TypedValue {
+    TypedValue::Struct(members.iter().map(|(n, v)| (n.to_string(), v.clone())).collect())
+  }
+
+  fn address(s: &str) -> TypedValue {
+    TypedValue::Address(Address::from_hex(s).unwrap())
+  }
+
+  fn string(s: &str) -> TypedValue {
+    TypedValue::String(s.to_string())
+  }
+
+  // example of EIP-712
+  #[test]
+  fn test_mail() {
+    let mut td = TypedData::new();
+    td.add_type("EIP712Domain", &[
+      ("name", "string"),
+      ("version", "string"),
+      ("chainId", "uint256"),
+      ("verifyingContract", "address"),
+    ]);
+    td.add_type("Person", &[("name", "string"), ("wallet", "address")]);
+    td.add_type("Mail", &[("from", "Person"), ("to", "Person"), ("contents", "string")]);
+
+    assert_eq!(
+      td.encode_type("Mail").unwrap(),
+      "Mail(Person from,Person to,string contents)Person(string name,address wallet)",
+    );
+
+    let domain = structure(&[
+      ("name", string("Ether Mail")),
+      ("version", string("1")),
+      ("chainId", TypedValue::Uint(Integer::from(1))),
+      ("verifyingContract", address("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC")),
+    ]);
+    let message = structure(&[
+      ("from", structure(&[
+        ("name", string("Cow")),
+        ("wallet", address("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826")),
+      ])),
+      ("to", structure(&[
+        ("name", string("Bob")),
+        ("wallet", address("0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB")),
+      ])),
+      ("contents", string("Hello, Bob!")),
+    ]);
+
+    let ds = td.hash_struct("EIP712Domain", &domain).unwrap();
+    assert_eq!(hex::encode(ds), "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f");
+    let hs = td.hash_struct("Mail", &message).unwrap();
+    assert_eq!(hex::encode(hs), "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+    let digest = td.signing_hash(&domain, "Mail", &message).unwrap();
+    assert_eq!(hex::encode(digest), "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+
+    // signed by keccak256("cow")
+    let sk = Scalar::from_bytes_checked(&keccak256(b"cow")).unwrap();
+    let sig = SimpleSigner::sign(&digest, MessageDigest::Prehashed, &sk).unwrap();
+    let rsv = to_rsv(&sig).unwrap();
+    assert_eq!(
+      hex::encode(rsv),
+      "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c",
+    );
+  }
+
+  #[test]
+  fn test_encode_value() {
+    let td = TypedData::new();
+    let word = td.encode_value("int8", &TypedValue::Int(Integer::from(-1))).unwrap();
+    assert_eq!(word, [0xff; 32]);
+    let word = td.encode_value("bytes2", &TypedValue::Bytes(vec![1, 2])).unwrap();
+    assert_eq!(&word[..3], &[1, 2, 0]);
+
+    assert!(td.encode_value("uint8", &TypedValue::Uint(Integer::from(256))).is_err());
+    assert!(td.encode_value("int8", &TypedValue::Int(Integer::from(128))).is_err());
+    assert!(td.encode_value("int0", &TypedValue::Int(Integer::from(0))).is_err());
+    assert!(td.encode_value("int264", &TypedValue::Int(Integer::from(0))).is_err());
+    assert!(td.encode_value("int7", &TypedValue::Int(Integer::from(0))).is_err());
+    assert!(td.encode_value("uint7", &TypedValue::Uint(Integer::from(0))).is_err());
+    assert!(td.encode_value("bytes2", &TypedValue::Bytes(vec![1])).is_err());
+    assert!(td.encode_value("uint256", &TypedValue::Bool(true)).is_err());
+    assert!(td.encode_value("Unknown", &TypedValue::Bool(true)).is_err());
+
+    // fixed-size arrays
+    let elems = |n: usize| TypedValue::Array(vec![TypedValue::Uint(Integer::from(1)); n]);
+    assert_eq!(td.encode_value("uint8[3]", &elems(3)).unwrap(), td.encode_value("uint8[]", &elems(3)).unwrap());
+    assert!(td.encode_value("uint8[3]", &elems(2)).is_err());
+    assert!(td.encode_value("uint8[3]", &elems(4)).is_err());
+    assert!(td.encode_value("uint8[x]", &elems(1)).is_err());
+  }
+}
@@ -0,0 +1,7 @@
+#![allow(dead_code)]
+
+use sha3::{Digest, Keccak256};
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+  Keccak256::digest(data).into()
+}
@@ -1,20 +1,39 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
-use crate::building_block::secp256k1::{
-  scalar::Scalar,
-  jacobian_point::JacobianPoint as Point,
+use crate::building_block::{
+  curve::{
+    Curve,
+    CurveScalar,
+  },
+  secp256k1::{
+    curve::Secp256k1,
+    secret_scalar::SecretScalar,
+  },
+};
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
 };
 use std::fmt;
 
-pub struct FeldmanVss {
-  coeffs: Vec<Scalar>, // polynomial coeffs from x^0 to x^degree
+pub struct FeldmanVss<C: Curve = Secp256k1> {
+  coeffs: Vec<SecretScalar<C::Scalar>>, // polynomial coeffs from x^0 to x^degree
 }
 
-impl FeldmanVss {
+impl<C: Curve> FeldmanVss<C> {
   // - # of shares > threshold
   // - degree = threshold - 1
-  pub fn new(secret: &Scalar, threshold: usize) -> Self {
+  pub fn new(secret: &C::Scalar, threshold: usize) -> Self {
+    Self::new_with_rng(secret, threshold, &mut OsRng)
+  }
+
+  pub fn new_with_rng(
+    secret: &C::Scalar,
+    threshold: usize,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Self {
     if threshold < 2 {
       panic!("Threshold must be at least 2");
     }
@@ -22,9 +41,9 @@ impl FeldmanVss {
     // generate random polynomial
     let degree = threshold - 1;
 
-    let mut coeffs = vec![secret.clone()];
+    let mut coeffs = vec![SecretScalar::new(*secret)];
     for _ in 1..=degree {
-      let c = Scalar::rand();
+      let c = SecretScalar::rand_with_rng(rng);
       coeffs.push(c);
     }
     Self {
@@ -32,127 +51,143 @@ impl FeldmanVss {
     }
   }
 
-  pub fn eval_P_at_i(&self, i: usize) -> Scalar {
-    let i = Scalar::from(i);
-    let mut x = i.clone();
-    let mut res = self.coeffs[0];
+  pub fn eval_P_at_i(&self, i: usize) -> C::Scalar {
+    let i = C::Scalar::from(i as u64);
+    let mut x = i;
+    let mut res = *self.coeffs[0].expose();
     
     for coeff in &self.coeffs[1..] {
-      res += coeff * x;
+      res += *coeff.expose() * x;
       x *= i;
     }
     res
   }
 
   // returns g^coeff from x^0 to x^degree
-  pub fn calc_coeff_hidings(&self) -> Vec<Point> {
+  pub fn calc_coeff_hidings(&self) -> Vec<C::Point> {
     self.coeffs.iter()
-      .map(|coeff| Point::from(coeff))
+      .map(|coeff| C::mul_base_secret(coeff.expose()))
       .collect::<Vec<_>>()
   }
 
   pub fn eval_P_at_i_with_coeff_hidings(
     i: usize,
-    coeff_hidings: &Vec<Point>,
-  ) -> Point {
-    let mut x = Scalar::from(i);
-    let i = Scalar::from(i);
-    let mut res = coeff_hidings[0].clone(); 
-
-    for coeff_hiding in &coeff_hidings[1..] {
-      res += coeff_hiding * x;
+    coeff_hidings: &Vec<C::Point>,
+  ) -> C::Point {
+    let mut x = C::Scalar::from(1u64);
+    let i = C::Scalar::from(i as u64);
+    let mut terms = vec![];
+
+    for coeff_hiding in coeff_hidings {
+      terms.push((*coeff_hiding, x));
       x *= i;
     }
-    res
+    C::multi_mul(&terms)
   }
 
   // k = threshold
   // lambda_i(x) = prod j=1->k, j!=i (x - x_j) / (x_i - x_j) 
   pub fn calc_lagrange_basis_polynomial(
-    xs: &Vec<&Scalar>,
+    xs: &Vec<&C::Scalar>,
+    i: usize,
+    target: &C::Scalar, // interpolation target x
+  ) -> C::Scalar {
+    let (num, deno) = Self::calc_lagrange_num_deno(xs, i, target);
+    num * deno.inv()
+  }
+
+  // numerator and denominator of lambda_i(x) so that
+  // the denominators can be inverted in a batch
+  fn calc_lagrange_num_deno(
+    xs: &[&C::Scalar],
     i: usize,
-    target: &Scalar, // interpolation target x
-  ) -> Scalar {
-    let mut prod = 1u8.into();
+    target: &C::Scalar,
+  ) -> (C::Scalar, C::Scalar) {
+    let mut num = C::Scalar::from(1u64);
+    let mut deno = C::Scalar::from(1u64);
 
     for (curr_i, x_j) in xs.iter().enumerate() {
       if curr_i == i {
         continue;
       }
-      let num = target + x_j.neg();
-      let deno = xs[i] + x_j.neg();
-      prod *= num * deno.inv();
+      num *= *target - **x_j;
+      deno *= *xs[i] - **x_j;
     }
-    prod
+    (num, deno)
   }
 
   // f(x) = sum i=1->k y_i * lambda_i(x)
   pub fn recover_secret(
     &self,
-    shares: Vec<(Scalar, Scalar)>,
-  ) -> Result<Scalar,String> {
+    shares: Vec<(C::Scalar, C::Scalar)>,
+  ) -> Result<C::Scalar,String> {
     if shares.len() < self.coeffs.len() {
       return Err(
         format!("{} or more shares are required", self.coeffs.len()));
     }
     let shares = &shares[..self.coeffs.len()];
-    let mut secret = Scalar::zero();
+    let mut secret = C::Scalar::zero();
 
     let xs = shares.iter().map(|(x, _)| x).collect::<Vec<_>>();
-    let target = Scalar::zero();
+    let target = C::Scalar::zero();
 
-    for (i, (_, y)) in shares.iter().enumerate() {
-      let lambda = Self::calc_lagrange_basis_polynomial(
-        &xs,
-        i,
-        &target,
-      );
-      secret += y * lambda
+    let (nums, denos): (Vec<C::Scalar>, Vec<C::Scalar>) = (0..shares.len())
+      .map(|i| Self::calc_lagrange_num_deno(&xs, i, &target))
+      .unzip();
+    let deno_invs = C::Scalar::batch_inv(&denos);
+
+    for ((_, y), (num, deno_inv)) in shares.iter().zip(nums.iter().zip(&deno_invs)) {
+      let lambda = *num * *deno_inv;
+      secret += *y * lambda
     }
     Ok(secret)
   }
 }
 
-impl fmt::Debug for FeldmanVss {
+// the coefficients are secret, so only the shape of the polynomial is shown
+impl<C: Curve> fmt::Debug for FeldmanVss<C> {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    let coeffs: Vec<(u64, usize)> =
-      self.coeffs.iter().rev().map(|x| (*x).into()).zip(0..).collect();
-
-    let mut s = String::new();
-    for (coeff, i) in coeffs.iter() {
-      if i > &0 {
-        s += "+";
-      }
-      let power = coeffs.len() - 1 - i;
-      let term = match power {
-        0 => "".to_string(),
-        1 => "x".to_string(),
-        _ => format!("x^{:?}", power)
-      };
-      s += &format!("{}{}", *coeff, term);
-    }
-    write!(f, "FeldmanVss({})", s)
+    write!(f, "FeldmanVss(degree={}, coeffs=<redacted>)", self.coeffs.len() - 1)
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::building_block::{
+    p256::curve::P256,
+    secp256k1::{
+      jacobian_point::JacobianPoint as Point,
+      scalar::Scalar,
+    },
+  };
 
   #[test]
   fn test_new() {
     let secret = Scalar::from(7u8);
     let threshold = 2;
-    let vss = FeldmanVss::new(&secret, threshold);
+    let vss: FeldmanVss = FeldmanVss::new(&secret, threshold);
     assert_eq!(vss.coeffs.len(), 2);
-    assert_eq!(vss.coeffs[0], secret);
+    assert_eq!(vss.coeffs[0].expose(), &secret);
+  }
+
+  #[test]
+  fn test_new_with_rng() {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    let secret = Scalar::from(7u8);
+    let vss1: FeldmanVss = FeldmanVss::new_with_rng(&secret, 3, &mut ChaCha20Rng::seed_from_u64(1));
+    let vss2: FeldmanVss = FeldmanVss::new_with_rng(&secret, 3, &mut ChaCha20Rng::seed_from_u64(1));
+    assert_eq!(vss1.calc_coeff_hidings(), vss2.calc_coeff_hidings());
+    assert_eq!(vss1.eval_P_at_i(5), vss2.eval_P_at_i(5));
   }
 
   #[test]
   fn test_secret_recovery() {
     for threshold in 2..=100 {
       let secret = Scalar::rand();
-      let vss = FeldmanVss::new(&secret, threshold);
+      let vss: FeldmanVss = FeldmanVss::new(&secret, threshold);
 
       let shares: Vec<(Scalar, Scalar)> = (1..=(threshold + 1))
         .map(|i| (Scalar::from(i as u8), vss.eval_P_at_i(i)))
@@ -162,13 +197,24 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_debug_is_redacted() {
+    let vss: FeldmanVss = FeldmanVss {
+      coeffs: vec![
+        SecretScalar::new(5u8.into()),
+        SecretScalar::new(3u8.into()),
+      ],
+    };
+    assert_eq!(format!("{:?}", vss), "FeldmanVss(degree=1, coeffs=<redacted>)");
+  }
+
   #[test]
   fn test_eval_at_i() {
-    let vss = FeldmanVss {
+    let vss: FeldmanVss = FeldmanVss {
       // P = 3x + 5
       coeffs: vec![
-        5u8.into(),
-        3u8.into(),
+        SecretScalar::new(5u8.into()),
+        SecretScalar::new(3u8.into()),
       ],
     };
     assert_eq!(vss.eval_P_at_i(1), 8u8.into());
@@ -181,7 +227,7 @@ mod tests {
     let secret = Scalar::rand();
     let num_shares = 100;
     let threshold = num_shares - 1;
-    let vss = FeldmanVss::new(&secret, threshold);
+    let vss: FeldmanVss = FeldmanVss::new(&secret, threshold);
 
     let coeff_hidings = vss.calc_coeff_hidings();
 
@@ -190,8 +236,29 @@ mod tests {
       println!("Verifying share {}", i);
       let P = Point::from(vss.eval_P_at_i(i));
       let P_recovered =
-        FeldmanVss::eval_P_at_i_with_coeff_hidings(i, &coeff_hidings);
+        FeldmanVss::<Secp256k1>::eval_P_at_i_with_coeff_hidings(i, &coeff_hidings);
       assert_eq!(P, P_recovered);
     }
   }
+
+  #[test]
+  fn test_p256() {
+    use crate::building_block::p256::scalar::Scalar;
+    use rand::rngs::OsRng;
+
+    let secret = Scalar::rand_with_rng(&mut OsRng);
+    let vss = FeldmanVss::<P256>::new(&secret, 3);
+
+    let coeff_hidings = vss.calc_coeff_hidings();
+    for i in 1..=4 {
+      assert_eq!(
+        P256::mul_base(&vss.eval_P_at_i(i)),
+        FeldmanVss::<P256>::eval_P_at_i_with_coeff_hidings(i, &coeff_hidings),
+      );
+    }
+    let shares = (2..=4)
+      .map(|i| (Scalar::from(i as u64), vss.eval_P_at_i(i)))
+      .collect();
+    assert_eq!(vss.recover_secret(shares).unwrap(), secret);
+  }
 }
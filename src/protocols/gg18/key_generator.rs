@@ -2,11 +2,12 @@
 #![allow(dead_code)]
 
 use rug::Integer;
-use crate::building_block::secp256k1::jacobian_point::JacobianPoint;
-use crate::building_block::secp256k1::{
-  jacobian_point::JacobianPoint as Point,
-  scalar::Scalar,
-  util::secp256k1_group_order,
+use crate::building_block::{
+  curve::Curve,
+  secp256k1::{
+    curve::Secp256k1,
+    secret_scalar::SecretScalar,
+  },
 };
 // use rug::Integer;
 use crate::protocols::gg18::{
@@ -22,26 +23,34 @@ use crate::protocols::gg18::{
     Paillier,
     PublicKey,
   },
-  pedersen_secp256k1::{
+  pedersen::{
     // CommitmentPair,
     Decommitment,
     PedersenCommitment,
   },
 };
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+  SeedableRng,
+};
+use rand_chacha::ChaCha20Rng;
 use std::sync::Arc;
 
-pub struct KeyGenerator {
+pub struct KeyGenerator<C: Curve = Secp256k1> {
   n: Integer,
   generator_id: u32,
   num_generators: usize,
   network: Arc<Network>,
-  pedersen: Arc<PedersenCommitment>,
+  pedersen: Arc<PedersenCommitment<C>>,
+  rng: ChaCha20Rng,
   // phase 1 result
-  u_i: Option<Scalar>,
-  dec_U_i: Option<Decommitment>,
+  u_i: Option<SecretScalar<C::Scalar>>,
+  dec_U_i: Option<Decommitment<C>>,
   // phase 2 result
-  pub x_i: Option<Scalar>, // shard private key
-  pub X_i: Option<Point>, // shard public key
+  pub x_i: Option<SecretScalar<C::Scalar>>, // shard private key
+  pub X_i: Option<C::Point>, // shard public key
   // phase 3 result
 }
 
@@ -54,20 +63,36 @@ const A_I_BCAST: BroadcastId = BroadcastId(5);
 const P_I_UNICAST: UnicastId = UnicastId(1);
 const P_I: ValueId = ValueId(1);
 
-impl KeyGenerator {
+impl<C: Curve> KeyGenerator<C> {
   pub fn new(
     n: &Integer,
     num_generators: usize,
     generator_id: u32,
     network: Arc<Network>,
-    pedersen: Arc<PedersenCommitment>,
+    pedersen: Arc<PedersenCommitment<C>>,
+  ) -> Self {
+    Self::new_with_rng(n, num_generators, generator_id, network, pedersen, &mut OsRng)
+  }
+
+  // all randomness of the generator is drawn from a ChaCha20 stream seeded by rng
+  pub fn new_with_rng(
+    n: &Integer,
+    num_generators: usize,
+    generator_id: u32,
+    network: Arc<Network>,
+    pedersen: Arc<PedersenCommitment<C>>,
+    rng: &mut (impl RngCore + CryptoRng),
   ) -> Self {
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+
     Self {
       n: n.clone(),
       num_generators,
       generator_id,
       network,
       pedersen,
+      rng: ChaCha20Rng::from_seed(seed),
       //
       u_i: None,
       dec_U_i: None,
@@ -78,11 +103,10 @@ impl KeyGenerator {
   }
 
   pub async fn run_phase_1(&mut self) {
-    let u_i = Scalar::from(self.generator_id + 1);
+    let u_i = SecretScalar::new(C::Scalar::from(self.generator_id as u64 + 1));
+    let comm_pair = self.pedersen.commit_with_rng(u_i.expose(), &mut self.rng);
     self.u_i = Some(u_i);
 
-    let comm_pair = self.pedersen.commit(&u_i);
-
     // broadcast Com(U_i)
     self.network.broadcast_with_index(
       &COM_U_I_BCAST,
@@ -93,10 +117,10 @@ impl KeyGenerator {
     self.dec_U_i = Some(comm_pair.decomm);
 
     // broadcast E_i the public key for Paillier’s cryptosystem
-    let ss_order = secp256k1_group_order();
-    let (p, q) = Paillier::gen_p_q(&ss_order);
-    let paillier = Paillier::new(
-      &p, &q, GCalcMethod::Random,
+    let ss_order = C::order();
+    let (p, q) = Paillier::gen_p_q_with_rng(&ss_order, &mut self.rng);
+    let paillier = Paillier::new_with_rng(
+      &p, &q, GCalcMethod::Random, &mut self.rng,
     );
     self.network.broadcast_with_index(
       &E_I_BCAST,
@@ -108,9 +132,11 @@ impl KeyGenerator {
   pub async fn run_phase_2(&mut self) -> Result<(), String> {
     // retrieve decommitment of Com(U_i)s
     // construct a polynomial of degree 1 using u_i as the constant term
-    let a_i = Scalar::rand();
-    let u_i = self.u_i.unwrap();
-    let p_i = Box::new(move |x: u32| { &u_i + a_i * Scalar::from(x) });
+    let a_i: SecretScalar<C::Scalar> = SecretScalar::rand_with_rng(&mut self.rng);
+    let p_i = {
+      let (u_i, a_i) = (self.u_i.clone().unwrap(), a_i.clone());
+      Box::new(move |x: u32| { *u_i.expose() + *a_i.expose() * C::Scalar::from(x as u64) })
+    };
 
     //// make sure all public keys are unique
     //for i in 0..E_is.len() {
@@ -153,7 +179,7 @@ impl KeyGenerator {
             self.generator_id,
             P_I,
           );
-          let eval_p_i = self.network.receive_unicast(&dest).await;
+          let eval_p_i: C::Scalar = self.network.receive_unicast(&dest).await?;
           eval_p_is.push(eval_p_i);
         }
       }
@@ -161,7 +187,7 @@ impl KeyGenerator {
     };
 
     // create A_i (hiding of a_i) and broadcast
-    let A_i = Point::get_base_point() * a_i;
+    let A_i = C::mul_base_secret(a_i.expose());
     self.network.broadcast_with_index(
       &A_I_BCAST,
       self.generator_id,
@@ -169,7 +195,8 @@ impl KeyGenerator {
     ).await;
 
     // retrieve broadcast A_is
-    let A_is = self.network.receive_idx_broadcasts(&A_I_BCAST).await;
+    let A_is: Vec<C::Point> =
+      self.network.receive_idx_broadcasts(&A_I_BCAST).await?;
     
     // broadcast Decommitment of Com(U_i)
     self.network.broadcast_with_index(
@@ -179,48 +206,47 @@ impl KeyGenerator {
     ).await;
 
     // retrieve decommits of Com(U_i)
-    let dec_U_is: Vec<Decommitment> =
-      self.network.receive_idx_broadcasts(&DEC_U_I_BCAST).await;
+    let dec_U_is: Vec<Decommitment<C>> =
+      self.network.receive_idx_broadcasts(&DEC_U_I_BCAST).await?;
 
     // reconstruct U_is
-    let g = JacobianPoint::get_base_point();
-    let U_is: Vec<JacobianPoint> = dec_U_is.iter().map(|x| g * x.secret).collect();
+    let U_is: Vec<C::Point> = dec_U_is.iter().map(|x| C::mul_base(&x.secret)).collect();
 
     // verify polynomials received from other generators are not compromised
     // i.e. p_i(gen_id) * G ==  U_i + A_i
+    let eval_point_scalar = C::Scalar::from(eval_point as u64);
     for (p_i, (A_i, U_i)) in eval_p_is.iter().zip(A_is.iter().zip(&U_is)) {
-      let lhs = g * p_i;
-      let rhs = U_i + A_i * Scalar::from(eval_point);
-      if lhs != rhs {
+      // p_i(gen_id) * G - A_i * gen_id. only p_i(gen_id) is secret, so
+      // the public A_i term is multiplied separately
+      let lhs = C::mul_base_secret(p_i) - *A_i * eval_point_scalar;
+      if lhs != *U_i {
         return Err(format!("---> {}: Phase 2: compromised polynomial found", self.generator_id));
       }
     }
  
     // calculate shard private key
-    let x_i = eval_p_is.iter().fold(
-      Scalar::zero(),
-      |acc, x| acc + x, 
-    );
-    self.x_i = Some(x_i);
+    let x_i: C::Scalar = eval_p_is.iter().sum();
+    self.x_i = Some(SecretScalar::new(x_i));
 
     // calculate shard public key
-    let PK = U_is.iter().fold(
-      JacobianPoint::point_at_infinity(),
-      |acc, x| acc + x, 
-    );
-    let X_i = A_is.iter().fold(
-      JacobianPoint::point_at_infinity(),
-      |acc, x| acc + x, 
-    );
-    self.X_i = Some(PK + X_i * Scalar::from(eval_point));
+    let PK: C::Point = U_is.iter().sum();
+    let X_i: C::Point = A_is.iter().sum();
+    self.X_i = Some(PK + X_i * C::Scalar::from(eval_point as u64));
 
     Ok(())
   }
 
-  pub async fn run_phase_3(&mut self) {
+  pub async fn run_phase_3(&mut self) -> Result<(), String> {
     // retrieve E_is here?
-    let _E_is: Vec<PublicKey> =
-      self.network.receive_idx_broadcasts(&E_I_BCAST).await;
+    let E_is: Vec<PublicKey> =
+      self.network.receive_idx_broadcasts(&E_I_BCAST).await?;
+
+    let ss_order = C::order();
+    for (i, E_i) in E_is.iter().enumerate() {
+      E_i.validate(&ss_order).map_err(|e|
+        format!("---> {}: Phase 3: invalid E_{}: {}", self.generator_id, i, e)
+      )?;
+    }
 
     // 1. prove that the generator knows the shard private key x_i through zkp
 
@@ -228,20 +254,27 @@ impl KeyGenerator {
 
     // N_i = p_i * q_i is the RSA modulus associated with E_i,
     // i.e. use zkp of knowing the p_i and q_i
+
+    Ok(())
   }
 
-  pub async fn generate_key(&mut self) -> Result<(Scalar, JacobianPoint), String> {
+  pub async fn generate_key(&mut self) -> Result<(SecretScalar<C::Scalar>, C::Point), String> {
     self.run_phase_1().await;
     self.run_phase_2().await?;
-    self.run_phase_3().await;
+    self.run_phase_3().await?;
 
-    Ok((self.x_i.unwrap(), self.X_i.unwrap()))
+    Ok((self.x_i.clone().unwrap(), self.X_i.unwrap()))
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::building_block::secp256k1::{
+    jacobian_point::JacobianPoint,
+    scalar::Scalar,
+    util::secp256k1_group_order,
+  };
   use tokio::spawn;
   use std::sync::Arc;
   use rug::ops::Pow;
@@ -255,7 +288,7 @@ mod tests {
 
     let mut generators = vec![];
     for generator_id in 0..3 {
-      let generator = KeyGenerator::new(
+      let generator: KeyGenerator = KeyGenerator::new(
         &n,
         num_generators,
         generator_id,
@@ -278,7 +311,7 @@ mod tests {
       .map(|res| res.unwrap().unwrap())
       .collect();
 
-    let x_is = res.iter().map(|(x_i, _)| x_i).collect::<Vec<_>>();
+    let x_is = res.iter().map(|(x_i, _)| x_i.expose()).collect::<Vec<_>>();
     let X_is = res.iter().map(|(_, X_i)| X_i).collect::<Vec<_>>();
 
     let G = JacobianPoint::get_base_point();
@@ -311,5 +344,38 @@ mod tests {
 
     Ok(())
   }
+
+  async fn gen_x_is_with_seed(seed: u64) -> Vec<Scalar> {
+    let network = Arc::new(Network::new(2));
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let pedersen = Arc::new(PedersenCommitment::new_with_rng(&mut rng));
+    let n = secp256k1_group_order().pow(8);
+
+    let mut handles = vec![];
+    for generator_id in 0..2 {
+      let mut generator: KeyGenerator = KeyGenerator::new_with_rng(
+        &n,
+        2,
+        generator_id,
+        Arc::clone(&network),
+        Arc::clone(&pedersen),
+        &mut rng,
+      );
+      handles.push(spawn(async move {
+        generator.generate_key().await.unwrap().0
+      }));
+    }
+    futures::future::join_all(handles).await
+      .into_iter()
+      .map(|res| *res.unwrap().expose())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn test_key_gen_with_seed() {
+    let x_is = gen_x_is_with_seed(1).await;
+    assert_eq!(x_is, gen_x_is_with_seed(1).await);
+    assert_ne!(x_is, gen_x_is_with_seed(2).await);
+  }
 }
 
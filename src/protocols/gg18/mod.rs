@@ -4,7 +4,7 @@ pub mod key_generator;
 pub mod mta;
 pub mod network;
 pub mod paillier;
-pub mod pedersen_secp256k1;
+pub mod pedersen;
 pub mod signer_id;
 pub mod signature;
 pub mod signer;
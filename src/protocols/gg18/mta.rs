@@ -7,12 +7,16 @@ use rug::{
   Complete,
 };
 use rug::Integer;
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
 use crate::{
   building_block::{
-    secp256k1::util::secp256k1_group_order,
     util::{
       gen_random_number,
-      get_32_byte_rng,
+      get_32_byte_rng_with_rng,
     },
   },
   protocols::gg18::paillier::{
@@ -32,19 +36,27 @@ pub struct Alice {
 
 impl Alice {
   pub fn new(
+    ss_order: &Integer, // order of group/field for secret sharing
     a: &Integer,
   ) -> Alice {
-    let ss_order = secp256k1_group_order();
-    let (pail_p, pail_q) = Paillier::gen_p_q(&ss_order);
-    let inst = Paillier::new(
+    Alice::new_with_rng(ss_order, a, &mut OsRng)
+  }
+
+  pub fn new_with_rng(
+    ss_order: &Integer, // order of group/field for secret sharing
+    a: &Integer,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Alice {
+    let (pail_p, pail_q) = Paillier::gen_p_q_with_rng(ss_order, rng);
+    let inst = Paillier::new_with_rng(
       &pail_p,
       &pail_q,
       GCalcMethod::Random,
+      rng,
     );
     let (pk, sk) = (inst.pk, inst.sk);
-    let mut rng = get_32_byte_rng();
 
-    let c_a = Paillier::encrypt(&mut rng, a, &pk);
+    let c_a = Paillier::encrypt_with_rng(rng, a, &pk);
 
     // TODO implement range proof of a < q^3
     let rp_a_lt_q3 = Integer::ZERO;
@@ -62,12 +74,13 @@ impl Alice {
     c_b: &Integer,
     _rp_b_lt_q3: &Integer,
     _rp_b_lt_q3_bp_le_q7: &Integer,
-  ) -> Option<Integer> {
+  ) -> Result<Integer, String> {
     // TODO check if the two range proofs are valid
+    Paillier::validate_ciphertext(c_b, &self.pk)?;
  
     // alice decrypts c_b to get alpha = ab + beta'
     let alpha = Paillier::decrypt(c_b, &self.sk, &self.pk) % &self.pk.n;
-    Some(alpha)
+    Ok(alpha)
   }
 }
 
@@ -80,15 +93,28 @@ pub struct Bob {
 
 impl Bob {
   pub fn new(
+    ss_order: &Integer, // order of group/field for secret sharing
+    c_a: &Integer,
+    pk: &PublicKey,
+    rp_a_lt_q3: &Integer,
+    b: &Integer,
+  ) -> Result<Bob, String> {
+    Bob::new_with_rng(ss_order, c_a, pk, rp_a_lt_q3, b, &mut OsRng)
+  }
+
+  pub fn new_with_rng(
     ss_order: &Integer, // order of group/field for secret sharing
     c_a: &Integer,
     pk: &PublicKey,
     _rp_a_lt_q3: &Integer,
     b: &Integer,
-  ) -> Bob {
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Result<Bob, String> {
     // TODO check if range proof of c_a is valid
+    pk.validate(ss_order)?;
+    Paillier::validate_ciphertext(c_a, pk)?;
     
-    let mut rng = get_32_byte_rng();
+    let mut rng = get_32_byte_rng_with_rng(rng);
 
     // sample beta' from z_{ss_order^5}
     let ss_order_bits_pow_5 = ss_order.pow(5).complete();
@@ -125,12 +151,12 @@ impl Bob {
     // b < {ss_order}^3 and beta' < {ss_order}^7
     let rp_b_lt_q3_bp_le_q7 = Integer::ZERO;
 
-    Bob {
+    Ok(Bob {
       c_b,
       beta,
       rp_b_lt_q3,
       rp_b_lt_q3_bp_le_q7,
-    }
+    })
   }
 }
 
@@ -166,24 +192,29 @@ impl MtA {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::building_block::{
+    secp256k1::util::secp256k1_group_order,
+    util::get_32_byte_rng,
+  };
 
   #[test]
   fn test_mta() {
-    let alice = Alice::new(&Integer::from(2));
+    let ss_order = secp256k1_group_order();
+    let alice = Alice::new(&ss_order, &Integer::from(2));
     let mta = MtA::new(&alice.pk.n);
     let mut rng = get_32_byte_rng();
 
     let a = gen_random_number(mta.q3.significant_bits(), &mut rng);
-    let alice = Alice::new(&a);
+    let alice = Alice::new(&ss_order, &a);
 
     let b = gen_random_number(mta.q3.significant_bits(), &mut rng);
     let bob = Bob::new(
-      &mta.q,
+      &ss_order,
       &alice.c_a,
       &alice.pk,
       &alice.rp_a_lt_q3,
       &b,
-    );
+    ).unwrap();
 
     let alpha = alice.calc_alpha(
       &bob.c_b,
@@ -196,15 +227,30 @@ mod tests {
 
     println!("a: {}", &a);
     println!("b: {}", &b);
-    let ab = (a * b) % &mta.q;
+    let ab = (a * b) % &ss_order;
     println!("ab: {}", ab);
 
     println!("alpha: {}", &alpha);
     println!("beta: {}", &bob.beta);
-    let alpha_plus_beta = (alpha + &bob.beta) % &mta.q;
+    let alpha_plus_beta = (alpha + &bob.beta) % &ss_order;
     println!("alpha_plus_beta: {}", alpha_plus_beta);
 
     assert_eq!(ab, alpha_plus_beta);
   } 
+
+  #[test]
+  fn test_malformed_ciphertexts() {
+    let ss_order = secp256k1_group_order();
+    let alice = Alice::new(&ss_order, &Integer::from(2));
+    let b = Integer::from(3);
+
+    let nn = (&alice.pk.n * &alice.pk.n).complete();
+    assert!(Bob::new(&ss_order, &nn, &alice.pk, &alice.rp_a_lt_q3, &b).is_err());
+    assert!(alice.calc_alpha(&alice.pk.n, &Integer::ZERO, &Integer::ZERO).is_err());
+
+    // n below q^8
+    let small_pk = PublicKey { n: Integer::from(35), g: Integer::from(2) };
+    assert!(Bob::new(&ss_order, &Integer::from(4), &small_pk, &alice.rp_a_lt_q3, &b).is_err());
+  }
 }
 
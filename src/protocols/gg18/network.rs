@@ -103,10 +103,12 @@ impl Network {
     ).await;
   }
 
+  // fails if any of the broadcasts doesn't decode to V. decoding of
+  // points and scalars rejects values off the curve or not less than n
   pub async fn receive_broadcasts<V>(
     &self,
     id: &BroadcastId,
-  ) -> Vec<V>
+  ) -> Result<Vec<V>, String>
   where
       V: DeserializeOwned,
   {
@@ -121,22 +123,30 @@ impl Network {
     };
     broadcasts
       .iter()
-      .map(|x| bincode::deserialize(x).unwrap())
+      .map(|x| bincode::deserialize(x).map_err(|e|
+        format!("Malformed broadcast {:?}: {}", id, e)
+      ))
       .collect()
   }
 
+  // also fails unless the indices are 0..num_parties without duplicates
   pub async fn receive_idx_broadcasts<V>(
     &self,
     id: &BroadcastId,
-  ) -> Vec<V>
+  ) -> Result<Vec<V>, String>
   where
       (u32, V): DeserializeOwned,
   {
-    let mut idx_ser_vec: Vec<(u32, V)> = self.receive_broadcasts(id).await;
+    let mut idx_ser_vec: Vec<(u32, V)> = self.receive_broadcasts(id).await?;
 
     // sort by index and return only values
     idx_ser_vec.sort_by_key(|(i, _)| *i);
-    idx_ser_vec.into_iter().map(|(_, v)| v).collect()
+    for (exp, (i, _)) in idx_ser_vec.iter().enumerate() {
+      if *i as usize != exp {
+        return Err(format!("Unexpected index {} in broadcast {:?}", i, id));
+      }
+    }
+    Ok(idx_ser_vec.into_iter().map(|(_, v)| v).collect())
   }
 
   pub async fn unicast<V>(
@@ -156,10 +166,11 @@ impl Network {
     self.data_added.notify_waiters();
   }
 
+  // fails if the unicast doesn't decode to V
   pub async fn receive_unicast<V>(
     &self,
     dest: &UnicastDest,
-  ) -> V
+  ) -> Result<V, String>
   where
       V: DeserializeOwned,
   {
@@ -169,8 +180,9 @@ impl Network {
       if unicasts.contains_key(&dest) { 
         let ser_value = unicasts.get(&dest).unwrap().clone();
         unicasts.remove(&dest).unwrap();
-        let value = bincode::deserialize(&ser_value).unwrap();
-        return value;
+        return bincode::deserialize(&ser_value).map_err(|e|
+          format!("Malformed unicast for {:?}: {}", dest, e)
+        );
       }
       drop(unicasts);
       self.data_added.notified().await;
@@ -178,3 +190,41 @@ impl Network {
   }
 }
 
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::secp256k1::{
+    jacobian_point::JacobianPoint,
+    scalar::Scalar,
+  };
+
+  #[tokio::test]
+  async fn test_malformed_values() {
+    let network = Network::new(2);
+    let id = BroadcastId(1);
+    let g = JacobianPoint::get_base_point();
+    network.broadcast_with_index(&id, 0u32, &g).await;
+
+    // replace G with x = 5, which is not on the curve
+    let mut off_curve = bincode::serialize(&(1u32, g)).unwrap();
+    off_curve[5..].fill(0);
+    off_curve[36] = 5;
+    network.broadcasts.lock().await.get_mut(&id).unwrap().push(off_curve);
+    assert!(network.receive_idx_broadcasts::<JacobianPoint>(&id).await.is_err());
+
+    // n is not a valid scalar
+    let dest = UnicastDest::new(UnicastId(1), 0, 1, ValueId(1));
+    network.unicast(&dest, &[0xffu8; 32]).await;
+    assert!(network.receive_unicast::<Scalar>(&dest).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_duplicate_index() {
+    let network = Network::new(2);
+    let id = BroadcastId(1);
+    network.broadcast_with_index(&id, 0u32, &Scalar::from(1u32)).await;
+    network.broadcast_with_index(&id, 0u32, &Scalar::from(2u32)).await;
+    assert!(network.receive_idx_broadcasts::<Scalar>(&id).await.is_err());
+  }
+}
@@ -4,18 +4,29 @@
 use rug::{
   Complete,
   Integer,
+  integer::{IsPrime, Order},
   ops::Pow,
   rand::MutRandState,
 };
-use crate::building_block::util::{
-  gen_random_number,
-  gen_random_prime,
-  get_32_byte_rng,
+use crate::building_block::{
+  serde_util,
+  util::{
+    gen_random_number,
+    gen_random_prime,
+    get_32_byte_rng_with_rng,
+  },
+};
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
 };
 use serde::{
   Deserialize,
   Serialize,
 };
+use std::fmt;
+use zeroize::Zeroize;
 
 pub enum GCalcMethod {
   Random,
@@ -25,12 +36,82 @@ pub enum GCalcMethod {
 pub struct Paillier();
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "PublicKeyRepr", into = "PublicKeyRepr")]
 pub struct PublicKey {
   pub n: Integer,
   pub g: Integer,
 }
 
-#[derive(Clone, Debug)]
+// n and g in big-endian, as hex in human-readable formats
+#[derive(Serialize, Deserialize)]
+struct PublicKeyRepr {
+  #[serde(serialize_with = "serde_util::serialize_bytes", deserialize_with = "serde_util::deserialize_bytes")]
+  n: Vec<u8>,
+  #[serde(serialize_with = "serde_util::serialize_bytes", deserialize_with = "serde_util::deserialize_bytes")]
+  g: Vec<u8>,
+}
+
+impl From<PublicKey> for PublicKeyRepr {
+  fn from(pk: PublicKey) -> Self {
+    PublicKeyRepr {
+      n: pk.n.to_digits(Order::MsfBe),
+      g: pk.g.to_digits(Order::MsfBe),
+    }
+  }
+}
+
+// n = pq is odd and g is in Z^*_n^2
+impl TryFrom<PublicKeyRepr> for PublicKey {
+  type Error = String;
+
+  fn try_from(repr: PublicKeyRepr) -> Result<Self, Self::Error> {
+    let n = Integer::from_digits(&repr.n, Order::MsfBe);
+    let g = Integer::from_digits(&repr.g, Order::MsfBe);
+    if n <= 1 || n.is_even() {
+      return Err("Paillier n should be an odd integer greater than 1".to_string());
+    }
+    let nn = (&n * &n).complete();
+    if g == 0 || g >= nn {
+      return Err("Paillier g should be in the range (0 < g < n^2)".to_string());
+    }
+    if g.clone().gcd(&n) != 1 {
+      return Err("Paillier g should be coprime to n".to_string());
+    }
+    Ok(PublicKey { n, g })
+  }
+}
+
+// primes below this are trial-divided out of received moduli
+const SMALL_PRIME_BOUND: u32 = 1 << 12;
+
+impl PublicKey {
+  // checks a received key on top of what deserialization does. n should
+  // exceed ss_order^8 as MtA requires and look like a product of two
+  // large primes, i.e. neither prime, a perfect square nor a multiple of
+  // a small prime. that n = pq can only be proven with a zk proof
+  pub fn validate(&self, ss_order: &Integer) -> Result<(), String> {
+    let n = &self.n;
+    if *n <= ss_order.clone().pow(8u32) {
+      return Err(format!("Paillier n should be greater than q^8; n is {} bits long", n.significant_bits()));
+    }
+    if n.is_perfect_square() {
+      return Err("Paillier n should not be a perfect square".to_string());
+    }
+    if n.is_probably_prime(30) != IsPrime::No {
+      return Err("Paillier n should not be a prime".to_string());
+    }
+    let mut p = Integer::from(2);
+    while p < SMALL_PRIME_BOUND {
+      if n.is_divisible(&p) {
+        return Err(format!("Paillier n should not have a small prime factor {}", p));
+      }
+      p.next_prime_mut();
+    }
+    Ok(())
+  }
+}
+
+#[derive(Clone)]
 pub struct SecretKey {
   // p: Integer,
   // q: Integer,
@@ -38,6 +119,29 @@ pub struct SecretKey {
   pub mu: Integer,
 }
 
+// overwrites the limbs in place. assigning zero would leave them in the allocation
+fn wipe(i: &mut Integer) {
+  unsafe {
+    let raw = i.as_raw_mut();
+    let limbs = std::slice::from_raw_parts_mut((*raw).d.as_ptr(), (*raw).alloc as usize);
+    limbs.zeroize();
+    (*raw).size = 0;
+  }
+}
+
+impl Drop for SecretKey {
+  fn drop(&mut self) {
+    wipe(&mut self.lambda);
+    wipe(&mut self.mu);
+  }
+}
+
+impl fmt::Debug for SecretKey {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "SecretKey(<redacted>)")
+  }
+}
+
 pub struct PaillierInstance {
   pub n: Integer,
   pub pk: PublicKey,
@@ -46,7 +150,14 @@ pub struct PaillierInstance {
 
 impl Paillier {
   pub fn gen_p_q(ss_order: &Integer) -> (Integer, Integer) {
-    let mut rng = get_32_byte_rng();
+    Self::gen_p_q_with_rng(ss_order, &mut OsRng)
+  }
+
+  pub fn gen_p_q_with_rng(
+    ss_order: &Integer,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> (Integer, Integer) {
+    let mut rng = get_32_byte_rng_with_rng(rng);
     let threshold = ss_order.clone().pow(8u32);
     let num_pq_bits = threshold.significant_bits() / 2 + 1;
 
@@ -110,11 +221,20 @@ impl Paillier {
     p: &Integer,
     q: &Integer,
     g_calc_method: GCalcMethod,
+  ) -> PaillierInstance {
+    Self::new_with_rng(p, q, g_calc_method, &mut OsRng)
+  }
+
+  pub fn new_with_rng(
+    p: &Integer,
+    q: &Integer,
+    g_calc_method: GCalcMethod,
+    rng: &mut (impl RngCore + CryptoRng),
   ) -> PaillierInstance {
     if p == q {
       panic!("p and q must be distinct primes");
     }
-    let mut rng = get_32_byte_rng();
+    let mut rng = get_32_byte_rng_with_rng(rng);
 
     let n = Integer::from(p * q);
     let nn = (&n * &n).complete();
@@ -146,6 +266,14 @@ impl Paillier {
     }
   }
 
+  pub fn encrypt_with_rng(
+    rng: &mut (impl RngCore + CryptoRng),
+    m: &Integer, // plaintext
+    pk: &PublicKey,
+  ) -> Integer {
+    Self::encrypt(&mut get_32_byte_rng_with_rng(rng), m, pk)
+  }
+
   // encrypted message is in multiplicative group modulo n^2
   pub fn encrypt(
     rng: &mut dyn MutRandState,
@@ -176,6 +304,21 @@ impl Paillier {
     (gm * rn) % nn
   }
 
+  // a received ciphertext should be in Z^*_n^2
+  pub fn validate_ciphertext(
+    c: &Integer, // cyphertext
+    pk: &PublicKey,
+  ) -> Result<(), String> {
+    let nn = (&pk.n * &pk.n).complete();
+    if *c <= 0 || c >= &nn {
+      return Err("Paillier ciphertext should be in the range (0 < c < n^2)".to_string());
+    }
+    if c.clone().gcd(&pk.n) != 1 {
+      return Err("Paillier ciphertext should be coprime to n".to_string());
+    }
+    Ok(())
+  }
+
   // decrypted message is in additive group modulo n
   pub fn decrypt(
     c: &Integer, // cyphertext
@@ -212,6 +355,7 @@ impl Paillier {
 #[cfg(test)]
 mod tests {
   use crate::building_block::secp256k1::util::secp256k1_group_order;
+  use crate::building_block::util::get_32_byte_rng;
 
 use super::*;
 
@@ -290,5 +434,78 @@ use super::*;
     let m3_prime = (m1 + m2) % &pk.n;
     assert_eq!(m3, m3_prime);
   }
-}
 
+  #[test]
+  fn test_encrypt_with_rng() {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    let pk = PublicKey {
+      n: Integer::from(15),
+      g: Integer::from(16),
+    };
+    let m = Integer::from(7);
+    let c1 = Paillier::encrypt_with_rng(&mut ChaCha20Rng::seed_from_u64(1), &m, &pk);
+    let c2 = Paillier::encrypt_with_rng(&mut ChaCha20Rng::seed_from_u64(1), &m, &pk);
+    assert_eq!(c1, c2);
+  }
+
+  #[test]
+  fn test_public_key_serde() {
+    let pk = PublicKey {
+      n: Integer::from(0x1234567u32),
+      g: Integer::from(0x1234568u32),
+    };
+    let json = serde_json::to_string(&pk).unwrap();
+    assert_eq!(json, r#"{"n":"01234567","g":"01234568"}"#);
+    let pk2: PublicKey = serde_json::from_str(&json).unwrap();
+    assert_eq!((pk2.n, pk2.g), (pk.n.clone(), pk.g.clone()));
+
+    let bin = bincode::serialize(&pk).unwrap();
+    let pk2: PublicKey = bincode::deserialize(&bin).unwrap();
+    assert_eq!((pk2.n, pk2.g), (pk.n, pk.g));
+
+    // even n, g out of range and g sharing a factor with n
+    assert!(serde_json::from_str::<PublicKey>(r#"{"n":"10","g":"03"}"#).is_err());
+    assert!(serde_json::from_str::<PublicKey>(r#"{"n":"0f","g":"00"}"#).is_err());
+    assert!(serde_json::from_str::<PublicKey>(r#"{"n":"0f","g":"ff"}"#).is_err());
+    assert!(serde_json::from_str::<PublicKey>(r#"{"n":"0f","g":"03"}"#).is_err());
+    assert!(serde_json::from_str::<PublicKey>(r#"{"n":"0f","g":"02"}"#).is_ok());
+  }
+
+  #[test]
+  fn test_validate() {
+    let ss_order = secp256k1_group_order();
+    let (p, q) = Paillier::gen_p_q(&ss_order);
+    let inst = Paillier::new(&p, &q, GCalcMethod::Random);
+    let pk = &inst.pk;
+    assert!(pk.validate(&ss_order).is_ok());
+
+    let with_n = |n: Integer| PublicKey { n, g: pk.g.clone() };
+    assert!(with_n(p.clone()).validate(&ss_order).is_err());
+    assert!(with_n((&p * &p).complete()).validate(&ss_order).is_err());
+    assert!(with_n((&p * 3u8).complete()).validate(&ss_order).is_err());
+    assert!(with_n(Integer::from(15)).validate(&ss_order).is_err());
+
+    let c = Paillier::encrypt(&mut get_32_byte_rng(), &Integer::from(7), pk);
+    assert!(Paillier::validate_ciphertext(&c, pk).is_ok());
+    assert!(Paillier::validate_ciphertext(&Integer::ZERO, pk).is_err());
+    assert!(Paillier::validate_ciphertext(&(&pk.n * &pk.n).complete(), pk).is_err());
+    assert!(Paillier::validate_ciphertext(&p, pk).is_err());
+  }
+
+  #[test]
+  fn test_secret_key_wipe() {
+    let mut i = Integer::from(u128::MAX);
+    let limbs = i.as_limbs().as_ptr();
+    wipe(&mut i);
+    assert_eq!(i, 0);
+    assert_eq!(unsafe { *limbs }, 0);
+
+    let sk = SecretKey {
+      lambda: Integer::from(12345),
+      mu: Integer::from(67890),
+    };
+    assert_eq!(format!("{:?}", sk), "SecretKey(<redacted>)");
+  }
+}
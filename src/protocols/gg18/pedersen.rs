@@ -0,0 +1,229 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+use crate::building_block::{
+  curve::{
+    Curve,
+    CurveScalar,
+  },
+  secp256k1::curve::Secp256k1,
+};
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+};
+use serde::{
+  Serialize,
+  Deserialize,
+};
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Decommitment<C: Curve = Secp256k1> {
+  pub secret: C::Scalar,
+  pub blinding_factor: C::Scalar,
+}
+
+impl<C: Curve> Decommitment<C> {
+  pub fn new(
+    secret: &C::Scalar,
+    blinding_factor: &C::Scalar,
+  ) -> Self {
+    Self {
+      secret: *secret,
+      blinding_factor: *blinding_factor,
+    }
+  }
+
+  pub fn serialize(&self) -> Vec<u8> {
+    bincode::serialize(self).unwrap()
+  }
+
+  pub fn deserialize(buf: &[u8]) -> Self {
+    bincode::deserialize(buf).unwrap()
+  }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CommitmentPair<C: Curve = Secp256k1> {
+  pub comm: C::Point,
+  pub decomm: Decommitment<C>,
+}
+
+impl<C: Curve> CommitmentPair<C> {
+  pub fn new(
+    comm: C::Point,
+    decomm: Decommitment<C>,
+  ) -> Self {
+    Self {
+      comm,
+      decomm,
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct PedersenCommitment<C: Curve = Secp256k1> {
+  pub g: C::Point,
+  pub h: C::Point,
+}
+
+// followed by the suite ID of the curve
+const H_DST_PREFIX: &str = "CRYPTO-PLAYGROUND-V01-CS01-PEDERSEN-H-with-";
+
+impl<C: Curve> PedersenCommitment<C> {
+  // whoever runs this knows log_g(h) and can open commitments to anything.
+  // use from_seed unless the setup is trusted
+  pub fn new() -> Self {
+    Self::new_with_rng(&mut OsRng)
+  }
+
+  pub fn new_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+    let g = C::generator();
+    let h = C::mul_base_secret(&C::Scalar::rand_with_rng(rng));
+
+    Self {
+      g,
+      h,
+    }
+  }
+
+  // derives h by hashing domain to the curve so that log_g(h) is unknown
+  // to everyone and any party can recompute h from domain
+  pub fn from_seed(domain: &[u8]) -> Self {
+    let g = C::generator();
+    let dst = format!("{}{}", H_DST_PREFIX, C::SUITE_ID);
+    let h = C::hash_to_curve(domain, dst.as_bytes()).unwrap();
+
+    Self {
+      g,
+      h,
+    }
+  }
+
+  pub fn is_derived_from(&self, domain: &[u8]) -> bool {
+    let exp = Self::from_seed(domain);
+    self.g == exp.g && self.h == exp.h
+  }
+
+  pub fn commit(
+    &self,
+    secret: &C::Scalar,
+  ) -> CommitmentPair<C> {
+    self.commit_with_rng(secret, &mut OsRng)
+  }
+
+  pub fn commit_with_rng(
+    &self,
+    secret: &C::Scalar,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> CommitmentPair<C> {
+    let blinding_factor = C::Scalar::rand_with_rng(rng);
+    // both scalars stay secret until the decommitment is opened
+    let comm = C::mul_base_secret(secret) + C::mul_secret(&self.h, &blinding_factor);
+    let decomm = Decommitment::new(secret, &blinding_factor);
+    CommitmentPair::new(comm, decomm)
+  }
+
+  pub fn verify(
+    &self,
+    comm: &C::Point,
+    decomm: &Decommitment<C>,
+  ) -> bool {
+    let comm_prime =
+      C::mul_base(&decomm.secret) + self.h * decomm.blinding_factor;
+    comm == &comm_prime
+  }
+
+  pub fn verify_vec(
+    &self,
+    comms: &[C::Point],
+    decomms: &[Decommitment<C>],
+  ) -> bool {
+    if comms.len() != decomms.len() {
+      panic!("Length of commx/decomms don't match. Check code.");
+    }
+    for i in 0..comms.len() {
+      if !self.verify(&comms[i], &decomms[i]) {
+        return false;
+      }
+    }
+    true    
+  }
+
+  pub fn aggr_secrets(decomms: &[Decommitment<C>]) -> C::Scalar {
+    decomms.iter().map(|decomm| decomm.secret).sum()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::{
+    p256::curve::P256,
+    secp256k1::scalar::Scalar,
+  };
+
+  #[test]
+  fn test_pedersen() {
+    let pedersen: PedersenCommitment = PedersenCommitment::new();
+
+    let secret = Scalar::rand();
+    let comm_pair = pedersen.commit(&secret);
+    println!("comm: {:?}, decomm: {:?}", comm_pair.comm, comm_pair.decomm);
+
+    assert!(pedersen.verify(&comm_pair.comm, &comm_pair.decomm));
+  } 
+
+  #[test]
+  fn test_from_seed() {
+    let pedersen: PedersenCommitment = PedersenCommitment::from_seed(b"session-1");
+    assert_eq!(pedersen.h, PedersenCommitment::<Secp256k1>::from_seed(b"session-1").h);
+    assert_ne!(pedersen.h, PedersenCommitment::<Secp256k1>::from_seed(b"session-2").h);
+    assert_ne!(pedersen.h, pedersen.g);
+
+    assert!(pedersen.is_derived_from(b"session-1"));
+    assert!(!pedersen.is_derived_from(b"session-2"));
+    assert!(!PedersenCommitment::<Secp256k1>::new().is_derived_from(b"session-1"));
+
+    let secret = Scalar::rand();
+    let comm_pair = pedersen.commit(&secret);
+    assert!(pedersen.verify(&comm_pair.comm, &comm_pair.decomm));
+  }
+
+  #[test]
+  fn test_decommitment_serde() {
+    let decomm: Decommitment = Decommitment::new(&Scalar::from(1u8), &Scalar::from(2u8));
+    let json = serde_json::to_string(&decomm).unwrap();
+    assert_eq!(
+      json,
+      format!(r#"{{"secret":"{:x}","blinding_factor":"{:x}"}}"#, decomm.secret, decomm.blinding_factor),
+    );
+    let decomm2: Decommitment = serde_json::from_str(&json).unwrap();
+    assert_eq!(decomm2.secret, decomm.secret);
+    assert_eq!(decomm2.blinding_factor, decomm.blinding_factor);
+
+    let bin = decomm.serialize();
+    assert_eq!(bin.len(), 64);
+    assert_eq!(Decommitment::<Secp256k1>::deserialize(&bin).blinding_factor, decomm.blinding_factor);
+  }
+
+  #[test]
+  fn test_p256() {
+    use crate::building_block::p256::scalar::Scalar;
+
+    let pedersen = PedersenCommitment::<P256>::from_seed(b"session-1");
+    assert!(pedersen.is_derived_from(b"session-1"));
+    assert_ne!(pedersen.h, pedersen.g);
+    assert_ne!(pedersen.h, PedersenCommitment::<P256>::from_seed(b"session-2").h);
+
+    let secret = Scalar::rand_with_rng(&mut OsRng);
+    let mut comm_pair = pedersen.commit(&secret);
+    assert!(pedersen.verify(&comm_pair.comm, &comm_pair.decomm));
+
+    comm_pair.decomm.secret += Scalar::from(1u64);
+    assert!(!pedersen.verify(&comm_pair.comm, &comm_pair.decomm));
+  }
+}
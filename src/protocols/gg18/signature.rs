@@ -2,42 +2,184 @@
 #![allow(non_snake_case)]
 
 use std::fmt;
-use crate::building_block::secp256k1::{
-  jacobian_point::JacobianPoint,
-  scalar::Scalar,
+use crate::building_block::{
+  curve::{
+    Curve,
+    CurvePoint,
+    CurveScalar,
+  },
+  message_digest::MessageDigest,
+  secp256k1::curve::Secp256k1,
 };
 
 #[derive(Clone)]
-pub struct Signature {
-  pub r: Scalar,
-  pub s: Scalar,
+pub struct Signature<C: Curve = Secp256k1> {
+  pub r: C::Scalar,
+  pub s: C::Scalar,
+  // recovery id of R. None if it was not recorded when the signature was created
+  pub recovery_id: Option<u8>,
 }
 
-impl Signature {
-  pub fn new(r: &Scalar, s: &Scalar) -> Self {
+impl<C: Curve> Signature<C> {
+  pub fn new(r: &C::Scalar, s: &C::Scalar) -> Self {
     Self {
-      r: r.clone(),
-      s: s.clone(),
+      r: *r,
+      s: *s,
+      recovery_id: None,
+    }
+  }
+
+  pub fn new_recoverable(r: &C::Scalar, s: &C::Scalar, recovery_id: u8) -> Self {
+    Self {
+      r: *r,
+      s: *s,
+      recovery_id: Some(recovery_id),
     }
   }
 
   pub fn verify(
     &self,
-    pk: &JacobianPoint,
-    M: &Scalar,
-    hasher: impl Fn(&Scalar) -> Scalar,
+    pk: &C::Point,
+    msg: &[u8],
+    digest: MessageDigest,
   ) -> bool {
-    let m = hasher(M);
-    let u1 = &self.s.inv() * m;
-    let u2 = &self.s.inv() * &self.r;
+    if self.r.is_zero() || self.s.is_zero() {
+      return false;
+    }
+    let m: C::Scalar = digest.to_scalar(msg);
+    let s_inv = self.s.inv();
+    let u1 = s_inv * m;
+    let u2 = s_inv * self.r;
 
-    let g = JacobianPoint::get_base_point();
-    let R = g * u1 + pk * u2;
-    let r: Scalar = R.to_affine().x().into();
+    let R = C::multi_mul(&[(C::generator(), u1), (*pk, u2)]);
+    let r = C::x_to_scalar(&R);
 
     self.r == r
   }
 
+  // public key that creates this signature for msg, i.e. r^-1 * (s * R - m * G)
+  pub fn recover(
+    &self,
+    msg: &[u8],
+    digest: MessageDigest,
+  ) -> Result<C::Point, String> {
+    let recovery_id = self.recovery_id.ok_or("Signature has no recovery id")?;
+    if self.r.is_zero() || self.s.is_zero() {
+      return Err("r and s should not be zero".to_string());
+    }
+    let R = C::recover_R(&self.r, recovery_id)?;
+    let m: C::Scalar = digest.to_scalar(msg);
+    let r_inv = self.r.inv();
+
+    let pk = C::multi_mul(&[(R, self.s * r_inv), (C::generator(), -(m * r_inv))]);
+    if pk.is_identity() {
+      return Err("Recovered public key is the point at infinity".to_string());
+    }
+    Ok(pk)
+  }
+
+  // s is at most n/2 as BIP-146 requires
+  pub fn is_low_s(&self) -> bool {
+    self.s.to_integer() <= C::order() >> 1
+  }
+
+  // replaces high s with n - s. negating s amounts to negating R,
+  // so the parity bit of the recovery id is flipped as well
+  pub fn normalize_s(&mut self) {
+    if !self.is_low_s() {
+      self.s = -self.s;
+      if let Some(recovery_id) = self.recovery_id.as_mut() {
+        *recovery_id ^= 1;
+      }
+    }
+  }
+
+  // r || s
+  pub fn to_bytes(&self) -> [u8; 64] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&self.r.to_bytes());
+    buf[32..].copy_from_slice(&self.s.to_bytes());
+    buf
+  }
+
+  // rejects r and s that are zero or not less than n
+  pub fn from_bytes(buf: &[u8; 64]) -> Result<Self, String> {
+    let r = C::Scalar::from_bytes_checked(buf[..32].try_into().unwrap())?;
+    let s = C::Scalar::from_bytes_checked(buf[32..].try_into().unwrap())?;
+    if r.is_zero() || s.is_zero() {
+      return Err("r and s should not be zero".to_string());
+    }
+    Ok(Self::new(&r, &s))
+  }
+
+  // r || s || recovery id
+  pub fn to_compact_recoverable(&self) -> Result<[u8; 65], String> {
+    let recovery_id = self.recovery_id.ok_or("Signature has no recovery id")?;
+    let mut buf = [0u8; 65];
+    buf[..64].copy_from_slice(&self.to_bytes());
+    buf[64] = recovery_id;
+    Ok(buf)
+  }
+
+  pub fn from_compact_recoverable(buf: &[u8; 65]) -> Result<Self, String> {
+    let sig = Self::from_bytes(buf[..64].try_into().unwrap())?;
+    if buf[64] > 3 {
+      return Err(format!("Invalid recovery id {}", buf[64]));
+    }
+    Ok(Self::new_recoverable(&sig.r, &sig.s, buf[64]))
+  }
+
+  // parses strict DER of BIP-66. rejects long form lengths, negative and
+  // zero-padded integers, trailing bytes and r and s not in [1, n-1]
+  pub fn from_der(der: &[u8]) -> Result<Self, String> {
+    if der.len() < 8 || der.len() > 72 {
+      return Err(format!("Invalid DER signature length {}", der.len()));
+    }
+    if der[0] != 0x30 {
+      return Err("DER signature should start with a sequence tag".to_string());
+    }
+    if der[1] as usize != der.len() - 2 {
+      return Err("DER sequence length does not match the signature length".to_string());
+    }
+    let (r, rest) = Self::parse_der_integer(&der[2..])?;
+    let (s, rest) = Self::parse_der_integer(rest)?;
+    if !rest.is_empty() {
+      return Err("Trailing bytes after DER signature".to_string());
+    }
+    Ok(Self::new(&r, &s))
+  }
+
+  // parses 0x02 || length || big-endian integer and returns the rest of buf
+  fn parse_der_integer(buf: &[u8]) -> Result<(C::Scalar, &[u8]), String> {
+    if buf.len() < 2 || buf[0] != 0x02 {
+      return Err("DER integer tag expected".to_string());
+    }
+    let len = buf[1] as usize;
+    if len == 0 || len > 33 || buf.len() < 2 + len {
+      return Err(format!("Invalid DER integer length {}", len));
+    }
+    let int = &buf[2..2 + len];
+    if int[0] & 0x80 != 0 {
+      return Err("DER integer should not be negative".to_string());
+    }
+    if len > 1 && int[0] == 0 && int[1] & 0x80 == 0 {
+      return Err("DER integer should not have excess leading zeroes".to_string());
+    }
+
+    // drop the sign byte
+    let int = if len > 1 && int[0] == 0 { &int[1..] } else { int };
+    if int.len() > 32 {
+      return Err("DER integer is too large".to_string());
+    }
+    let mut bytes = [0u8; 32];
+    bytes[32 - int.len()..].copy_from_slice(int);
+    let x = C::Scalar::from_bytes_checked(&bytes)?;
+    if x.is_zero() {
+      return Err("DER integer should not be zero".to_string());
+    }
+    Ok((x, &buf[2 + len..]))
+  }
+
   pub fn to_der(&self) -> Vec<u8> {
     fn to_der(ser_scalar: &Vec<u8>) -> Vec<u8> {
       let mut vec = ser_scalar.to_vec();
@@ -59,12 +201,13 @@ impl Signature {
       res
     }
 
-    let ser_r = self.r.secp256k1_serialize();
-    let ser_s = self.s.secp256k1_serialize();
+    let ser_r = self.r.to_bytes().to_vec();
+    let ser_s = self.s.to_bytes().to_vec();
 
     let der_r = to_der(&ser_r);
     let der_s = to_der(&ser_s);
 
+    // the sequence is at most 70 bytes long, so the lengths always fit in the short form
     let mut der = Vec::new();
     der.push(0x30); // 0x30 = Sequence
     der.push((der_r.len() + der_s.len()) as u8);
@@ -74,11 +217,87 @@ impl Signature {
   }
 }
 
-impl fmt::Debug for Signature {
+impl<C: Curve> fmt::Debug for Signature<C> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let to_hex = |x: &C::Scalar| format!("0x{}", hex::encode(x.to_bytes()));
     f.debug_struct("Signature")
-      .field("r", &self.r.to_hex())
-      .field("s", &self.s.to_hex())
+      .field("r", &to_hex(&self.r))
+      .field("s", &to_hex(&self.s))
+      .field("recovery_id", &self.recovery_id)
       .finish()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::building_block::secp256k1::scalar::Scalar;
+
+  #[test]
+  fn test_der() {
+    let sig: Signature = Signature::new(&Scalar::rand(), &Scalar::rand());
+    let der = sig.to_der();
+    let sig2: Signature = Signature::from_der(&der).unwrap();
+    assert_eq!((sig2.r, sig2.s), (sig.r, sig.s));
+
+    // small r and s with the high bit set
+    let sig: Signature = Signature::new(&Scalar::from(1u32), &Scalar::from(0x80u32));
+    let der = sig.to_der();
+    assert_eq!(der, vec![0x30, 0x07, 0x02, 0x01, 0x01, 0x02, 0x02, 0x00, 0x80]);
+    let sig2: Signature = Signature::from_der(&der).unwrap();
+    assert_eq!((sig2.r, sig2.s), (sig.r, sig.s));
+
+    let reject = |f: &dyn Fn(&mut Vec<u8>)| {
+      let mut der = der.clone();
+      f(&mut der);
+      assert!(Signature::<Secp256k1>::from_der(&der).is_err());
+    };
+    reject(&|der| der[0] = 0x31); // not a sequence
+    reject(&|der| der[1] += 1); // sequence length mismatch
+    reject(&|der| { der.push(0); der[1] += 1; }); // trailing byte
+    reject(&|der| der[2] = 0x03); // not an integer
+    reject(&|der| der[4] = 0x81); // negative r
+    reject(&|der| der[8] = 0x7f); // unnecessary sign byte of s
+    reject(&|der| { der.insert(4, 0); der[3] += 1; der[1] += 1; }); // zero-padded r
+    reject(&|der| { der[3] = 0x81; der.insert(4, 2); der[1] += 1; }); // long form length
+  }
+
+  #[test]
+  fn test_low_s() {
+    let half_n = Scalar::from_integer(&(Secp256k1::order() >> 1));
+    let sig: Signature = Signature::new_recoverable(&Scalar::from(1u32), &half_n, 0);
+    assert!(sig.is_low_s());
+
+    // n - (n - 1) / 2 = (n - 1) / 2 + 1
+    let mut sig: Signature = Signature::new_recoverable(&Scalar::from(1u32), &-half_n, 0);
+    assert!(!sig.is_low_s());
+    sig.normalize_s();
+    assert!(sig.is_low_s());
+    assert_eq!(sig.s, half_n);
+    assert_eq!(sig.recovery_id, Some(1));
+  }
+
+  #[test]
+  fn test_compact() {
+    let sig: Signature = Signature::new(&Scalar::rand(), &Scalar::rand());
+    let sig2: Signature = Signature::from_bytes(&sig.to_bytes()).unwrap();
+    assert_eq!((sig2.r, sig2.s), (sig.r, sig.s));
+    assert!(sig.to_compact_recoverable().is_err());
+
+    let mut buf = sig.to_bytes();
+    buf[32..].fill(0);
+    assert!(Signature::<Secp256k1>::from_bytes(&buf).is_err());
+    buf[32..].fill(0xff);
+    assert!(Signature::<Secp256k1>::from_bytes(&buf).is_err());
+  }
+
+  #[test]
+  fn test_verify_zero_r_or_s() {
+    let pk = Secp256k1::mul_base(&Scalar::rand());
+    let zero = Scalar::from(0u32);
+    for (r, s) in [(zero, zero), (zero, Scalar::rand()), (Scalar::rand(), zero)] {
+      let sig: Signature = Signature::new(&r, &s);
+      assert!(!sig.verify(&pk, b"hello", MessageDigest::Sha256));
+    }
+  }
+}
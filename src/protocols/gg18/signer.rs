@@ -3,10 +3,16 @@
 #![allow(dead_code)]
 
 use crate::{
-  building_block::secp256k1::{
-    jacobian_point::JacobianPoint,
-    scalar::Scalar,
-    util::secp256k1_group_order,
+  building_block::{
+    curve::{
+      Curve,
+      CurveScalar,
+    },
+    message_digest::MessageDigest,
+    secp256k1::{
+      curve::Secp256k1,
+      secret_scalar::SecretScalar,
+    },
   },
   protocols::gg18::{
     mta::{
@@ -14,7 +20,7 @@ use crate::{
       Bob,
     },
     paillier::PublicKey,
-    pedersen_secp256k1::{
+    pedersen::{
       Decommitment,
       PedersenCommitment
     },
@@ -30,34 +36,43 @@ use crate::{
   },
 };
 use std::sync::Arc;
+use rand::{
+  rngs::OsRng,
+  CryptoRng,
+  RngCore,
+  SeedableRng,
+};
+use rand_chacha::ChaCha20Rng;
 use rug::Integer;
 
-pub struct Signer {
+pub struct Signer<C: Curve = Secp256k1> {
   signer_id: SignerId,
   network: Arc<Network>,
   ss_group_order: Integer,
-  pedersen: PedersenCommitment,
-  M: Scalar,
-  hasher: Box<dyn Fn(&Scalar) -> Scalar + Send + Sync>,
-  omega_i: Scalar,
+  pedersen: PedersenCommitment<C>,
+  m: C::Scalar, // digest of the message to sign
+  omega_i: SecretScalar<C::Scalar>,
+  rng: ChaCha20Rng,
+  low_s: bool,
 
   // phase 1 result
-  k_i: Option<Scalar>,
-  gamma_i: Option<Scalar>,
-  dec_Gamma_i: Option<Decommitment>,
+  k_i: Option<SecretScalar<C::Scalar>>,
+  gamma_i: Option<SecretScalar<C::Scalar>>,
+  dec_Gamma_i: Option<Decommitment<C>>,
   
   // phase 2 result
-  delta_i: Option<Scalar>,
-  sigma_i: Option<Scalar>,
+  delta_i: Option<C::Scalar>,
+  sigma_i: Option<SecretScalar<C::Scalar>>,
 
   // phase 3 result
-  delta: Option<Scalar>,
+  delta: Option<C::Scalar>,
  
   // phase 4 result
-  r: Option<Scalar>,
+  r: Option<C::Scalar>,
+  recovery_id: Option<u8>,
 
   // phase 5 result
-  s: Option<Scalar>,
+  s: Option<C::Scalar>,
 }
 
 const UNICAST_TO_SIGNER_A: UnicastId = UnicastId(1);
@@ -80,23 +95,40 @@ const BETA: ValueId = ValueId(5);
 const RP_B_LT_Q3: ValueId = ValueId(6);
 const RP_B_LT_Q3_BP_LE_Q7: ValueId = ValueId(7);
 
-impl Signer {
+impl<C: Curve> Signer<C> {
   pub fn new(
     signer_id: SignerId,
     network: Arc<Network>,
-    pedersen: PedersenCommitment,
-    M: &Scalar,
-    hasher: Box<dyn Fn(&Scalar) -> Scalar + Send + Sync>,
-    omega_i: &Scalar,
+    pedersen: PedersenCommitment<C>,
+    msg: &[u8],
+    digest: MessageDigest,
+    omega_i: &C::Scalar,
   ) -> Self {
+    Self::new_with_rng(signer_id, network, pedersen, msg, digest, omega_i, &mut OsRng)
+  }
+
+  // all randomness of the signer is drawn from a ChaCha20 stream seeded by rng
+  pub fn new_with_rng(
+    signer_id: SignerId,
+    network: Arc<Network>,
+    pedersen: PedersenCommitment<C>,
+    msg: &[u8],
+    digest: MessageDigest,
+    omega_i: &C::Scalar,
+    rng: &mut (impl RngCore + CryptoRng),
+  ) -> Self {
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+
     Self {
       signer_id,
       network,
-      ss_group_order: secp256k1_group_order(),
+      ss_group_order: C::order(),
       pedersen,
-      M: M.clone(),
-      hasher,
-      omega_i: omega_i.clone(),
+      m: digest.to_scalar(msg),
+      omega_i: SecretScalar::new(*omega_i),
+      rng: ChaCha20Rng::from_seed(seed),
+      low_s: true,
       //
       k_i: None,
       dec_Gamma_i: None,
@@ -105,16 +137,26 @@ impl Signer {
       gamma_i: None,
       delta: None,
       r: None,
+      recovery_id: None,
       s: None,
     }
   }
 
+  // signatures are normalized to low-S (BIP-146) unless disabled
+  pub fn set_low_s(&mut self, low_s: bool) {
+    self.low_s = low_s;
+  }
+
   pub async fn perfrom_mta_as_alice(
     &mut self,
     alice_id: &SignerId,
-    secret: &Scalar,
-  ) -> Scalar {
-    let alice = Alice::new(&secret.into());
+    secret: &C::Scalar,
+  ) -> Result<C::Scalar, String> {
+    let alice = Alice::new_with_rng(
+      &self.ss_group_order,
+      &secret.to_integer(),
+      &mut self.rng,
+    );
  
     let bob_id = &alice_id.the_other();
 
@@ -147,35 +189,38 @@ impl Signer {
       value_id,
     );
     let c_b: Integer = 
-      self.network.receive_unicast( &to_alice(C_B)).await;
+      self.network.receive_unicast( &to_alice(C_B)).await?;
 
     let beta: Integer = 
-      self.network.receive_unicast( &to_alice(BETA)).await;
+      self.network.receive_unicast( &to_alice(BETA)).await?;
+    if beta < 0 || beta >= self.ss_group_order {
+      return Err("beta should be in the range (0 <= beta < q)".to_string());
+    }
 
     let rp_b_lt_q3: Integer = self.network.receive_unicast(
       &to_alice(RP_B_LT_Q3),
-    ).await;
+    ).await?;
 
     let rp_b_lt_q3_bp_le_q7: Integer = self.network.receive_unicast(
       &to_alice(RP_B_LT_Q3_BP_LE_Q7),
-    ).await;
+    ).await?;
   
     // Calculate Alpha
     let alpha = alice.calc_alpha(
       &c_b,
       &rp_b_lt_q3,
       &rp_b_lt_q3_bp_le_q7,
-    ).unwrap();
+    )?;
 
     // Calculate multiplicative share
-    ((alpha + beta) % &self.ss_group_order).into()
+    Ok(C::Scalar::from_integer(&(alpha + beta)))
   }
 
   pub async fn perfrom_MtA_as_Bob(
     &mut self,
     bob_id: &SignerId,
-    secret: &Scalar,
-  ) {
+    secret: &C::Scalar,
+  ) -> Result<(), String> {
     let alice_id = &bob_id.the_other();
 
     // Receive c_a, pk, and range proof from Alice
@@ -187,24 +232,25 @@ impl Signer {
     );
     let c_a: Integer = self.network.receive_unicast(
       &to_bob(C_A),
-    ).await;
+    ).await?;
 
     let pk: PublicKey = self.network.receive_unicast(
       &to_bob(PK),
-    ).await;
+    ).await?;
 
     let rp_a_lt_q3: Integer = self.network.receive_unicast(
       &to_bob(RP_A_LT_Q3),
-    ).await;
+    ).await?;
 
     // Calculate C_B, beta, and range proofs
-    let bob = Bob::new(
-      &secp256k1_group_order(),
+    let bob = Bob::new_with_rng(
+      &self.ss_group_order,
       &c_a,
       &pk,
       &rp_a_lt_q3,
-      &secret.into(),
-    );
+      &secret.to_integer(),
+      &mut self.rng,
+    )?;
 
     // Send C_b, beta, and range proofs to Alice
     let to_alice = |value_id| UnicastDest::new(
@@ -232,17 +278,19 @@ impl Signer {
       &to_alice(RP_B_LT_Q3_BP_LE_Q7),
       &bob.rp_b_lt_q3_bp_le_q7,
     ).await;
+
+    Ok(())
   }
 
   pub async fn run_phase_1(&mut self) {
     // select k_i and gamma_i in Z_q and broadcasts C_i
-    let k_i = Scalar::rand();
-    let gamma_i = Scalar::rand();
-    self.k_i = Some(k_i);
-    self.gamma_i = Some(gamma_i);
+    let k_i: SecretScalar<C::Scalar> = SecretScalar::rand_with_rng(&mut self.rng);
+    let gamma_i: SecretScalar<C::Scalar> = SecretScalar::rand_with_rng(&mut self.rng);
 
     // calculate Com(Gamma_i = gamma_i * G)
-    let comm_pair = self.pedersen.commit(&gamma_i);
+    let comm_pair = self.pedersen.commit_with_rng(gamma_i.expose(), &mut self.rng);
+    self.k_i = Some(k_i);
+    self.gamma_i = Some(gamma_i);
     self.dec_Gamma_i = Some(comm_pair.decomm);
 
     // broadcast Com(Gamma_i)
@@ -255,98 +303,104 @@ impl Signer {
 
   pub async fn run_phase_2_Player_A(
     &mut self,
-  ) {
+  ) -> Result<(), String> {
     // MtA 1: k_A * gamma B
-    let k_A = &self.k_i.unwrap().clone();
-    let k_A_gamma_B: Scalar = self.perfrom_mta_as_alice(
+    let k_A = &self.k_i.clone().unwrap();
+    let k_A_gamma_B = self.perfrom_mta_as_alice(
       &SignerId::A,
-      k_A
-    ).await.into(); 
+      k_A.expose(),
+    ).await?;
 
     // MtA 2: k_B * gamma A
-    let gamma_A = &self.gamma_i.unwrap().clone();
+    let gamma_A = &self.gamma_i.clone().unwrap();
     self.perfrom_MtA_as_Bob(
       &SignerId::A,
-      gamma_A,
-    ).await;
+      gamma_A.expose(),
+    ).await?;
 
     // MtA 3: k_A * omega B
-    let k_A_omega_B: Scalar = self.perfrom_mta_as_alice(
+    let k_A_omega_B = self.perfrom_mta_as_alice(
       &SignerId::A,
-      k_A,
-    ).await.into(); 
+      k_A.expose(),
+    ).await?;
 
     let omega_A = &self.omega_i.clone();
 
     // MtA 4: k_B * omega A
     self.perfrom_MtA_as_Bob(
       &SignerId::A,
-      omega_A,
-    ).await;
+      omega_A.expose(),
+    ).await?;
 
     let delta_A = k_A * gamma_A + &k_A_gamma_B;
     let sigma_A = k_A * omega_A + &k_A_omega_B;
 
-    self.delta_i = Some(delta_A);
+    self.delta_i = Some(*delta_A.expose());
     self.sigma_i = Some(sigma_A);
+
+    Ok(())
   }
 
   pub async fn run_phase_2_Player_B(
     &mut self,
-  ) {
+  ) -> Result<(), String> {
     // MtA 1: k_A * gamma B
-    let gamma_B = &self.gamma_i.unwrap().clone();
+    let gamma_B = &self.gamma_i.clone().unwrap();
     self.perfrom_MtA_as_Bob(
       &SignerId::B,
-      gamma_B,
-    ).await;
+      gamma_B.expose(),
+    ).await?;
 
     // MtA 2: k_B * gamma A
-    let k_B = &self.k_i.unwrap().clone();
+    let k_B = &self.k_i.clone().unwrap();
     let k_B_gamma_A = self.perfrom_mta_as_alice(
       &SignerId::B,
-      k_B,
-    ).await; 
+      k_B.expose(),
+    ).await?; 
 
     let omega_B = &self.omega_i.clone();
 
     // MtA 3: k_A * omega B
     self.perfrom_MtA_as_Bob(
       &SignerId::B,
-      omega_B,
-    ).await;
+      omega_B.expose(),
+    ).await?;
 
     // MtA 4: k_B * omega A
     let k_B_omega_A = self.perfrom_mta_as_alice(
       &SignerId::B,
-      k_B,
-    ).await; 
+      k_B.expose(),
+    ).await?; 
 
     let delta_B = k_B * gamma_B + &k_B_gamma_A;
     let sigma_B = k_B * omega_B + &k_B_omega_A;
 
-    self.delta_i = Some(delta_B);
+    self.delta_i = Some(*delta_B.expose());
     self.sigma_i = Some(sigma_B);
+
+    Ok(())
   }
 
-  pub async fn run_phase_3(&mut self) {
-    let delta_i = self.delta_i.unwrap().clone();
+  pub async fn run_phase_3(&mut self) -> Result<(), String> {
+    let delta_i = self.delta_i.unwrap();
 
     // broadcast delta_i
     self.network.broadcast( &DELTA_I_BCAST, &delta_i).await;
 
     // retrieve delta_is to construct delta
-    let delta_is: Vec<Scalar> = 
-      self.network.receive_broadcasts(&DELTA_I_BCAST).await;
+    let delta_is: Vec<C::Scalar> = 
+      self.network.receive_broadcasts(&DELTA_I_BCAST).await?;
 
-    let delta = delta_is.iter().fold(Scalar::zero(), |acc, x| acc + x);
+    let delta: C::Scalar = delta_is.iter().sum();
     self.delta = Some(delta);
+
+    Ok(())
   }
 
   pub async fn run_phase_4(&mut self) -> Result<(), String> {
     // retrieve Com(Gamma_i) from broadcast
-    let com_Gamma_is: Vec<JacobianPoint> =
-      self.network.receive_idx_broadcasts(&COM_GAMMA_I_BCAST).await;
+    let com_Gamma_is: Vec<C::Point> =
+      self.network.receive_idx_broadcasts(&COM_GAMMA_I_BCAST).await?;
 
     // broadcasst Decommitment of Com(Gamma_i)
     self.network.broadcast_with_index(
@@ -356,8 +410,8 @@ impl Signer {
     ).await;
     
     // retrieve decommitment of Com(Gamma_i)s
-    let dec_Gamma_is: Vec<Decommitment> = 
-      self.network.receive_idx_broadcasts(&DEC_GAMMA_I_BCAST).await;
+    let dec_Gamma_is: Vec<Decommitment<C>> = 
+      self.network.receive_idx_broadcasts(&DEC_GAMMA_I_BCAST).await?;
 
     // verify decommitment of Com(Gamma_i)
     if !self.pedersen.verify_vec(&com_Gamma_is, &dec_Gamma_is) {
@@ -367,20 +421,21 @@ impl Signer {
     // TODO prove that the party know gamma_i using zk proof
 
     // compute Gamma
-    let Gamma: JacobianPoint = dec_Gamma_is.iter().fold(
-      JacobianPoint::point_at_infinity(),
-      |acc, decomm| acc + self.pedersen.g * decomm.secret
+    // sum of gamma_i * G = (sum of gamma_i) * G
+    let Gamma = C::mul_base(
+      &PedersenCommitment::aggr_secrets(&dec_Gamma_is),
     );
 
     let delta_inv = self.delta.unwrap().inv();
-    let R = Gamma * &delta_inv;
-    let r: Scalar = R.to_affine().x().into();
+    let R = Gamma * delta_inv;
+    let r = C::x_to_scalar(&R);
 
     // if r is 0, start over
     if r.is_zero() {
       return Err("r is zero".to_string());
     }
     self.r = Some(r);
+    self.recovery_id = Some(C::recovery_id(&R));
 
     Ok(())
   }
@@ -389,12 +444,12 @@ impl Signer {
     let k_i = self.k_i.as_ref().unwrap();
     let sigma_i = self.sigma_i.as_ref().unwrap();
     let r = self.r.as_ref().unwrap();
-    let m = (self.hasher)(&self.M);
+    let m = self.m;
 
-    let s_i = m * k_i + r * sigma_i;
+    let s_i = *(k_i * &m + sigma_i * r).expose();
 
     // broadcast Com(S_i)
-    let s_i_comm_pair = self.pedersen.commit(&s_i);
+    let s_i_comm_pair = self.pedersen.commit_with_rng(&s_i, &mut self.rng);
     self.network.broadcast_with_index(
       &COM_S_I_BCAST,
       &self.signer_id,
@@ -402,8 +457,8 @@ impl Signer {
     ).await;
 
     // retrieve Com(S_i)s
-    let com_S_is: Vec<JacobianPoint> =
-      self.network.receive_idx_broadcasts(&COM_S_I_BCAST).await;
+    let com_S_is: Vec<C::Point> =
+      self.network.receive_idx_broadcasts(&COM_S_I_BCAST).await?;
 
     // broadcast Decommitment of Com(S_i) w/ index
     self.network.broadcast_with_index(
@@ -413,8 +468,8 @@ impl Signer {
     ).await;
 
     // retrieve decommitment of Com(S_i)s
-    let dec_S_is: Vec<Decommitment> = 
-      self.network.receive_idx_broadcasts(&DEC_S_I_BCAST).await;
+    let dec_S_is: Vec<Decommitment<C>> = 
+      self.network.receive_idx_broadcasts(&DEC_S_I_BCAST).await?;
 
     // verify decommitment of Com(S_i)
     if !self.pedersen.verify_vec(&com_S_is, &dec_S_is) {
@@ -429,35 +484,35 @@ impl Signer {
 
   // i and j are evaluation points assigned to players
   // e.g. player i uses evaluation point i+1
-  fn calc_lambda_i_j(i: usize, j: usize) -> Scalar {
+  fn calc_lambda_i_j(i: usize, j: usize) -> C::Scalar {
     assert!(i < j);
     let x = j / (j - i);
-    Scalar::from(x)
+    C::Scalar::from(x as u64)
   }
  
   // i and j are evaluation points assigned to players
   // e.g. player i uses evaluation point i+1
-  fn calc_lambda_j_i(i: i32, j: i32) -> Scalar {
+  fn calc_lambda_j_i(i: i32, j: i32) -> C::Scalar {
     assert!(i < j);
     let x = (i - j) * -1;  // i - j is always negative
-    Scalar::from(x as usize).neg()
+    -C::Scalar::from(x as u64)
   }
 
   pub async fn create_signature(
     &mut self,
-  ) -> Result<Signature, String> {
+  ) -> Result<Signature<C>, String> {
     // Phase 1
     self.run_phase_1().await;
 
     // Phase 2
     if self.signer_id == SignerId::A {
-      self.run_phase_2_Player_A().await
+      self.run_phase_2_Player_A().await?
     } else {
-      self.run_phase_2_Player_B().await
+      self.run_phase_2_Player_B().await?
     }
 
     // Phase 3
-    self.run_phase_3().await;
+    self.run_phase_3().await?;
 
     // Phase 4
     self.run_phase_4().await?;
@@ -465,10 +520,14 @@ impl Signer {
     // Phase 5
     self.run_phase_5().await?;
 
-    let sig = Signature::new(
-      &self.r.as_ref().unwrap(),
-      &self.s.as_ref().unwrap(),
+    let mut sig = Signature::new_recoverable(
+      self.r.as_ref().unwrap(),
+      self.s.as_ref().unwrap(),
+      self.recovery_id.unwrap(),
     );
+    if self.low_s {
+      sig.normalize_s();
+    }
     Ok(sig)
   }
 }
@@ -482,18 +541,29 @@ mod tests {
   };
   use std::sync::Arc;
   use crate::{
-    building_block::util::bitcoin_hasher,
-    protocols::gg18::{
-      key_generator::KeyGenerator,
-      paillier::Paillier,
-   },
+    building_block::p256::curve::P256,
+    protocols::{
+      bitcoin::{
+        address::Address as BitcoinAddress,
+        psbt::{Psbt, PsbtInput, PsbtOutput},
+        transaction::{OutPoint, TxOut},
+      },
+      ethereum::{
+        address::Address,
+        transaction::{Eip1559Transaction, Transaction},
+      },
+      gg18::{
+        key_generator::KeyGenerator,
+        paillier::Paillier,
+      },
+    },
   };
   use rug::ops::Pow;
 
-  async fn generate_keys(
+  async fn generate_keys<C: Curve>(
     n: &Integer,
     num_generators: usize,
-  ) -> Result<Vec<KeyGenerator>, String> {
+  ) -> Result<Vec<KeyGenerator<C>>, String> {
     let network = Arc::new(Network::new(num_generators));
     let pedersen = Arc::new(PedersenCommitment::new());
 
@@ -509,7 +579,7 @@ mod tests {
         Arc::clone(&pedersen),
       );
 
-      let handle: JoinHandle<Result<KeyGenerator, String>> = tokio::spawn(async move {
+      let handle: JoinHandle<Result<KeyGenerator<C>, String>> = tokio::spawn(async move {
         let mut gen = generator;
         gen.generate_key().await?;
         Ok(gen)
@@ -526,60 +596,69 @@ mod tests {
     Ok(generators)
   }
 
-  #[tokio::test]
-  async fn test_signing() {
+  // runs key generation with 3 generators and returns the public key and
+  // the shards of generator 1 and 2 weighted with the lagrange coefficients
+  async fn generate_shards<C: Curve>() -> (C::Point, C::Scalar, C::Scalar) {
     let num_generators = 3;
 
     // group/field order for the secrets 
-    let ss_order = secp256k1_group_order();
+    let ss_order = C::order();
 
     // requirement: n > {ss_order}^8
     let n = &ss_order.clone().pow(8u32);
 
-    // sign with 2 key shard sets from generator 1 and 2
-    let num_signers = 2;
-    let network = Arc::new(Network::new(num_signers));
-    let pedersen = PedersenCommitment::new();
-
-    // message M to sign in Z_n 
-    let M = Scalar::rand();
-
     let _ = Paillier::gen_p_q(&ss_order);
 
     // calculate pk and omegas with lagrange interpolation
-    let lambda_1_2 = Signer::calc_lambda_i_j(1, 2);
-    let lambda_2_1 = Signer::calc_lambda_j_i(1, 2);
-    assert_eq!(lambda_1_2, Scalar::from(2u8));
-    assert_eq!(lambda_2_1, Scalar::from(1u8).neg());
+    let lambda_1_2 = Signer::<C>::calc_lambda_i_j(1, 2);
+    let lambda_2_1 = Signer::<C>::calc_lambda_j_i(1, 2);
+    assert_eq!(lambda_1_2, C::Scalar::from(2u64));
+    assert_eq!(lambda_2_1, -C::Scalar::from(1u64));
 
     // generate key shard sets 
-    let generators = generate_keys(n, num_generators).await.unwrap();
+    let generators = generate_keys::<C>(n, num_generators).await.unwrap();
 
-    let omega_1 = lambda_1_2 * generators[0].x_i.unwrap();
-    let omega_2 = lambda_2_1 * generators[1].x_i.unwrap();
+    let omega_1 = lambda_1_2 * *generators[0].x_i.as_ref().unwrap().expose();
+    let omega_2 = lambda_2_1 * *generators[1].x_i.as_ref().unwrap().expose();
 
     let pk = 
       generators[0].X_i.unwrap() * lambda_1_2 +
       generators[1].X_i.unwrap() * lambda_2_1;
 
     // confirm that pk and omegas are consistent
-    assert!(pk == JacobianPoint::get_base_point() * (omega_1 + omega_2));
+    assert!(pk == C::mul_base(&(omega_1 + omega_2)));
+
+    (pk, omega_1, omega_2)
+  }
+
+  // signs msg with 2 signers holding omega_1 and omega_2. returns the
+  // signatures of both signers
+  async fn sign_with_shards<C: Curve>(
+    msg: &[u8],
+    digest: MessageDigest,
+    omega_1: &C::Scalar,
+    omega_2: &C::Scalar,
+  ) -> Vec<Signature<C>> {
+    // sign with 2 key shard sets from generator 1 and 2
+    let num_signers = 2;
+    let network = Arc::new(Network::new(num_signers));
+    let pedersen = PedersenCommitment::new();
 
     let mut signer_a = Signer::new(
       SignerId::A,
       Arc::clone(&network),
-      pedersen.clone(),
-      &M,
-      Box::new(bitcoin_hasher),
-      &omega_1,
+      pedersen,
+      msg,
+      digest,
+      omega_1,
     );
     let mut signer_b = Signer::new(
       SignerId::B,
       Arc::clone(&network),
       pedersen,
-      &M,
-      Box::new(bitcoin_hasher),
-      &omega_2,
+      msg,
+      digest,
+      omega_2,
     );
     
     let handles = vec![
@@ -591,16 +670,113 @@ mod tests {
       }),
     ];
 
-    let sigs: Vec<_> = futures::future::join_all(handles).await
+    futures::future::join_all(handles).await
       .into_iter()
       .map(|res| res.unwrap())
-      .collect();
+      .collect()
+  }
+
+  // runs key generation and signs msg with 2 of the shards.
+  // returns the public key and the signatures of both signers
+  async fn sign<C: Curve>(
+    msg: &[u8],
+    digest: MessageDigest,
+  ) -> (C::Point, Vec<Signature<C>>) {
+    let (pk, omega_1, omega_2) = generate_shards::<C>().await;
+    let sigs = sign_with_shards::<C>(msg, digest, &omega_1, &omega_2).await;
+    (pk, sigs)
+  }
+
+  #[tokio::test]
+  async fn test_signing() {
+    let msg = b"threshold signature";
+    let (pk, sigs) = sign::<Secp256k1>(msg, MessageDigest::DoubleSha256).await;
 
     let is_sig_valid = sigs[0].verify(
       &pk,
-      &M,
-      bitcoin_hasher,
+      msg,
+      MessageDigest::DoubleSha256,
     );
     assert!(is_sig_valid);
+    assert!(!sigs[0].verify(&pk, msg, MessageDigest::Sha256));
+    assert!(sigs[0].is_low_s());
+    assert_eq!(sigs[0].recover(msg, MessageDigest::DoubleSha256).unwrap(), pk);
+
+    // the same signature over a digest computed by the caller
+    let digest = MessageDigest::DoubleSha256.digest(msg);
+    assert!(sigs[0].verify(&pk, &digest, MessageDigest::Prehashed));
+  }
+
+  #[tokio::test]
+  async fn test_signing_ethereum_transaction() {
+    let tx = Transaction::Eip1559(Eip1559Transaction {
+      chain_id: 1,
+      nonce: 0,
+      max_priority_fee_per_gas: Integer::from(1_000_000_000u64),
+      max_fee_per_gas: Integer::from(100_000_000_000u64),
+      gas_limit: 21000,
+      to: Some(Address([0x35; 20])),
+      value: Integer::from(1),
+      data: vec![],
+      access_list: vec![],
+    });
+    let (pk, sigs) = sign::<Secp256k1>(&tx.sighash().unwrap(), MessageDigest::Prehashed).await;
+
+    assert!(tx.encode_signed(&sigs[0]).is_ok());
+    assert_eq!(tx.recover_sender(&sigs[0]).unwrap(), Address::from_public_key(&pk).unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_signing_psbt() {
+    let (pk, omega_1, omega_2) = generate_shards::<Secp256k1>().await;
+    let script_pubkey = BitcoinAddress::p2wpkh(&pk).unwrap().script_pubkey();
+    let mut psbt = Psbt {
+      version: 2,
+      tx_version: 2,
+      fallback_locktime: None,
+      inputs: (0..2).map(|vout| PsbtInput {
+        previous_output: OutPoint { txid: [0x11; 32], vout },
+        witness_utxo: Some(TxOut { value: 50_000, script_pubkey: script_pubkey.clone() }),
+        ..Default::default()
+      }).collect(),
+      outputs: vec![PsbtOutput { amount: 90_000, script: script_pubkey.clone(), ..Default::default() }],
+      unknown: Default::default(),
+    };
+
+    // each input is signed in a signing session of its own
+    let signed = psbt.sign_inputs(&pk, |_, sighash| async move {
+      let mut sigs = sign_with_shards::<Secp256k1>(&sighash, MessageDigest::Prehashed, &omega_1, &omega_2).await;
+      Ok(sigs.remove(0))
+    }).await.unwrap();
+    assert_eq!(signed, (vec![0, 1], vec![]));
+
+    let psbt = Psbt::decode(&psbt.encode()).unwrap();
+    for index in 0..2 {
+      let value = &psbt.inputs[index].partial_sigs[&pk.to_sec1(true)];
+      assert_eq!(value.last(), Some(&0x01));
+      let sig: Signature = Signature::from_der(&value[..value.len() - 1]).unwrap();
+      assert!(sig.verify(&pk, &psbt.sighash(index).unwrap(), MessageDigest::Prehashed));
+    }
+  }
+
+  #[tokio::test]
+  async fn test_signing_p256() {
+    use p256::ecdsa::{
+      signature::Verifier,
+      Signature as EcdsaSignature,
+      VerifyingKey,
+    };
+
+    let msg = b"threshold signature";
+    let (pk, sigs) = sign::<P256>(msg, MessageDigest::Sha256).await;
+    assert!(sigs[0].verify(&pk, msg, MessageDigest::Sha256));
+    assert_eq!(sigs[0].r, sigs[1].r);
+    assert_eq!(sigs[0].s, sigs[1].s);
+
+    // the threshold signature verifies as a plain ECDSA/P-256 signature
+    let vk = VerifyingKey::from_sec1_bytes(&pk.to_sec1(false)).unwrap();
+    let sig = EcdsaSignature::from_scalars(sigs[0].r.to_bytes(), sigs[0].s.to_bytes()).unwrap();
+    assert!(vk.verify(msg, &sig).is_ok());
+    assert_eq!(sigs[0].recover(msg, MessageDigest::Sha256).unwrap(), pk);
   }
 }
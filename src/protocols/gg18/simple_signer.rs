@@ -2,75 +2,220 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
+// single-party ECDSA with deterministic nonces of RFC 6979.
+// serves as a reference to cross-check the threshold signer against
+
+use std::marker::PhantomData;
 use crate::{
-  building_block::secp256k1::{
-    jacobian_point::JacobianPoint,
-    scalar::Scalar,
+  building_block::{
+    curve::{
+      Curve,
+      CurveScalar,
+    },
+    message_digest::MessageDigest,
+    secp256k1::curve::Secp256k1,
   },
   protocols::gg18::signature::Signature,
 };
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac_sha256(key: &[u8; 32], chunks: &[&[u8]]) -> [u8; 32] {
+  let mut mac = HmacSha256::new_from_slice(key).unwrap();
+  for chunk in chunks {
+    mac.update(chunk);
+  }
+  mac.finalize().into_bytes().into()
+}
+
+// HMAC_DRBG of RFC 6979 section 3.2 instantiated with HMAC-SHA256
+struct NonceGenerator {
+  k: [u8; 32],
+  v: [u8; 32],
+}
+
+impl NonceGenerator {
+  // extra_entropy is appended to the seed as k' of section 3.6
+  fn new(x: &[u8; 32], h: &[u8; 32], extra_entropy: Option<&[u8; 32]>) -> Self {
+    let extra: &[u8] = match extra_entropy {
+      Some(extra) => extra,
+      None => &[],
+    };
+    let mut gen = NonceGenerator {
+      k: [0u8; 32],
+      v: [1u8; 32],
+    };
+    for tag in [0u8, 1] {
+      gen.k = hmac_sha256(&gen.k, &[&gen.v, &[tag], x, h, extra]);
+      gen.v = hmac_sha256(&gen.k, &[&gen.v]);
+    }
+    gen
+  }
+
+  // next k in [1, n-1]
+  fn next<S: CurveScalar>(&mut self) -> S {
+    loop {
+      self.v = hmac_sha256(&self.k, &[&self.v]);
+      let k = S::from_bytes_checked(&self.v).ok().filter(|k| !k.is_zero());
 
-struct SimpleSigner();
+      // K and V are updated before the next candidate is generated
+      self.k = hmac_sha256(&self.k, &[&self.v, &[0]]);
+      self.v = hmac_sha256(&self.k, &[&self.v]);
+      if let Some(k) = k {
+        return k;
+      }
+    }
+  }
+}
+
+impl Drop for NonceGenerator {
+  fn drop(&mut self) {
+    self.k.zeroize();
+    self.v.zeroize();
+  }
+}
 
-impl SimpleSigner {
+pub struct SimpleSigner<C: Curve = Secp256k1>(PhantomData<C>);
+
+impl<C: Curve> SimpleSigner<C> {
   pub fn sign(
-    k: &Scalar, // k in GG18
-    hasher: impl Fn(&Scalar) -> Scalar,
-    M: &Scalar,
-    x: &Scalar, // x in GG18
-  ) -> Signature {
-    let m = hasher(M);
-
-    let gamma = Scalar::from(15u32);
-    let delta = k * &gamma;
-    //println!("====> delta: {:?}", &delta);
-    let Gamma = JacobianPoint::get_base_point() * &gamma;
-    //println!("====> Gamma: {:?}", &Gamma);
-
-    let R = (&Gamma * delta.inv()).to_affine();
-    let r: Scalar = R.x().into();
-    println!("====> r: {:?}", &r);
-    println!("====> R: {:?}", &R);
-    println!("====> m: {:?}", &m);
-    println!("====> k: {:?}", &k);
-    println!("====> r: {:?}", &r);
-
-    let kx = k * x; // = sigma
-    //println!("====> kx: {:?}", &kx);
-
-    let s = m * k + r * kx;
-    println!("====> s: {:?}", &s);
-
-    Signature::new(&r, &s)
+    msg: &[u8],
+    digest: MessageDigest,
+    x: &C::Scalar, // private key
+  ) -> Result<Signature<C>, String> {
+    Self::sign_with_extra_entropy(msg, digest, x, None)
+  }
+
+  // k is derived from x and m = bits2int(digest(msg)) mod n by RFC 6979.
+  // the signature is normalized to low-S and carries the recovery id
+  pub fn sign_with_extra_entropy(
+    msg: &[u8],
+    digest: MessageDigest,
+    x: &C::Scalar, // private key
+    extra_entropy: Option<&[u8; 32]>,
+  ) -> Result<Signature<C>, String> {
+    if x.is_zero() {
+      return Err("Private key should not be zero".to_string());
+    }
+    let m: C::Scalar = digest.to_scalar(msg);
+
+    let mut x_bytes = x.to_bytes();
+    let mut gen = NonceGenerator::new(&x_bytes, &m.to_bytes(), extra_entropy);
+    x_bytes.zeroize();
+
+    loop {
+      let mut k: C::Scalar = gen.next();
+      let R = C::mul_base_secret(&k);
+      let r = C::x_to_scalar(&R);
+      let s = k.inv() * (m + r * *x);
+      k.zeroize();
+
+      // the probability is negligible, but RFC 6979 continues with the next k
+      if r.is_zero() || s.is_zero() {
+        continue;
+      }
+      let mut sig = Signature::new_recoverable(&r, &s, C::recovery_id(&R));
+      sig.normalize_s();
+      return Ok(sig);
+    }
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::building_block::util::bitcoin_hasher;
+  use crate::building_block::{
+    p256::curve::P256,
+    secp256k1::scalar::Scalar,
+  };
+
+  fn scalar<C: Curve>(s: &str) -> C::Scalar {
+    let buf: [u8; 32] = hex::decode(s).unwrap().try_into().unwrap();
+    C::Scalar::from_bytes_checked(&buf).unwrap()
+  }
+
+  // signs msg with SHA-256 and compares with (r, s) normalized to low-S
+  fn check<C: Curve>(x: &str, msg: &str, r: &str, s: &str) {
+    let x = scalar::<C>(x);
+    let msg = msg.as_bytes();
+
+    let sig = SimpleSigner::<C>::sign(msg, MessageDigest::Sha256, &x).unwrap();
+    let mut expected = Signature::<C>::new(&scalar::<C>(r), &scalar::<C>(s));
+    expected.normalize_s();
+    assert_eq!(sig.r, expected.r);
+    assert_eq!(sig.s, expected.s);
+    assert!(sig.is_low_s());
+
+    let pk = C::mul_base(&x);
+    assert!(sig.verify(&pk, msg, MessageDigest::Sha256));
+    assert_eq!(sig.recover(msg, MessageDigest::Sha256).unwrap(), pk);
+
+    // signing the digest computed by the caller yields the same signature
+    let digest = MessageDigest::Sha256.digest(msg);
+    let sig2 = SimpleSigner::<C>::sign(&digest, MessageDigest::Prehashed, &x).unwrap();
+    assert_eq!(sig2.to_bytes(), sig.to_bytes());
+  }
+
+  // RFC 6979 A.2.5
+  #[test]
+  fn test_rfc6979_p256() {
+    let x = "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721";
+    check::<P256>(
+      x,
+      "sample",
+      "EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716",
+      "F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8",
+    );
+    check::<P256>(
+      x,
+      "test",
+      "F1ABB023518351CD71D881567B1EA663ED3EFCF6C5132B354F28D3B0B7D38367",
+      "019F4113742A2B14BD25926B49C649155F267E60D3814B4C0CC84250E46F0083",
+    );
+  }
+
+  #[test]
+  fn test_rfc6979_secp256k1() {
+    check::<Secp256k1>(
+      "0000000000000000000000000000000000000000000000000000000000000001",
+      "Satoshi Nakamoto",
+      "934B1EA10A4B3C1757E2B0C017D0B6143CE3C9A7E6A4A49860D7A6AB210EE3D8",
+      "2442CE9D2B916064108014783E923EC36B49743E2FFA1C4496F01A512AAFD9E5",
+    );
+    check::<Secp256k1>(
+      "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364140",
+      "Satoshi Nakamoto",
+      "FD567D121DB66E382991534ADA77A6BD3106F0A1098C231E47993447CD6AF2D0",
+      "6B39CD0EB1BC8603E159EF5C20A5C8AD685A45B06CE9BEBED3F153D10D93BED5",
+    );
+  }
 
   #[test]
-  pub fn test_sign_verify() {
-    let M = Scalar::from(123u32);
+  fn test_sign_verify() {
+    let msg = b"message";
     let x = Scalar::from(10u32);
-    let pk = JacobianPoint::get_base_point() * &x;
+    let pk = Secp256k1::mul_base(&x);
 
-    // k = delta in GG18
-    let k = Scalar::from(3u32); // k = k_1 + k_2
+    let sig = SimpleSigner::<Secp256k1>::sign(msg, MessageDigest::DoubleSha256, &x).unwrap();
+    assert!(sig.verify(&pk, msg, MessageDigest::DoubleSha256));
+    assert_eq!(sig.recover(msg, MessageDigest::DoubleSha256).unwrap(), pk);
 
-    let sig = SimpleSigner::sign(
-      &k,
-      bitcoin_hasher,
-      &M,
+    // deterministic without extra entropy
+    let sig2 = SimpleSigner::<Secp256k1>::sign(msg, MessageDigest::DoubleSha256, &x).unwrap();
+    assert_eq!(sig.to_bytes(), sig2.to_bytes());
+
+    let sig3 = SimpleSigner::<Secp256k1>::sign_with_extra_entropy(
+      msg,
+      MessageDigest::DoubleSha256,
       &x,
-    );
-    println!("sig: {:?}", sig);
+      Some(&[1u8; 32]),
+    ).unwrap();
+    assert_ne!(sig.r, sig3.r);
+    assert!(sig3.verify(&pk, msg, MessageDigest::DoubleSha256));
 
-    assert!(sig.verify(
-      &pk,
-      &M,
-      bitcoin_hasher,
-    ));
+    assert!(SimpleSigner::<Secp256k1>::sign(msg, MessageDigest::DoubleSha256, &Scalar::zero()).is_err());
   }
 }
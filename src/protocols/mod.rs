@@ -1,2 +1,4 @@
+mod bitcoin;
+mod ethereum;
 mod gg18;
 mod yao_gc;